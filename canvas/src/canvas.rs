@@ -1,5 +1,22 @@
-pub use crate::color::RGB;
-pub use crate::pixel::Pixel;
+pub use crate::color::{Color, RGB};
+use crate::image_format::{ImageFormat, SaveError};
+pub use crate::pixel::{FirstHit, Pixel};
+use crate::tone_mapping::ToneMapping;
+use exr::prelude::{AnyChannel, AnyChannels, Encoding, FlatSamples, Image, Layer, SmallVec};
+use exr::prelude::{LayerAttributes, WritableImage};
+use image::codecs::bmp::BmpEncoder;
+use image::codecs::hdr::HdrEncoder;
+use image::codecs::jpeg::JpegEncoder;
+use image::codecs::png::PngEncoder;
+use image::codecs::pnm::{PnmEncoder, PnmSubtype, SampleEncoding};
+use image::codecs::tga::TgaEncoder;
+use image::error::{EncodingError, ImageFormatHint};
+use image::{ImageError, Rgb32FImage, RgbImage};
+use std::fs;
+use std::io::{Cursor, Seek, Write};
+
+/// the quality (between 1 and 100) JPEG images are saved with
+const JPEG_QUALITY: u8 = 95;
 
 #[derive(Debug, Default)]
 pub struct Canvas {
@@ -12,7 +29,7 @@ pub struct Canvas {
 impl Canvas {
     /// Creates a new image based upon the given `width` and `height` and creates all of the
     /// [`Pixels`] colored white
-    pub fn new(width: u32, height: u32, background_color: RGB) -> Self {
+    pub fn new(width: u32, height: u32, background_color: Color) -> Self {
         let mut pixels: Vec<Pixel> = Vec::new();
 
         for y in 0..height {
@@ -43,6 +60,286 @@ impl Canvas {
     pub fn get_aspect_ratio(&self) -> f64 {
         self.aspect_ratio
     }
+
+    /// converts every pixel's linear color to 8 bit sRGB (row by row) after tone mapping it with
+    /// the given `exposure`, this is the only place where the image loses precision
+    pub fn get_encoded_pixels(&self, tone_mapping: ToneMapping, exposure: f64) -> Vec<RGB> {
+        self.pixels
+            .iter()
+            .map(|pixel| tone_mapping.apply(*pixel.get_color(), exposure).to_srgb())
+            .collect()
+    }
+
+    /// saves the image at the given path, the format is chosen by the path's extension (png,
+    /// jpg/jpeg, tga, bmp, ppm, exr, hdr or pfm)
+    ///
+    /// 8 bit formats are tone mapped with the given `exposure`, high dynamic range ones store the
+    /// linear colors as they are so that exposure and grading can be done later
+    pub fn save(
+        &self,
+        path: &str,
+        tone_mapping: ToneMapping,
+        exposure: f64,
+    ) -> Result<(), SaveError> {
+        let format =
+            ImageFormat::from_path(path).ok_or_else(|| SaveError::UnknownFormat(path.into()))?;
+
+        self.save_with_format(path, format, tone_mapping, exposure)
+    }
+
+    /// saves the image at the given path in the given format (regardless of the path's
+    /// extension), the tone mapping is only applied to 8 bit formats; the file is written only
+    /// once the whole image is encoded, so a failed encoding leaves an existing file untouched
+    pub fn save_with_format(
+        &self,
+        path: &str,
+        format: ImageFormat,
+        tone_mapping: ToneMapping,
+        exposure: f64,
+    ) -> Result<(), SaveError> {
+        let mut writer = Cursor::new(Vec::new());
+
+        if format.is_high_dynamic_range() {
+            self.write_linear(&mut writer, format)?;
+        } else {
+            self.write_encoded(&mut writer, format, tone_mapping, exposure)?;
+        }
+
+        fs::write(path, writer.into_inner())?;
+
+        Ok(())
+    }
+
+    /// writes the tone mapped and sRGB encoded image in an 8 bit format
+    fn write_encoded<W: Write + Seek>(
+        &self,
+        writer: &mut W,
+        format: ImageFormat,
+        tone_mapping: ToneMapping,
+        exposure: f64,
+    ) -> Result<(), SaveError> {
+        let bytes: Vec<u8> = self
+            .get_encoded_pixels(tone_mapping, exposure)
+            .iter()
+            .flat_map(|color| [color.get_red(), color.get_green(), color.get_blue()])
+            .collect();
+        // there is a pixel for every position so the buffer always has the right size
+        let image = RgbImage::from_raw(self.width, self.height, bytes)
+            .expect("the canvas should have a pixel for every position");
+
+        match format {
+            ImageFormat::Png => image.write_with_encoder(PngEncoder::new(writer))?,
+            ImageFormat::Jpeg => {
+                image.write_with_encoder(JpegEncoder::new_with_quality(writer, JPEG_QUALITY))?
+            }
+            ImageFormat::Tga => image.write_with_encoder(TgaEncoder::new(writer))?,
+            ImageFormat::Bmp => image.write_with_encoder(BmpEncoder::new(writer))?,
+            ImageFormat::Ppm => image.write_with_encoder(
+                PnmEncoder::new(writer).with_subtype(PnmSubtype::Pixmap(SampleEncoding::Binary)),
+            )?,
+            ImageFormat::Exr | ImageFormat::Hdr | ImageFormat::Pfm => {
+                unreachable!("high dynamic range formats are written by `write_linear`")
+            }
+        }
+
+        Ok(())
+    }
+
+    /// writes the linear colors in a high dynamic range format
+    fn write_linear<W: Write + Seek>(
+        &self,
+        writer: &mut W,
+        format: ImageFormat,
+    ) -> Result<(), SaveError> {
+        let channels = |pixel: &Pixel| {
+            let color = pixel.get_color();
+
+            [
+                color.get_red() as f32,
+                color.get_green() as f32,
+                color.get_blue() as f32,
+            ]
+        };
+
+        match format {
+            ImageFormat::Exr => self.write_exr(writer)?,
+            ImageFormat::Hdr => {
+                let floats: Vec<f32> = self.pixels.iter().flat_map(channels).collect();
+                let image = Rgb32FImage::from_raw(self.width, self.height, floats)
+                    .expect("the canvas should have a pixel for every position");
+
+                image.write_with_encoder(HdrEncoder::new(writer))?;
+            }
+            ImageFormat::Pfm => {
+                // a negative scale means little endian, rows go from the bottom to the top
+                write!(writer, "PF\n{} {}\n-1.0\n", self.width, self.height)?;
+
+                for row in self.pixels.chunks(self.width.max(1) as usize).rev() {
+                    for pixel in row {
+                        for channel in channels(pixel) {
+                            writer.write_all(&channel.to_le_bytes())?;
+                        }
+                    }
+                }
+            }
+            ImageFormat::Png
+            | ImageFormat::Jpeg
+            | ImageFormat::Tga
+            | ImageFormat::Bmp
+            | ImageFormat::Ppm => {
+                unreachable!("8 bit formats are written by `write_encoded`")
+            }
+        }
+
+        Ok(())
+    }
+
+    /// writes the linear colors in an OpenEXR image, the first hits of the pixels (if any pixel
+    /// has one) are written in the `Z`, `normal` and `albedo` channels; the pixels seeing the
+    /// background are infinitely far away and have neither a normal nor an albedo
+    fn write_exr<W: Write + Seek>(&self, writer: &mut W) -> Result<(), SaveError> {
+        let channel = |name: &str, sample: fn(&Pixel) -> f64| {
+            let samples = self.pixels.iter().map(|pixel| sample(pixel) as f32);
+
+            AnyChannel::new(name, FlatSamples::F32(samples.collect()))
+        };
+
+        let mut channels = vec![
+            channel("R", |pixel| pixel.get_color().get_red()),
+            channel("G", |pixel| pixel.get_color().get_green()),
+            channel("B", |pixel| pixel.get_color().get_blue()),
+        ];
+
+        if self
+            .pixels
+            .iter()
+            .any(|pixel| pixel.get_first_hit().is_some())
+        {
+            channels.extend([
+                channel("Z", |pixel| {
+                    pixel
+                        .get_first_hit()
+                        .map_or(f64::INFINITY, FirstHit::get_depth)
+                }),
+                channel("normal.X", |pixel| {
+                    pixel.get_first_hit().map_or(0.0, |hit| hit.get_normal()[0])
+                }),
+                channel("normal.Y", |pixel| {
+                    pixel.get_first_hit().map_or(0.0, |hit| hit.get_normal()[1])
+                }),
+                channel("normal.Z", |pixel| {
+                    pixel.get_first_hit().map_or(0.0, |hit| hit.get_normal()[2])
+                }),
+                channel("albedo.R", |pixel| {
+                    pixel
+                        .get_first_hit()
+                        .map_or(0.0, |hit| hit.get_albedo().get_red())
+                }),
+                channel("albedo.G", |pixel| {
+                    pixel
+                        .get_first_hit()
+                        .map_or(0.0, |hit| hit.get_albedo().get_green())
+                }),
+                channel("albedo.B", |pixel| {
+                    pixel
+                        .get_first_hit()
+                        .map_or(0.0, |hit| hit.get_albedo().get_blue())
+                }),
+            ]);
+        }
+
+        let layer = Layer::new(
+            (self.width as usize, self.height as usize),
+            LayerAttributes::default(),
+            Encoding::FAST_LOSSLESS,
+            AnyChannels::sort(SmallVec::from_vec(channels)),
+        );
+
+        Image::from_layer(layer)
+            .write()
+            .to_buffered(writer)
+            .map_err(|error| match error {
+                exr::error::Error::Io(error) => SaveError::Io(error),
+                error => SaveError::Encoding(ImageError::Encoding(EncodingError::new(
+                    ImageFormatHint::Exact(image::ImageFormat::OpenExr),
+                    error,
+                ))),
+            })
+    }
+
+    /// creates a new image holding a copy of the `width` x `height` window whose upper left pixel
+    /// is at `x`, `y` (the window is clamped to the image)
+    pub fn get_cropped(&self, x: u32, y: u32, width: u32, height: u32) -> Canvas {
+        let x = x.min(self.width);
+        let y = y.min(self.height);
+        let width = width.min(self.width - x);
+        let height = height.min(self.height - y);
+
+        let mut pixels: Vec<Pixel> = Vec::with_capacity((width as usize) * (height as usize));
+
+        for row in 0..height {
+            let start = ((y + row) as usize) * (self.width as usize) + (x as usize);
+
+            for (column, pixel) in self.pixels[start..(start + width as usize)]
+                .iter()
+                .enumerate()
+            {
+                let mut cropped_pixel = Pixel::new(column as u32, row, *pixel.get_color());
+                cropped_pixel.set_first_hit(pixel.get_first_hit().copied());
+
+                pixels.push(cropped_pixel);
+            }
+        }
+
+        Canvas {
+            width,
+            height,
+            pixels,
+            aspect_ratio: f64::from(width) / f64::from(height),
+        }
+    }
+
+    /// splits the image into disjoint regions of `rows_per_region` full rows each (the last
+    /// region may be smaller), every region can then be modified independently (for example by a
+    /// different thread)
+    pub fn get_regions_mut(&mut self, rows_per_region: u32) -> Vec<CanvasRegion<'_>> {
+        let region_size = (self.width as usize) * (rows_per_region.max(1) as usize);
+
+        if region_size == 0 {
+            return vec![];
+        }
+
+        self.pixels
+            .chunks_mut(region_size)
+            .map(|pixels| CanvasRegion { pixels })
+            .collect()
+    }
+}
+
+/// A disjoint set of full rows of a [`Canvas`] that can be modified on its own
+pub struct CanvasRegion<'a> {
+    pixels: &'a mut [Pixel],
+}
+
+impl CanvasRegion<'_> {
+    /// returns the number of pixels in the region
+    pub fn len(&self) -> usize {
+        self.pixels.len()
+    }
+
+    /// returns true if the region has no pixels
+    pub fn is_empty(&self) -> bool {
+        self.pixels.is_empty()
+    }
+}
+
+impl<'a, 'b> IntoIterator for &'b mut CanvasRegion<'a> {
+    type Item = &'b mut Pixel;
+    type IntoIter = std::slice::IterMut<'b, Pixel>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.pixels.iter_mut()
+    }
 }
 
 impl<'a> IntoIterator for &'a Canvas {
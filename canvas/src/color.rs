@@ -94,3 +94,172 @@ impl ops::Div<u8> for RGB {
         )
     }
 }
+
+/// An object abstracting a linear floating point color (radiance)
+///
+/// Unlike [`RGB`] the channels are not limited to [0, 1], so the lighting calculations never clip
+/// or band; a color is converted to an 8 bit [`RGB`] only when the image is written (after tone
+/// mapping it, see [`crate::ToneMapping`])
+///
+/// # Examples
+///
+/// Convert an 8 bit sRGB color to a linear one, brighten it and convert it back
+///
+/// ```no_run
+/// # use canvas::{Color, RGB};
+/// let color = Color::from(RGB::new(200, 100, 50)) * 1.5;
+///
+/// println!("{:?}", color.to_srgb());
+/// ```
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct Color {
+    red: f64,
+    green: f64,
+    blue: f64,
+}
+
+impl Color {
+    /// creates a new linear color
+    pub fn new(red: f64, green: f64, blue: f64) -> Self {
+        Color { red, green, blue }
+    }
+
+    /// retrieves the color's red value
+    pub fn get_red(&self) -> f64 {
+        self.red
+    }
+
+    /// retrieves the color's green value
+    pub fn get_green(&self) -> f64 {
+        self.green
+    }
+
+    /// retrieves the color's blue value
+    pub fn get_blue(&self) -> f64 {
+        self.blue
+    }
+
+    /// sets the color's red value
+    pub fn set_red(&mut self, new_red: f64) -> &mut Self {
+        self.red = new_red;
+
+        self
+    }
+
+    /// sets the color's green value
+    pub fn set_green(&mut self, new_green: f64) -> &mut Self {
+        self.green = new_green;
+
+        self
+    }
+
+    /// sets the color's blue value
+    pub fn set_blue(&mut self, new_blue: f64) -> &mut Self {
+        self.blue = new_blue;
+
+        self
+    }
+
+    /// returns the color's luminance (how bright it's perceived)
+    pub fn get_luminance(&self) -> f64 {
+        0.2126 * self.red + 0.7152 * self.green + 0.0722 * self.blue
+    }
+
+    /// returns the biggest of the color's channels
+    pub fn get_max_channel(&self) -> f64 {
+        self.red.max(self.green).max(self.blue)
+    }
+
+    /// encodes the color with the sRGB transfer function and converts it to 8 bits, channels
+    /// outside of [0, 1] are clamped so the color should be tone mapped first
+    pub fn to_srgb(&self) -> RGB {
+        RGB::new(
+            encode_srgb(self.red),
+            encode_srgb(self.green),
+            encode_srgb(self.blue),
+        )
+    }
+}
+
+/// converts a linear channel into an 8 bit sRGB one
+fn encode_srgb(channel: f64) -> u8 {
+    let channel = channel.clamp(0.0, 1.0);
+    let encoded = if channel <= 0.0031308 {
+        channel * 12.92
+    } else {
+        1.055 * channel.powf(1.0 / 2.4) - 0.055
+    };
+
+    (encoded * 255.0).round() as u8
+}
+
+/// converts an 8 bit sRGB channel into a linear one
+fn decode_srgb(channel: u8) -> f64 {
+    let channel = f64::from(channel) / 255.0;
+
+    if channel <= 0.04045 {
+        channel / 12.92
+    } else {
+        ((channel + 0.055) / 1.055).powf(2.4)
+    }
+}
+
+impl From<RGB> for Color {
+    /// decodes an 8 bit sRGB color into a linear one
+    fn from(rgb: RGB) -> Self {
+        Color::new(
+            decode_srgb(rgb.red),
+            decode_srgb(rgb.green),
+            decode_srgb(rgb.blue),
+        )
+    }
+}
+
+impl ops::Mul<f64> for Color {
+    type Output = Self;
+
+    fn mul(self, rhs: f64) -> Self::Output {
+        Color::new(self.red * rhs, self.green * rhs, self.blue * rhs)
+    }
+}
+
+impl ops::Mul<Color> for Color {
+    type Output = Self;
+
+    /// multiplies the colors channel by channel (for example to filter a light trough a surface)
+    fn mul(self, rhs: Color) -> Self::Output {
+        Color::new(
+            self.red * rhs.red,
+            self.green * rhs.green,
+            self.blue * rhs.blue,
+        )
+    }
+}
+
+impl ops::Div<f64> for Color {
+    type Output = Self;
+
+    fn div(self, rhs: f64) -> Self::Output {
+        Color::new(self.red / rhs, self.green / rhs, self.blue / rhs)
+    }
+}
+
+impl ops::Add<Color> for Color {
+    type Output = Self;
+
+    fn add(self, rhs: Self) -> Self::Output {
+        Color::new(
+            self.red + rhs.red,
+            self.green + rhs.green,
+            self.blue + rhs.blue,
+        )
+    }
+}
+
+impl ops::AddAssign<Color> for Color {
+    fn add_assign(&mut self, rhs: Color) {
+        self.red += rhs.red;
+        self.green += rhs.green;
+        self.blue += rhs.blue;
+    }
+}
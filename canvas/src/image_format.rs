@@ -0,0 +1,127 @@
+use std::fmt;
+use std::io;
+use std::path::Path;
+
+/// An enum containing every image format a [`Canvas`](crate::Canvas) can be saved as
+///
+/// # Examples
+///
+/// Find the format of an output path
+///
+/// ```no_run
+/// # use canvas::ImageFormat;
+/// assert_eq!(ImageFormat::from_path("render.jpg"), Some(ImageFormat::Jpeg));
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum ImageFormat {
+    /// lossless and compressed, the default choice
+    Png,
+    /// lossy, saved with a quality of 95
+    Jpeg,
+    /// an uncompressed truevision targa
+    Tga,
+    /// an uncompressed windows bitmap
+    Bmp,
+    /// a binary portable pixmap (P6)
+    Ppm,
+    /// an OpenEXR image with 32 bit float red, green and blue channels, plus the depth (`Z`), the
+    /// normal (`normal.X`, `.Y`, `.Z`) and the albedo (`albedo.R`, `.G`, `.B`) channels when the
+    /// pixels have a [`FirstHit`](crate::FirstHit)
+    Exr,
+    /// a Radiance RGBE image
+    Hdr,
+    /// a portable float map (3 channels of 32 bit floats)
+    Pfm,
+}
+
+impl ImageFormat {
+    /// retrieves the format with the given name or file extension (case insensitive)
+    pub fn from_extension(extension: &str) -> Option<Self> {
+        match extension.to_ascii_lowercase().as_str() {
+            "png" => Some(ImageFormat::Png),
+            "jpg" | "jpeg" => Some(ImageFormat::Jpeg),
+            "tga" => Some(ImageFormat::Tga),
+            "bmp" => Some(ImageFormat::Bmp),
+            "ppm" => Some(ImageFormat::Ppm),
+            "exr" => Some(ImageFormat::Exr),
+            "hdr" => Some(ImageFormat::Hdr),
+            "pfm" => Some(ImageFormat::Pfm),
+            _ => None,
+        }
+    }
+
+    /// returns true if the format stores the linear (unclamped) colors, such images are neither
+    /// tone mapped nor sRGB encoded
+    pub fn is_high_dynamic_range(&self) -> bool {
+        matches!(self, ImageFormat::Exr | ImageFormat::Hdr | ImageFormat::Pfm)
+    }
+
+    /// retrieves the format matching the extension of the given path
+    pub fn from_path(path: &str) -> Option<Self> {
+        Path::new(path)
+            .extension()
+            .and_then(|extension| extension.to_str())
+            .and_then(Self::from_extension)
+    }
+}
+
+impl fmt::Display for ImageFormat {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let name = match self {
+            ImageFormat::Png => "png",
+            ImageFormat::Jpeg => "jpeg",
+            ImageFormat::Tga => "tga",
+            ImageFormat::Bmp => "bmp",
+            ImageFormat::Ppm => "ppm",
+            ImageFormat::Exr => "exr",
+            ImageFormat::Hdr => "hdr",
+            ImageFormat::Pfm => "pfm",
+        };
+
+        write!(f, "{}", name)
+    }
+}
+
+/// An error that can happen while saving a [`Canvas`](crate::Canvas)
+#[derive(Debug)]
+pub enum SaveError {
+    /// the format can't be told from the path's extension
+    UnknownFormat(String),
+    /// the file couldn't be created or written
+    Io(io::Error),
+    /// the image couldn't be encoded
+    Encoding(image::ImageError),
+}
+
+impl fmt::Display for SaveError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            SaveError::UnknownFormat(path) => {
+                write!(
+                    f,
+                    "can't tell the image format of '{}' from its extension",
+                    path
+                )
+            }
+            SaveError::Io(error) => write!(f, "couldn't write the image: {}", error),
+            SaveError::Encoding(error) => write!(f, "couldn't encode the image: {}", error),
+        }
+    }
+}
+
+impl std::error::Error for SaveError {}
+
+impl From<io::Error> for SaveError {
+    fn from(error: io::Error) -> Self {
+        SaveError::Io(error)
+    }
+}
+
+impl From<image::ImageError> for SaveError {
+    fn from(error: image::ImageError) -> Self {
+        match error {
+            image::ImageError::IoError(error) => SaveError::Io(error),
+            error => SaveError::Encoding(error),
+        }
+    }
+}
@@ -1,7 +1,11 @@
 mod canvas;
 mod color;
+mod image_format;
 mod pixel;
+mod tone_mapping;
 
-pub use canvas::Canvas;
-pub use color::RGB;
-pub use pixel::Pixel;
+pub use canvas::{Canvas, CanvasRegion};
+pub use color::{Color, RGB};
+pub use image_format::{ImageFormat, SaveError};
+pub use pixel::{FirstHit, Pixel};
+pub use tone_mapping::ToneMapping;
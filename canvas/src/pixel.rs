@@ -1,17 +1,23 @@
-use crate::color::RGB;
+use crate::color::Color;
 
 /// An object abstracting the idea of a pixel on an image
 #[derive(Debug, Clone)]
 pub struct Pixel {
     x: u32,
     y: u32,
-    color: RGB,
+    color: Color,
+    first_hit: Option<FirstHit>,
 }
 
 impl Pixel {
     /// Create a new pixel with a specific position and color
-    pub fn new(x: u32, y: u32, color: RGB) -> Self {
-        Self { x, y, color }
+    pub fn new(x: u32, y: u32, color: Color) -> Self {
+        Self {
+            x,
+            y,
+            color,
+            first_hit: None,
+        }
     }
 
     /// retrieve the x position
@@ -25,14 +31,83 @@ impl Pixel {
     }
 
     /// retrieve the pixel's color
-    pub fn get_color(&self) -> &RGB {
+    pub fn get_color(&self) -> &Color {
         &self.color
     }
 
     /// change the pixel's color
-    pub fn change_color(&mut self, new_color: RGB) -> &mut Self {
+    pub fn change_color(&mut self, new_color: Color) -> &mut Self {
         self.color = new_color;
 
         self
     }
+
+    /// retrieve the first surface seen trough the pixel (if it has been recorded and the pixel
+    /// doesn't see the background)
+    pub fn get_first_hit(&self) -> Option<&FirstHit> {
+        self.first_hit.as_ref()
+    }
+
+    /// change the first surface seen trough the pixel, None means that the pixel sees the
+    /// background
+    pub fn set_first_hit(&mut self, first_hit: Option<FirstHit>) -> &mut Self {
+        self.first_hit = first_hit;
+
+        self
+    }
+}
+
+/// An object holding the first surface seen trough a pixel
+///
+/// Trough the use of this object the renderer can save the depth, the normal and the albedo of
+/// what a pixel sees next to its color, the multi-channel formats (see
+/// [`ImageFormat::Exr`](crate::ImageFormat::Exr)) write them in channels of their own so that
+/// they can be used while compositing (for example by a denoiser)
+///
+/// # Examples
+///
+/// Record that a pixel sees a red surface facing the camera 5 units away
+///
+/// ```no_run
+/// # use canvas::{Color, FirstHit, Pixel};
+/// let mut pixel = Pixel::new(0, 0, Color::new(0.5, 0.0, 0.0));
+///
+/// pixel.set_first_hit(Some(FirstHit::new(
+///     5.0,
+///     [0.0, 0.0, -1.0],
+///     Color::new(1.0, 0.0, 0.0),
+/// )));
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct FirstHit {
+    depth: f64,
+    normal: [f64; 3],
+    albedo: Color,
+}
+
+impl FirstHit {
+    /// creates a new first hit, the `depth` is the distance from the camera along the pixel's
+    /// ray and the `normal` is the (unit) shading normal in world space
+    pub fn new(depth: f64, normal: [f64; 3], albedo: Color) -> Self {
+        Self {
+            depth,
+            normal,
+            albedo,
+        }
+    }
+
+    /// retrieves the distance of the surface from the camera along the pixel's ray
+    pub fn get_depth(&self) -> f64 {
+        self.depth
+    }
+
+    /// retrieves the surface's shading normal (x, y and z in world space)
+    pub fn get_normal(&self) -> [f64; 3] {
+        self.normal
+    }
+
+    /// retrieves the surface's color without any lighting
+    pub fn get_albedo(&self) -> &Color {
+        &self.albedo
+    }
 }
@@ -0,0 +1,61 @@
+use crate::color::Color;
+
+/// An enum defining how the (unbounded) linear colors of the canvas are compressed into [0, 1]
+/// before being encoded to 8 bits
+///
+/// # Examples
+///
+/// Tone map a very bright color
+///
+/// ```no_run
+/// # use canvas::{Color, ToneMapping};
+/// let color = ToneMapping::Reinhard.apply(Color::new(4.0, 2.0, 0.5), 1.0);
+///
+/// println!("{:?}", color.to_srgb());
+/// ```
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub enum ToneMapping {
+    /// every channel is simply clamped to [0, 1]
+    #[default]
+    Clamp,
+    /// every channel `c` becomes `c / (1 + c)`
+    Reinhard,
+    /// the filmic curve fitted to the ACES reference rendering transform by Krzysztof Narkowicz
+    Aces,
+}
+
+impl ToneMapping {
+    /// retrieves the tone mapping with the given name (`clamp`, `reinhard` or `aces`)
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name {
+            "clamp" => Some(ToneMapping::Clamp),
+            "reinhard" => Some(ToneMapping::Reinhard),
+            "aces" => Some(ToneMapping::Aces),
+            _ => None,
+        }
+    }
+
+    /// applies the tone mapping to the color after multiplying it by `exposure`
+    pub fn apply(&self, color: Color, exposure: f64) -> Color {
+        let color = color * exposure;
+
+        Color::new(
+            self.map_channel(color.get_red()),
+            self.map_channel(color.get_green()),
+            self.map_channel(color.get_blue()),
+        )
+    }
+
+    /// tone maps a single channel
+    fn map_channel(&self, channel: f64) -> f64 {
+        let channel = channel.max(0.0);
+
+        match self {
+            ToneMapping::Clamp => channel.min(1.0),
+            ToneMapping::Reinhard => channel / (1.0 + channel),
+            ToneMapping::Aces => ((channel * (2.51 * channel + 0.03))
+                / (channel * (2.43 * channel + 0.59) + 0.14))
+                .clamp(0.0, 1.0),
+        }
+    }
+}
@@ -0,0 +1,451 @@
+use crate::scene_file::IntegratorKind;
+use canvas::{ImageFormat, ToneMapping};
+use std::path::Path;
+
+/// the text printed by `--help`
+const HELP: &str = "\
+Usage: ray_tracer [OPTIONS] [SCENE]
+
+Renders the scene described in the SCENE file (./scenes/demo.scene by default).
+
+Options:
+  -s, --scene <PATH>        the scene file to render (instead of the SCENE argument)
+      --width <PIXELS>      the width of the image (overrides the scene's canvas)
+      --height <PIXELS>     the height of the image (overrides the scene's canvas)
+      --samples <COUNT>     the number of samples per pixel (overrides the scene's samples)
+      --integrator <NAME>   how the light is computed (overrides the scene's integrator)
+                            [possible values: whitted, path]
+      --max-depth <COUNT>   the maximum number of bounces [default: 3 for whitted, 8 for path]
+  -o, --output <PATH>       the image to write [default: output.png]
+      --format <FORMAT>     the format of the image, by default it's taken from the output's
+                            extension [possible values: png, jpeg, tga, bmp, ppm,
+                            exr, hdr, pfm]
+      --tone-mapping <NAME> how the colors are brought into the 8 bit range [default: reinhard]
+                            [possible values: clamp, reinhard, aces]
+      --exposure <FACTOR>   the factor the colors are multiplied by before the tone mapping
+                            [default: 1]
+  -t, --threads <COUNT>     the number of rendering threads [default: every core]
+      --seed <NUMBER>       the seed of the random sampling, a fixed seed renders the same image
+                            regardless of the number of threads [default: random]
+      --crop <X,Y,W,H>      renders (and writes) only the W x H window whose upper left pixel is
+                            at X, Y
+  -h, --help                prints this help
+";
+
+/// A window of the image (in pixels) that is rendered instead of the whole image
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct CropWindow {
+    pub x: u32,
+    pub y: u32,
+    pub width: u32,
+    pub height: u32,
+}
+
+/// The options given on the command line, the `None` ones keep the scene's (or ray tracer's)
+/// values
+#[derive(Debug)]
+pub struct Options {
+    pub scene_path: String,
+    pub width: Option<u32>,
+    pub height: Option<u32>,
+    pub samples: Option<u32>,
+    pub integrator: Option<IntegratorKind>,
+    pub max_depth: Option<u8>,
+    pub output_path: String,
+    pub output_format: ImageFormat,
+    pub tone_mapping: ToneMapping,
+    pub exposure: f64,
+    pub threads: Option<usize>,
+    pub seed: Option<u64>,
+    pub crop: Option<CropWindow>,
+}
+
+/// An enum defining what the user asked for on the command line
+#[derive(Debug)]
+pub enum Command {
+    /// render a scene with the given options
+    Render(Options),
+    /// print the help
+    Help,
+}
+
+impl Command {
+    /// prints the help text
+    pub fn print_help() {
+        print!("{}", HELP);
+    }
+
+    /// parses the command line arguments (without the program name)
+    pub fn parse<I: IntoIterator<Item = String>>(arguments: I) -> Result<Self, String> {
+        let mut arguments = arguments.into_iter();
+
+        let mut scene_path: Option<String> = None;
+        let mut positional_scene: Option<String> = None;
+        let mut width: Option<u32> = None;
+        let mut height: Option<u32> = None;
+        let mut samples: Option<u32> = None;
+        let mut integrator: Option<IntegratorKind> = None;
+        let mut max_depth: Option<u8> = None;
+        let mut output_path: Option<String> = None;
+        let mut output_format: Option<ImageFormat> = None;
+        let mut tone_mapping: Option<ToneMapping> = None;
+        let mut exposure: Option<f64> = None;
+        let mut threads: Option<usize> = None;
+        let mut seed: Option<u64> = None;
+        let mut crop: Option<CropWindow> = None;
+
+        while let Some(argument) = arguments.next() {
+            if !argument.starts_with('-') || argument == "-" {
+                if positional_scene.is_some() {
+                    return Err(format!("unexpected argument '{}'", argument));
+                }
+
+                positional_scene = Some(argument);
+                continue;
+            }
+
+            // both `--flag value` and `--flag=value` are accepted
+            let (flag, inline_value) = match argument.split_once('=') {
+                Some((flag, value)) => (String::from(flag), Some(String::from(value))),
+                None => (argument.clone(), None),
+            };
+
+            if flag == "-h" || flag == "--help" {
+                return Ok(Command::Help);
+            }
+
+            let value = match inline_value.or_else(|| arguments.next()) {
+                Some(value) => value,
+                None => return Err(format!("'{}' needs a value", flag)),
+            };
+
+            match flag.as_str() {
+                "-s" | "--scene" => set_once(&mut scene_path, value, &flag)?,
+                "--width" => set_once(&mut width, parse_positive(&value, &flag)?, &flag)?,
+                "--height" => set_once(&mut height, parse_positive(&value, &flag)?, &flag)?,
+                "--samples" => set_once(&mut samples, parse_positive(&value, &flag)?, &flag)?,
+                "--integrator" => match IntegratorKind::from_name(&value) {
+                    Some(name) => set_once(&mut integrator, name, &flag)?,
+                    None => return Err(format!("unknown integrator '{}'", value)),
+                },
+                "--max-depth" => set_once(&mut max_depth, parse_number(&value, &flag)?, &flag)?,
+                "-o" | "--output" => set_once(&mut output_path, value, &flag)?,
+                "--format" => match ImageFormat::from_extension(&value) {
+                    Some(format) => set_once(&mut output_format, format, &flag)?,
+                    None => return Err(format!("unknown output format '{}'", value)),
+                },
+                "--tone-mapping" => match ToneMapping::from_name(&value) {
+                    Some(name) => set_once(&mut tone_mapping, name, &flag)?,
+                    None => return Err(format!("unknown tone mapping '{}'", value)),
+                },
+                "--exposure" => set_once(&mut exposure, parse_exposure(&value, &flag)?, &flag)?,
+                "-t" | "--threads" => {
+                    set_once(&mut threads, parse_positive(&value, &flag)?, &flag)?
+                }
+                "--seed" => set_once(&mut seed, parse_number(&value, &flag)?, &flag)?,
+                "--crop" => set_once(&mut crop, parse_crop_window(&value)?, &flag)?,
+                _ => return Err(format!("unknown option '{}'", flag)),
+            }
+        }
+
+        if scene_path.is_some() && positional_scene.is_some() {
+            return Err(String::from(
+                "the scene is given both as an argument and with '--scene'",
+            ));
+        }
+
+        let scene_path = scene_path
+            .or(positional_scene)
+            .unwrap_or(String::from("./scenes/demo.scene"));
+        let output_path = output_path.unwrap_or(String::from("output.png"));
+
+        // the format can't contradict the output's extension
+        let extension_format = Path::new(&output_path)
+            .extension()
+            .and_then(|extension| extension.to_str())
+            .map(|extension| (extension, ImageFormat::from_extension(extension)));
+
+        let output_format = match (output_format, extension_format) {
+            (Some(format), Some((extension, Some(extension_format))))
+                if format != extension_format =>
+            {
+                return Err(format!(
+                    "the output format {} contradicts the '.{}' extension of '{}'",
+                    format, extension, output_path
+                ));
+            }
+            (Some(format), _) => format,
+            (None, Some((_, Some(extension_format)))) => extension_format,
+            (None, _) => {
+                return Err(format!(
+                    "can't tell the format of '{}' from its extension, use '--format'",
+                    output_path
+                ));
+            }
+        };
+
+        // the high dynamic range formats store the linear colors
+        if output_format.is_high_dynamic_range() && (tone_mapping.is_some() || exposure.is_some()) {
+            return Err(format!(
+                "the {} format stores the linear colors, it can't be tone mapped",
+                output_format
+            ));
+        }
+
+        // when the size is given on the command line the crop window is checked right away,
+        // otherwise it's checked against the scene's canvas
+        if let Some(crop) = crop {
+            if let Some(width) = width
+                && crop.x + crop.width > width
+            {
+                return Err(format!(
+                    "the crop window ends at x = {} but the image is only {} pixels wide",
+                    crop.x + crop.width,
+                    width
+                ));
+            }
+
+            if let Some(height) = height
+                && crop.y + crop.height > height
+            {
+                return Err(format!(
+                    "the crop window ends at y = {} but the image is only {} pixels high",
+                    crop.y + crop.height,
+                    height
+                ));
+            }
+        }
+
+        Ok(Command::Render(Options {
+            scene_path,
+            width,
+            height,
+            samples,
+            integrator,
+            max_depth,
+            output_path,
+            output_format,
+            tone_mapping: tone_mapping.unwrap_or(ToneMapping::Reinhard),
+            exposure: exposure.unwrap_or(1.0),
+            threads,
+            seed,
+            crop,
+        }))
+    }
+}
+
+/// stores an option's value making sure it's not given twice
+fn set_once<T>(slot: &mut Option<T>, value: T, flag: &str) -> Result<(), String> {
+    if slot.is_some() {
+        return Err(format!("'{}' is given more than once", flag));
+    }
+
+    *slot = Some(value);
+
+    Ok(())
+}
+
+/// parses a number option
+fn parse_number<T: std::str::FromStr>(value: &str, flag: &str) -> Result<T, String> {
+    value
+        .parse::<T>()
+        .map_err(|_| format!("'{}' is not a valid value for '{}'", value, flag))
+}
+
+/// parses a number option that must be bigger than zero
+fn parse_positive<T: std::str::FromStr + Default + PartialOrd>(
+    value: &str,
+    flag: &str,
+) -> Result<T, String> {
+    let number = parse_number::<T>(value, flag)?;
+
+    if number <= T::default() {
+        return Err(format!("'{}' must be bigger than 0", flag));
+    }
+
+    Ok(number)
+}
+
+/// parses the exposure, a finite factor bigger than zero
+fn parse_exposure(value: &str, flag: &str) -> Result<f64, String> {
+    let exposure = parse_positive::<f64>(value, flag)?;
+
+    if !exposure.is_finite() {
+        return Err(format!("'{}' must be a finite number", flag));
+    }
+
+    Ok(exposure)
+}
+
+/// parses a crop window given as `X,Y,WIDTH,HEIGHT`
+fn parse_crop_window(value: &str) -> Result<CropWindow, String> {
+    let numbers = value
+        .split(',')
+        .map(|number| number.trim().parse::<u32>())
+        .collect::<Result<Vec<u32>, _>>()
+        .map_err(|_| format!("'{}' is not a valid crop window (X,Y,WIDTH,HEIGHT)", value))?;
+
+    let [x, y, width, height] = numbers[..] else {
+        return Err(format!(
+            "the crop window needs 4 values (X,Y,WIDTH,HEIGHT), {} given",
+            numbers.len()
+        ));
+    };
+
+    if width == 0 || height == 0 {
+        return Err(String::from("the crop window can't be empty"));
+    }
+
+    if x.checked_add(width).is_none() || y.checked_add(height).is_none() {
+        return Err(format!("the crop window '{}' is too big", value));
+    }
+
+    Ok(CropWindow {
+        x,
+        y,
+        width,
+        height,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn parse(arguments: &[&str]) -> Result<Command, String> {
+        Command::parse(arguments.iter().map(|argument| String::from(*argument)))
+    }
+
+    /// parses arguments that should ask for a render
+    fn options(arguments: &[&str]) -> Options {
+        match parse(arguments) {
+            Ok(Command::Render(options)) => options,
+            Ok(Command::Help) => panic!("{:?} should not ask for the help", arguments),
+            Err(error) => panic!("{:?} should parse, found the error: {}", arguments, error),
+        }
+    }
+
+    /// retrieves the error of arguments that should be rejected
+    fn error(arguments: &[&str]) -> String {
+        match parse(arguments) {
+            Err(error) => error,
+            Ok(command) => panic!("{:?} should be rejected, found {:?}", arguments, command),
+        }
+    }
+
+    #[test]
+    fn defaults() {
+        let options = options(&[]);
+
+        assert_eq!(options.scene_path, "./scenes/demo.scene");
+        assert_eq!(options.output_path, "output.png");
+        assert_eq!(options.output_format, ImageFormat::Png);
+        assert!(options.width.is_none() && options.samples.is_none() && options.crop.is_none());
+    }
+
+    #[test]
+    fn values_can_follow_the_flag_or_an_equal_sign() {
+        let options = options(&["room.scene", "--width", "640", "--height=480", "-o=out.exr"]);
+
+        assert_eq!(options.scene_path, "room.scene");
+        assert_eq!((options.width, options.height), (Some(640), Some(480)));
+        assert_eq!(options.output_format, ImageFormat::Exr);
+    }
+
+    #[test]
+    fn help() {
+        assert!(matches!(parse(&["--help"]), Ok(Command::Help)));
+        assert!(matches!(parse(&["-h"]), Ok(Command::Help)));
+        // the help wins over the other (even wrong) arguments before it
+        assert!(matches!(
+            parse(&["room.scene", "--samples", "4", "-h"]),
+            Ok(Command::Help)
+        ));
+    }
+
+    #[test]
+    fn rejects_contradictory_flags() {
+        assert!(error(&["a.scene", "--scene", "b.scene"]).contains("both"));
+        assert!(error(&["--samples", "4", "--samples", "8"]).contains("more than once"));
+        assert!(error(&["-o", "out.png", "--format", "exr"]).contains("contradicts"));
+        assert!(error(&["a.scene", "b.scene"]).contains("unexpected"));
+    }
+
+    #[test]
+    fn format_and_extension() {
+        // an extension that doesn't name a format needs `--format`
+        assert!(error(&["-o", "out.image"]).contains("--format"));
+        assert_eq!(
+            options(&["-o", "out.image", "--format", "hdr"]).output_format,
+            ImageFormat::Hdr
+        );
+        assert_eq!(
+            options(&["-o", "out.png", "--format", "png"]).output_format,
+            ImageFormat::Png
+        );
+    }
+
+    #[test]
+    fn rejects_invalid_values() {
+        assert!(error(&["--width", "0"]).contains("bigger than 0"));
+        assert!(error(&["--threads", "-2"]).contains("not a valid value"));
+        assert!(error(&["--integrator", "photon"]).contains("unknown integrator"));
+        assert!(error(&["--seed"]).contains("needs a value"));
+        assert!(error(&["--fast", "1"]).contains("unknown option"));
+    }
+
+    #[test]
+    fn parses_the_tone_mapping() {
+        let tone_mapped = options(&["--tone-mapping", "aces", "--exposure", "0.5"]);
+
+        assert_eq!(tone_mapped.tone_mapping, ToneMapping::Aces);
+        assert_eq!(tone_mapped.exposure, 0.5);
+        assert_eq!(options(&[]).tone_mapping, ToneMapping::Reinhard);
+        assert_eq!(options(&[]).exposure, 1.0);
+    }
+
+    #[test]
+    fn rejects_invalid_tone_mappings() {
+        assert!(error(&["--tone-mapping", "filmic"]).contains("unknown tone mapping"));
+        assert!(error(&["--exposure", "0"]).contains("bigger than 0"));
+        assert!(error(&["--exposure", "inf"]).contains("finite"));
+        assert!(error(&["--exposure", "NaN"]).contains("finite"));
+        // the high dynamic range formats aren't tone mapped
+        assert!(error(&["-o", "out.exr", "--exposure", "2"]).contains("can't be tone mapped"));
+        assert!(error(&["-o", "out.hdr", "--tone-mapping", "clamp"]).contains("tone mapped"));
+    }
+
+    #[test]
+    fn parses_the_crop_window() {
+        assert_eq!(
+            options(&["--crop", "10, 20,30,40"]).crop,
+            Some(CropWindow {
+                x: 10,
+                y: 20,
+                width: 30,
+                height: 40
+            })
+        );
+    }
+
+    #[test]
+    fn rejects_invalid_crop_windows() {
+        assert!(error(&["--crop", "1,2,3"]).contains("needs 4 values"));
+        assert!(error(&["--crop", "1,2,3,x"]).contains("not a valid crop window"));
+        assert!(error(&["--crop", "0,0,0,10"]).contains("can't be empty"));
+        assert!(error(&["--crop", "4294967295,0,1,1"]).contains("too big"));
+    }
+
+    #[test]
+    fn checks_the_crop_window_against_the_given_size() {
+        assert!(error(&["--width", "100", "--crop", "50,0,51,10"]).contains("wide"));
+        assert!(error(&["--height", "100", "--crop", "0,90,10,11"]).contains("high"));
+        assert!(
+            options(&["--width", "100", "--height", "100", "--crop", "50,90,50,10"])
+                .crop
+                .is_some()
+        );
+        // without the size the window is checked against the scene's canvas later
+        assert!(options(&["--crop", "5000,5000,10,10"]).crop.is_some());
+    }
+}
@@ -1,164 +1,109 @@
+mod cli;
 mod ray_tracer;
+mod scene_file;
 
 use canvas::*;
-use ray_tracer::RayTracer;
-use std::fs::File;
-use std::io::prelude::Write;
-use world::*;
+use cli::Command;
+use scene_file::SceneDescription;
 
 fn main() {
-    let mut ray_tracer = RayTracer::new(
-        Vec3::new(0.0, 0.0, 0.0),
-        Vec3::new(0.0, 0.0, 1.0),
-        Vec3::new(0.0, 1.0, 0.0),
-        1200,
-        1200,
-        RGB::new(53, 81, 92),
-        2.0,
-        2,
-    );
-
-    // blue right sphere
-    ray_tracer.add_object(Objects::create_object(Objects::Sphere(
-        Vec3::new(1.3, -3.0, 6.0),
-        1.0,
-        MaterialBuilder::default()
-            .set_color(RGB::new(87, 87, 201))
-            .set_reflectiveness(0.9)
-            .build(),
-    )));
-
-    // yellow left sphere
-    ray_tracer.add_object(Objects::create_object(Objects::Sphere(
-        Vec3::new(-1.0, -3.0, 5.0),
-        1.0,
-        MaterialBuilder::default()
-            .set_color(RGB::new(183, 183, 78))
-            .build(),
-    )));
-
-    // world sphere
-    ray_tracer.add_object(Objects::create_object(Objects::Sphere(
-        Vec3::new(-2.0, 1.5, 5.0),
-        0.8,
-        MaterialBuilder::default()
-            .set_texture("./textures/earthmap.jpg")
-            .build(),
-    )));
-
-    // refracted sphere
-    ray_tracer.add_object(Objects::create_object(Objects::Sphere(
-        Vec3::new(2.0, 1.5, 4.0),
-        0.8,
-        MaterialBuilder::default()
-            .set_color(RGB::new(255, 255, 255))
-            .set_refraction(1.55)
-            .set_transparency(1.0)
-            .build(),
-    )));
-
-    // Back panel
-    ray_tracer.add_object(Objects::create_object(Objects::Panel(
-        Vec3::new(0.0, 0.0, 7.0),
-        80.0,
-        80.0,
-        Vec3::new(0.0, 0.0, -1.0),
-        MaterialBuilder::default()
-            .set_color(RGB::new(233, 233, 233))
-            .build(),
-    )));
-
-    // front panel
-    ray_tracer.add_object(Objects::create_object(Objects::Panel(
-        Vec3::new(0.0, 0.0, 0.0),
-        8.0,
-        8.0,
-        Vec3::new(0.0, 0.0, 1.0),
-        MaterialBuilder::default()
-            .set_color(RGB::new(233, 233, 233))
-            .build(),
-    )));
-
-    // bottom panel
-    ray_tracer.add_object(Objects::create_object(Objects::Panel(
-        Vec3::new(0.0, -4.0, 4.0),
-        80.0,
-        80.0,
-        Vec3::new(0.0, 1.0, 0.0),
-        MaterialBuilder::default()
-            .set_color(RGB::new(233, 233, 233))
-            .build(),
-    )));
-
-    // right panel
-    ray_tracer.add_object(Objects::create_object(Objects::Panel(
-        Vec3::new(4.0, 0.0, 4.0),
-        8.0,
-        8.0,
-        Vec3::new(-1.0, 0.0, 0.0),
-        MaterialBuilder::default()
-            .set_color(RGB::new(255, 118, 118))
-            .build(),
-    )));
-
-    // left panel
-    ray_tracer.add_object(Objects::create_object(Objects::Panel(
-        Vec3::new(-4.0, 0.0, 4.0),
-        8.0,
-        8.0,
-        Vec3::new(1.0, 0.0, 0.0),
-        MaterialBuilder::default()
-            .set_color(RGB::new(100, 227, 106))
-            .build(),
-    )));
-
-    // top panel
-    ray_tracer.add_object(Objects::create_object(Objects::Panel(
-        Vec3::new(0.0, 4.0, 4.0),
-        8.0,
-        8.0,
-        Vec3::new(0.0, -1.0, 0.0),
-        MaterialBuilder::default()
-            .set_color(RGB::new(233, 233, 233))
-            .build(),
-    )));
-
-    ray_tracer.add_light(Lights::create_light(Lights::PanelLight(
-        Vec3::new(0.0, 3.9, 4.0),
-        1.0,
-        1.0,
-        Vec3::new(0.0, -1.0, 0.0),
-        2.0,
-        0.10,
-        Some(RGB::new(255, 255, 255)),
-    )));
-
-    ray_tracer.render();
+    let options = match Command::parse(std::env::args().skip(1)) {
+        Ok(Command::Render(options)) => options,
+        Ok(Command::Help) => {
+            Command::print_help();
+            return;
+        }
+        Err(error) => {
+            eprintln!("error: {}\n\nFor more information, try '--help'.", error);
+            std::process::exit(2);
+        }
+    };
 
-    let mut file = match File::create("output.ppm") {
-        Ok(file) => file,
-        Err(error) => panic!("{}", error),
+    let mut scene = match SceneDescription::load(&options.scene_path) {
+        Ok(scene) => scene,
+        Err(error) => {
+            eprintln!(
+                "couldn't load the scene '{}': {}",
+                options.scene_path, error
+            );
+            std::process::exit(1);
+        }
     };
 
-    let canvas = ray_tracer.get_canvas();
+    if let Some(width) = options.width {
+        scene.set_canvas_width(width);
+    }
+
+    if let Some(height) = options.height {
+        scene.set_canvas_height(height);
+    }
 
-    let _ = file.write(b"P3\n");
-    let _ = file.write(format!("{} {}\n", canvas.get_width(), canvas.get_height()).as_bytes());
-    let _ = file.write(b"255\n");
+    if let Some(samples) = options.samples {
+        scene.set_pixel_samples(samples);
+    }
 
-    let gamma_correction_value = 1.0;
+    if let Some(integrator) = options.integrator {
+        scene.set_integrator(integrator);
+    }
+
+    if let Some(max_depth) = options.max_depth {
+        scene.set_max_depth(max_depth);
+    }
 
-    for pixel in canvas {
-        let pixel_color = pixel.get_color();
+    let mut ray_tracer = scene.into_ray_tracer();
 
-        // applying gamma correction
-        let red =
-            ((f64::from(pixel_color.get_red()) / 255.0).powf(gamma_correction_value) * 255.0) as u8;
-        let green = ((f64::from(pixel_color.get_green()) / 255.0).powf(gamma_correction_value)
-            * 255.0) as u8;
-        let blue = ((f64::from(pixel_color.get_blue()) / 255.0).powf(gamma_correction_value)
-            * 255.0) as u8;
+    if let Some(threads) = options.threads {
+        ray_tracer.set_thread_count(threads);
+    }
+
+    // with a fixed seed the image is the same regardless of how many threads render it
+    if let Some(seed) = options.seed {
+        ray_tracer.set_seed(seed);
+    }
+
+    if let Some(crop) = options.crop {
+        // the crop window may only be checked now if the size comes from the scene file
+        let canvas = ray_tracer.get_canvas();
+
+        if crop.x + crop.width > canvas.get_width() || crop.y + crop.height > canvas.get_height() {
+            eprintln!(
+                "error: the crop window {},{},{},{} doesn't fit in the {}x{} image",
+                crop.x,
+                crop.y,
+                crop.width,
+                crop.height,
+                canvas.get_width(),
+                canvas.get_height()
+            );
+            std::process::exit(2);
+        }
+
+        ray_tracer.set_crop_window(crop.x, crop.y, crop.width, crop.height);
+    }
+
+    // the multi-channel formats have room for the first surface seen by every pixel
+    if options.output_format == ImageFormat::Exr {
+        ray_tracer.set_records_first_hits(true);
+    }
+
+    ray_tracer.render();
 
-        let _ = file.write(format!("{} {} {}\n", red, green, blue,).as_bytes());
+    // only the crop window is written
+    let cropped_canvas = options.crop.map(|crop| {
+        ray_tracer
+            .get_canvas()
+            .get_cropped(crop.x, crop.y, crop.width, crop.height)
+    });
+    let canvas = cropped_canvas.as_ref().unwrap_or(ray_tracer.get_canvas());
+
+    // the canvas holds linear colors, they're tone mapped and sRGB encoded only now
+    if let Err(error) = canvas.save_with_format(
+        &options.output_path,
+        options.output_format,
+        options.tone_mapping,
+        options.exposure,
+    ) {
+        eprintln!("couldn't save '{}': {}", options.output_path, error);
+        std::process::exit(1);
     }
 }
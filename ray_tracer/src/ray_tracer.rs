@@ -1,17 +1,27 @@
 #![allow(clippy::too_many_arguments)]
 
 use canvas::*;
+use rand::rngs::StdRng;
+use rand::{Rng, SeedableRng};
+use std::sync::Mutex;
+use std::thread;
 use world::*;
 
+/// the number of canvas rows each rendering thread takes at a time
+const ROWS_PER_REGION: u32 = 8;
+
 pub struct RayTracer {
     camera: Camera,
     canvas: Canvas,
-    background_color: RGB, // until a skybox is implemented the "sky" will be a background color
     viewport_incr_x: f64,
     viewport_incr_y: f64,
-    objects: Vec<Box<dyn Object>>,
-    lights: Vec<Box<dyn Light>>,
+    scene: Scene,
     pixel_samples: u32,
+    thread_count: usize,
+    seed: Option<u64>,
+    integrator: Box<dyn Integrator>,
+    crop_window: Option<(u32, u32, u32, u32)>,
+    records_first_hits: bool,
 }
 
 impl RayTracer {
@@ -22,7 +32,7 @@ impl RayTracer {
         up_vector: Vec3,
         canvas_width: u32,
         canvas_height: u32,
-        background_color: RGB,
+        background_color: Color,
         viewport_width: f64,
         pixel_samples: u32,
     ) -> Self {
@@ -40,101 +50,182 @@ impl RayTracer {
         RayTracer {
             camera,
             canvas,
-            background_color,
             viewport_incr_x,
             viewport_incr_y,
-            objects: vec![],
-            lights: vec![],
+            scene: Scene::new(),
             pixel_samples,
+            thread_count: thread::available_parallelism().map_or(1, |count| count.get()),
+            seed: None,
+            integrator: Integrators::create_integrator(Integrators::Whitted(3, background_color)),
+            crop_window: None,
+            records_first_hits: false,
         }
     }
 
+    /// sets how many threads are used to render the image (by default every core is used)
+    pub fn set_thread_count(&mut self, thread_count: usize) -> &mut Self {
+        self.thread_count = thread_count.max(1);
+
+        self
+    }
+
+    /// sets the seed used for the random sampling, with a fixed seed the rendered image is the
+    /// same regardless of the number of threads
+    pub fn set_seed(&mut self, seed: u64) -> &mut Self {
+        self.seed = Some(seed);
+
+        self
+    }
+
+    /// sets how the light seen by the camera is computed (a Whitted integrator following 3
+    /// reflections or refractions by default)
+    pub fn set_integrator(&mut self, integrator: Box<dyn Integrator>) -> &mut Self {
+        self.integrator = integrator;
+
+        self
+    }
+
+    /// restricts the rendering to the `width` x `height` window of the canvas whose upper left
+    /// pixel is at `x`, `y`; the pixels outside of it keep the background color
+    pub fn set_crop_window(&mut self, x: u32, y: u32, width: u32, height: u32) -> &mut Self {
+        self.crop_window = Some((x, y, width, height));
+
+        self
+    }
+
+    /// sets whether the depth, the normal and the albedo of the first surface seen by every pixel
+    /// are saved in the canvas next to its color (see [`FirstHit`]), it takes a ray more for
+    /// every pixel so it's off by default
+    pub fn set_records_first_hits(&mut self, records_first_hits: bool) -> &mut Self {
+        self.records_first_hits = records_first_hits;
+
+        self
+    }
+
     /// adds a new object in the ray tracer
     pub fn add_object(&mut self, new_object: Box<dyn Object>) {
-        self.objects.push(new_object);
+        self.scene.add_object(new_object);
     }
 
     /// addsa new light in the ray tracer
     pub fn add_light(&mut self, new_light: Box<dyn Light>) {
-        self.lights.push(new_light);
+        self.scene.add_light(new_light);
     }
 
-    /// this functions traces a ray between the starting and end position, returning an RGB color
-    pub fn trace_ray(&self, starting_position: Vec3, end_position: Vec3) -> RGB {
-        let mut final_red: u32 = 0;
-        let mut final_green: u32 = 0;
-        let mut final_blue: u32 = 0;
+    /// this functions traces a ray between the starting and end position, returning the linear
+    /// color (radiance) seen by the ray
+    pub fn trace_ray<R: Rng>(
+        &self,
+        starting_position: Vec3,
+        end_position: Vec3,
+        rng: &mut R,
+    ) -> Color {
+        let mut final_color = Color::new(0.0, 0.0, 0.0);
+
+        // the angle between the rays of two neighbouring pixels
+        let spread = self.viewport_incr_x / (end_position - starting_position).get_length();
 
         for _ in 0..self.pixel_samples {
             let mut ray = Ray::new(starting_position, end_position - starting_position);
-            ray.scatter(
+            ray.set_spread(spread).scatter(
+                rng,
                 Some(-self.viewport_incr_x..self.viewport_incr_x),
                 Some(-self.viewport_incr_y..self.viewport_incr_y),
                 None,
             );
 
-            if let Some(object_intersection) = ObjectRayIntersection::check_intersection(
-                ray,
-                &self.objects,
-                &self.lights,
-                1.0,
-                f64::MAX,
-            ) {
-                if !object_intersection.is_light_hit() {
-                    for light in &self.lights {
-                        let hit_color = light.compute_color(
-                            &object_intersection,
-                            &self.objects,
-                            &self.lights,
-                            3,
-                            self.background_color,
-                        );
-
-                        final_red = final_red.saturating_add(u32::from(hit_color.get_red()));
-                        final_green = final_green.saturating_add(u32::from(hit_color.get_green()));
-                        final_blue = final_blue.saturating_add(u32::from(hit_color.get_blue()));
-                    }
-                } else {
-                    // we return just the objects color
-                    let object_color = object_intersection
-                        .get_hit_object()
-                        .get_color(*object_intersection.get_hit_point());
-                    final_red = final_red.saturating_add(u32::from(object_color.get_red()));
-                    final_green = final_green.saturating_add(u32::from(object_color.get_green()));
-                    final_blue = final_blue.saturating_add(u32::from(object_color.get_blue()));
-                }
-            } else {
-                final_red = final_red.saturating_add(u32::from(self.background_color.get_red()));
-                final_green =
-                    final_green.saturating_add(u32::from(self.background_color.get_green()));
-                final_blue = final_blue.saturating_add(u32::from(self.background_color.get_blue()));
-            }
+            final_color += self.integrator.compute_color(ray, &self.scene, 1.0, rng);
         }
 
-        RGB::new(
-            final_red.saturating_div(self.pixel_samples).min(255) as u8,
-            final_green.saturating_div(self.pixel_samples).min(255) as u8,
-            final_blue.saturating_div(self.pixel_samples).min(255) as u8,
-        )
+        final_color / f64::from(self.pixel_samples)
+    }
+
+    /// this function finds the first surface seen by the ray going from the starting position
+    /// trough the end position (the center of a pixel), None is returned if the ray sees the
+    /// background
+    pub fn trace_first_hit(&self, starting_position: Vec3, end_position: Vec3) -> Option<FirstHit> {
+        let ray = Ray::new(starting_position, end_position - starting_position);
+        let intersection =
+            ObjectRayIntersection::check_intersection(ray, &self.scene, 0.0, f64::MAX)?;
+        let normal = intersection.get_hit_record().get_shading_normal();
+        let color = intersection.get_hit_color();
+
+        // like a reflectance the albedo can't be bigger than 1
+        Some(FirstHit::new(
+            intersection.get_hit_record().get_t(),
+            [*normal.get_x(), *normal.get_y(), *normal.get_z()],
+            Color::new(
+                color.get_red().clamp(0.0, 1.0),
+                color.get_green().clamp(0.0, 1.0),
+                color.get_blue().clamp(0.0, 1.0),
+            ),
+        ))
     }
 
-    /// this function renders the image on the "canvas"
+    /// this function renders the image on the "canvas", the canvas is split into regions of rows
+    /// that are rendered in parallel by `thread_count` threads
     pub fn render(&mut self) {
         let mut canvas = std::mem::take(&mut self.canvas);
-        let viewport_upper_left = self.camera.get_viewport_angle(ViewportAngles::UpperLeft);
-
-        for pixel in &mut canvas {
-            println!("{:?}", pixel);
-            let pixel_center = viewport_upper_left
-                + ((*self.camera.get_u_vector())
-                    * (f64::from(pixel.get_x()) * self.viewport_incr_x))
-                + ((*self.camera.get_v_vector())
-                    * (f64::from(pixel.get_y()) * (self.viewport_incr_y * -1.0)));
+        // every object and light has been added by now, so the hierarchy can be built once
+        self.scene.build_bvh();
 
-            pixel.change_color(self.trace_ray(*self.camera.get_position(), pixel_center));
-        }
+        let ray_tracer = &*self;
+        let seed = self.seed.unwrap_or_else(rand::random);
+        let canvas_width = u64::from(canvas.get_width());
+        let viewport_upper_left = self.camera.get_viewport_angle(ViewportAngles::UpperLeft);
+        let regions = Mutex::new(canvas.get_regions_mut(ROWS_PER_REGION).into_iter());
+
+        thread::scope(|scope| {
+            for _ in 0..self.thread_count {
+                scope.spawn(|| {
+                    loop {
+                        // the lock is released as soon as the next region is taken
+                        let next_region = regions.lock().unwrap().next();
+                        let Some(mut region) = next_region else {
+                            break;
+                        };
+
+                        for pixel in &mut region {
+                            if let Some((x, y, width, height)) = ray_tracer.crop_window
+                                && (pixel.get_x() < x
+                                    || pixel.get_x() >= x + width
+                                    || pixel.get_y() < y
+                                    || pixel.get_y() >= y + height)
+                            {
+                                continue;
+                            }
+
+                            let pixel_center = viewport_upper_left
+                                + ((*ray_tracer.camera.get_u_vector())
+                                    * (f64::from(pixel.get_x()) * ray_tracer.viewport_incr_x))
+                                + ((*ray_tracer.camera.get_v_vector())
+                                    * (f64::from(pixel.get_y()) * -ray_tracer.viewport_incr_y));
+
+                            // every pixel has its own generator so the result doesn't depend on
+                            // which thread renders it
+                            let pixel_index =
+                                u64::from(pixel.get_y()) * canvas_width + u64::from(pixel.get_x());
+                            let mut rng = StdRng::seed_from_u64(seed.wrapping_add(pixel_index));
+
+                            pixel.change_color(ray_tracer.trace_ray(
+                                *ray_tracer.camera.get_position(),
+                                pixel_center,
+                                &mut rng,
+                            ));
+
+                            if ray_tracer.records_first_hits {
+                                pixel.set_first_hit(ray_tracer.trace_first_hit(
+                                    *ray_tracer.camera.get_position(),
+                                    pixel_center,
+                                ));
+                            }
+                        }
+                    }
+                });
+            }
+        });
 
-        self.canvas = std::mem::take(&mut canvas);
+        self.canvas = canvas;
     }
 
     /// this function returns the "canvas"
@@ -0,0 +1,2090 @@
+use canvas::{Color, RGB};
+use std::collections::HashMap;
+use std::fmt;
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use world::*;
+
+use crate::ray_tracer::RayTracer;
+
+/// the default number of times a ray can be reflected or refracted by the Whitted integrator
+const DEFAULT_WHITTED_MAX_DEPTH: u8 = 3;
+/// the default number of bounces of a path traced path
+const DEFAULT_PATH_TRACING_MAX_DEPTH: u8 = 8;
+
+/// An enum containing the integrators a scene can be rendered with (see [`Integrators`])
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub enum IntegratorKind {
+    #[default]
+    Whitted,
+    PathTracing,
+}
+
+impl IntegratorKind {
+    /// retrieves the integrator with the given name (`whitted` or `path`)
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name {
+            "whitted" => Some(IntegratorKind::Whitted),
+            "path" => Some(IntegratorKind::PathTracing),
+            _ => None,
+        }
+    }
+
+    /// creates the integrator, without a `max_depth` the integrator's default one is used
+    fn create_integrator(
+        &self,
+        max_depth: Option<u8>,
+        background_color: Color,
+    ) -> Box<dyn Integrator> {
+        Integrators::create_integrator(match self {
+            IntegratorKind::Whitted => Integrators::Whitted(
+                max_depth.unwrap_or(DEFAULT_WHITTED_MAX_DEPTH),
+                background_color,
+            ),
+            IntegratorKind::PathTracing => Integrators::PathTracing(
+                max_depth.unwrap_or(DEFAULT_PATH_TRACING_MAX_DEPTH),
+                background_color,
+            ),
+        })
+    }
+}
+
+/// An error that can happen while loading a scene file
+#[derive(Debug)]
+pub enum SceneError {
+    /// the scene file couldn't be read
+    Io(io::Error),
+    /// the scene file is malformed at the given position (both `line` and `column` start from 1)
+    Parse {
+        line: usize,
+        column: usize,
+        message: String,
+    },
+}
+
+impl fmt::Display for SceneError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            SceneError::Io(error) => write!(f, "couldn't read the scene file: {}", error),
+            SceneError::Parse {
+                line,
+                column,
+                message,
+            } => write!(f, "{}:{}: {}", line, column, message),
+        }
+    }
+}
+
+impl std::error::Error for SceneError {}
+
+impl From<io::Error> for SceneError {
+    fn from(error: io::Error) -> Self {
+        SceneError::Io(error)
+    }
+}
+
+/// the kinds of token a scene file is made of
+#[derive(Debug, Clone, PartialEq)]
+enum TokenKind {
+    Identifier(String),
+    Number(f64),
+    String(String),
+    OpenBrace,
+    CloseBrace,
+}
+
+impl fmt::Display for TokenKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            TokenKind::Identifier(identifier) => write!(f, "'{}'", identifier),
+            TokenKind::Number(number) => write!(f, "the number {}", number),
+            TokenKind::String(string) => write!(f, "the string \"{}\"", string),
+            TokenKind::OpenBrace => write!(f, "'{{'"),
+            TokenKind::CloseBrace => write!(f, "'}}'"),
+        }
+    }
+}
+
+/// a token of the scene file and where it starts
+#[derive(Debug, Clone)]
+struct Token {
+    kind: TokenKind,
+    line: usize,
+    column: usize,
+}
+
+impl Token {
+    /// creates an error pointing at this token
+    fn error(&self, message: String) -> SceneError {
+        SceneError::Parse {
+            line: self.line,
+            column: self.column,
+            message,
+        }
+    }
+}
+
+/// splits the scene file into tokens, everything after a '#' is a comment
+fn tokenize(source: &str) -> Result<Vec<Token>, SceneError> {
+    let mut tokens: Vec<Token> = vec![];
+
+    for (line_index, line) in source.lines().enumerate() {
+        let characters: Vec<char> = line.chars().collect();
+        let mut index = 0;
+
+        while index < characters.len() {
+            let character = characters[index];
+            let line = line_index + 1;
+            let column = index + 1;
+            let error = |message: String| SceneError::Parse {
+                line,
+                column,
+                message,
+            };
+
+            if character == '#' {
+                break;
+            } else if character.is_whitespace() {
+                index += 1;
+                continue;
+            }
+
+            let kind = if character == '{' {
+                index += 1;
+                TokenKind::OpenBrace
+            } else if character == '}' {
+                index += 1;
+                TokenKind::CloseBrace
+            } else if character == '"' {
+                let mut string = String::new();
+                index += 1;
+
+                loop {
+                    match characters.get(index) {
+                        Some('"') => break,
+                        Some('\\') if index + 1 < characters.len() => {
+                            string.push(characters[index + 1]);
+                            index += 2;
+                        }
+                        Some(string_character) => {
+                            string.push(*string_character);
+                            index += 1;
+                        }
+                        None => return Err(error(String::from("unterminated string"))),
+                    }
+                }
+
+                index += 1;
+                TokenKind::String(string)
+            } else if character.is_ascii_digit() || "+-.".contains(character) {
+                let start = index;
+
+                while index < characters.len()
+                    && (characters[index].is_ascii_alphanumeric()
+                        || "+-.".contains(characters[index]))
+                {
+                    index += 1;
+                }
+
+                let number: String = characters[start..index].iter().collect();
+
+                match number.parse::<f64>() {
+                    Ok(number) if number.is_finite() => TokenKind::Number(number),
+                    _ => return Err(error(format!("'{}' is not a valid number", number))),
+                }
+            } else if character.is_alphabetic() || character == '_' {
+                let start = index;
+
+                while index < characters.len()
+                    && (characters[index].is_alphanumeric() || characters[index] == '_')
+                {
+                    index += 1;
+                }
+
+                TokenKind::Identifier(characters[start..index].iter().collect())
+            } else {
+                return Err(error(format!("unexpected character '{}'", character)));
+            };
+
+            tokens.push(Token { kind, line, column });
+        }
+    }
+
+    Ok(tokens)
+}
+
+/// An object holding everything described by a scene file
+///
+/// A scene file is a list of statements, every object and light is a block of properties, for
+/// example:
+///
+/// ```text
+/// canvas { width 1200 height 1200 }
+/// camera { position 0 0 0 look_at 0 0 1 up 0 1 0 viewport_width 2 }
+/// background 53 81 92
+/// samples 2
+/// integrator whitted
+///
+/// material blue { color 87 87 201 reflectiveness 0.9 }
+///
+/// sphere { position 1.3 -3 6 radius 1 material blue }
+/// panel { origin 0 -4 4 width 80 height 80 normal 0 1 0 material { color 233 233 233 } }
+/// sphere {
+///     position 0 0 0 radius 1
+///     transform { scale 1 2 1 rotate_z 30 translate -1 -2 6 }
+/// }
+/// cylinder { base -2 -4 5 axis 0 1 0 radius 0.5 height 3 material blue }
+/// torus { center 2 -3 5 axis 0 1 0 major_radius 1 minor_radius 0.25 }
+/// point_light { position 0 3 4 intensity 2 color 255 255 255 }
+/// ```
+///
+/// the other shapes are `triangle` (`vertex_1`, `vertex_2` and `vertex_3`, optionally with a
+/// `normal_` and a `uv_` for every vertex), `mesh`, `box` (`min` and `max` corners), `oriented_box`
+/// (`center`, `size`, `x_axis` and `y_axis`), `cone` (like `cylinder`), `disk` (`center`, `normal`
+/// and `radius`), `annulus` (a disk with an `inner_radius`) and the infinite `plane` (`point` and
+/// `normal`, tiled by 1x1 squares); every object can have a `transform` applied in the written
+/// order (`translate`, `scale`, `rotate_x`, `rotate_y`, `rotate_z` and `rotate` around an axis, the
+/// angles are in degrees)
+///
+/// closed objects can be combined by `union`, `intersection` and `difference` blocks containing
+/// two or more objects (the result has the first object's material), for example:
+///
+/// ```text
+/// difference {
+///     sphere { position 0 -3 5 radius 1 material blue }
+///     cylinder { base 0 -5 5 axis 0 1 0 radius 0.4 height 4 }
+/// }
+/// ```
+///
+/// implicit shapes are `sdf` blocks containing a distance field: `sphere` (`radius`), `box`
+/// (`size` and `rounding`), `torus` (`major_radius` and `minor_radius`, on the xz plane),
+/// `capsule` (`start`, `end` and `radius`) and `mandelbulb` (`power` and `iterations`), or an
+/// operator containing other fields: `union`, `intersection` and `difference` (blended over their
+/// `smoothness`), `repeat` (`period`), `twist` (`rate` in degrees per unit around the y axis) and
+/// `displace` (`amplitude` and `frequency`); every field can have a `transform`, for example:
+///
+/// ```text
+/// sdf {
+///     material blue
+///     union {
+///         smoothness 0.5
+///         sphere { radius 1 }
+///         sphere { radius 0.8 transform { translate 1.2 0 0 } }
+///     }
+///     transform { translate 0 -3 6 }
+/// }
+/// ```
+///
+/// a material's `texture` can be placed with `texture_scale` (how many times it repeats along u
+/// and v), `texture_offset`, `texture_rotation` (in degrees) and `texture_wrap` (`repeat`, `clamp`
+/// or `mirror`); `texture_filter` picks how its texels are blended: `nearest`, `bilinear` or
+/// `trilinear` (the default, blending the mipmap levels matching the area seen by every pixel)
+///
+/// the shading normal can be bent by a tangent space `normal_map` and by a grayscale `bump_map`
+/// (whose slopes are multiplied by `bump_strength`), both are placed like the texture
+///
+/// a material using any of `metallic` (0 by default), `roughness` (0.5), `ior` (1.5) or
+/// `transmission` (0) is physically based: its color is the base color of a microfacet model and
+/// the `reflectiveness`, `specularity`, `refraction` and `transparency` are ignored; every one of
+/// `metallic`, `roughness` and `transmission` can be multiplied by a grayscale map
+/// (`metallic_map`, `roughness_map` and `transmission_map`) placed like the texture, a map
+/// without its value is used as is (the value defaults to 1), for example:
+///
+/// ```text
+/// material frosted_glass { color 255 255 255 roughness 0.2 transmission 1 ior 1.5 }
+/// ```
+///
+/// refractive materials (a `refraction` or a `transmission`) split the light between reflection
+/// and refraction with the Fresnel equations, the light travelling inside of them takes the
+/// `absorption_color` after `absorption_distance` units (1 by default) and gets darker beyond;
+/// materials can be nested (an object inside of a refractive one refracts with the ratio of their
+/// indices)
+///
+/// instead of an image a material can have a procedural texture blending `color_1` (white by
+/// default) and `color_2` (black by default): `checker` (squares `scale` units wide), `noise` and
+/// `turbulence` (with `scale` sized features and a number of `octaves`), `marble` and `wood` (bent
+/// by an amount of `turbulence`), `gradient` (from the origin to the end of the `direction`) and
+/// `voronoi` (`scale` sized cells); the patterns lie on the UVs unless `space` is `world`, for
+/// example:
+///
+/// ```text
+/// material floor { checker { color_1 233 233 233 color_2 40 40 40 scale 0.5 space world } }
+/// ```
+///
+/// any object can glow with an `emission` color multiplied by `emission_strength` (1 by default),
+/// the path tracer uses the objects glowing this way as lights (except for planes, csg and sdf
+/// objects which only glow when a ray hits them), for example:
+///
+/// ```text
+/// sphere { position 0 3 5 radius 0.5 material { emission 255 230 200 emission_strength 8 } }
+/// ```
+///
+/// a `spot_light` shines from its `position` along its `direction` with an `intensity` and a
+/// `color` like a `point_light`, at full strength within the `inner_angle` and fading to nothing at the
+/// `outer_angle` (in degrees from the axis, smaller than 90); it can project an image, its `gobo`:
+///
+/// ```text
+/// spot_light {
+///     position 0 4 0 direction 0 -1 1
+///     inner_angle 15 outer_angle 25
+///     intensity 6 gobo "window.png"
+/// }
+/// ```
+///
+/// colors are 8 bit sRGB triplets, paths (textures, meshes and gobos) are relative to the scene
+/// file
+pub struct SceneDescription {
+    canvas_width: u32,
+    canvas_height: u32,
+    camera_position: Vec3,
+    camera_look_at: Vec3,
+    camera_up: Vec3,
+    viewport_width: f64,
+    background_color: Color,
+    pixel_samples: u32,
+    integrator: IntegratorKind,
+    max_depth: Option<u8>,
+    objects: Vec<Box<dyn Object>>,
+    lights: Vec<Box<dyn Light>>,
+}
+
+impl SceneDescription {
+    /// loads and parses the scene file at the given path
+    pub fn load(scene_path: &str) -> Result<Self, SceneError> {
+        let source = fs::read_to_string(scene_path)?;
+        let base_directory = Path::new(scene_path)
+            .parent()
+            .map(Path::to_path_buf)
+            .unwrap_or_default();
+
+        Self::parse(&source, base_directory)
+    }
+
+    /// parses a scene file, relative paths are resolved from `base_directory`
+    fn parse(source: &str, base_directory: PathBuf) -> Result<Self, SceneError> {
+        let mut parser = Parser {
+            tokens: tokenize(source)?,
+            position: 0,
+            base_directory,
+            materials: HashMap::new(),
+        };
+
+        let mut scene = Self {
+            canvas_width: 1200,
+            canvas_height: 1200,
+            camera_position: Vec3::new(0.0, 0.0, 0.0),
+            camera_look_at: Vec3::new(0.0, 0.0, 1.0),
+            camera_up: Vec3::new(0.0, 1.0, 0.0),
+            viewport_width: 2.0,
+            background_color: Color::new(0.0, 0.0, 0.0),
+            pixel_samples: 1,
+            integrator: IntegratorKind::default(),
+            max_depth: None,
+            objects: vec![],
+            lights: vec![],
+        };
+
+        while let Some(statement) = parser.next_token() {
+            let TokenKind::Identifier(name) = &statement.kind else {
+                return Err(
+                    statement.error(format!("expected a statement, found {}", statement.kind))
+                );
+            };
+
+            match name.as_str() {
+                "canvas" => parser.parse_canvas(&mut scene, &statement)?,
+                "camera" => parser.parse_camera(&mut scene, &statement)?,
+                "background" => scene.background_color = parser.parse_color()?,
+                "samples" => scene.pixel_samples = parser.parse_positive_integer()?,
+                "integrator" => scene.integrator = parser.parse_integrator()?,
+                "material" => parser.parse_named_material()?,
+                "ambient_light" => scene.lights.push(parser.parse_ambient_light(&statement)?),
+                "directional_light" => scene
+                    .lights
+                    .push(parser.parse_directional_light(&statement)?),
+                "panel_light" => scene.lights.push(parser.parse_panel_light(&statement)?),
+                "point_light" => scene.lights.push(parser.parse_point_light(&statement)?),
+                "spot_light" => scene.lights.push(parser.parse_spot_light(&statement)?),
+                _ => match parser.parse_object(name, &statement)? {
+                    Some(objects) => scene.objects.extend(objects),
+                    None => {
+                        return Err(statement.error(format!("unknown statement '{}'", name)));
+                    }
+                },
+            }
+        }
+
+        Ok(scene)
+    }
+
+    /// overrides the width of the rendered image
+    pub fn set_canvas_width(&mut self, canvas_width: u32) -> &mut Self {
+        self.canvas_width = canvas_width;
+
+        self
+    }
+
+    /// overrides the height of the rendered image
+    pub fn set_canvas_height(&mut self, canvas_height: u32) -> &mut Self {
+        self.canvas_height = canvas_height;
+
+        self
+    }
+
+    /// overrides the number of samples taken for every pixel
+    pub fn set_pixel_samples(&mut self, pixel_samples: u32) -> &mut Self {
+        self.pixel_samples = pixel_samples;
+
+        self
+    }
+
+    /// overrides how the light seen by the camera is computed
+    pub fn set_integrator(&mut self, integrator: IntegratorKind) -> &mut Self {
+        self.integrator = integrator;
+
+        self
+    }
+
+    /// overrides the maximum number of bounces of a ray
+    pub fn set_max_depth(&mut self, max_depth: u8) -> &mut Self {
+        self.max_depth = Some(max_depth);
+
+        self
+    }
+
+    /// creates the ray tracer rendering the described scene
+    pub fn into_ray_tracer(self) -> RayTracer {
+        let mut ray_tracer = RayTracer::new(
+            self.camera_position,
+            self.camera_look_at,
+            self.camera_up,
+            self.canvas_width,
+            self.canvas_height,
+            self.background_color,
+            self.viewport_width,
+            self.pixel_samples,
+        );
+        ray_tracer.set_integrator(
+            self.integrator
+                .create_integrator(self.max_depth, self.background_color),
+        );
+
+        for object in self.objects {
+            ray_tracer.add_object(object);
+        }
+
+        for light in self.lights {
+            ray_tracer.add_light(light);
+        }
+
+        ray_tracer
+    }
+}
+
+/// stores a property's value making sure it's not given twice in the same block
+fn set_once<T>(slot: &mut Option<T>, value: T, property: &Token) -> Result<(), SceneError> {
+    if slot.is_some() {
+        return Err(property.error(format!("{} is given more than once", property.kind)));
+    }
+
+    *slot = Some(value);
+
+    Ok(())
+}
+
+/// retrieves a required property's value
+fn required<T>(slot: Option<T>, name: &str, block: &Token) -> Result<T, SceneError> {
+    slot.ok_or_else(|| block.error(format!("{} is missing the '{}' property", block.kind, name)))
+}
+
+/// the state of the parser going trough the scene file's tokens
+struct Parser {
+    tokens: Vec<Token>,
+    position: usize,
+    base_directory: PathBuf,
+    materials: HashMap<String, Material>,
+}
+
+impl Parser {
+    /// retrieves the next token (if any)
+    fn next_token(&mut self) -> Option<Token> {
+        let token = self.tokens.get(self.position).cloned();
+        self.position += 1;
+
+        token
+    }
+
+    /// retrieves the next token, reaching the end of the file is an error
+    fn expect_token(&mut self, expected: &str) -> Result<Token, SceneError> {
+        match self.next_token() {
+            Some(token) => Ok(token),
+            None => {
+                let (line, column) = self
+                    .tokens
+                    .last()
+                    .map_or((1, 1), |token| (token.line, token.column));
+
+                Err(SceneError::Parse {
+                    line,
+                    column,
+                    message: format!("expected {} but the file ended", expected),
+                })
+            }
+        }
+    }
+
+    /// parses a number
+    fn parse_number(&mut self) -> Result<f64, SceneError> {
+        let token = self.expect_token("a number")?;
+
+        match token.kind {
+            TokenKind::Number(number) => Ok(number),
+            _ => Err(token.error(format!("expected a number, found {}", token.kind))),
+        }
+    }
+
+    /// parses a number bigger than zero
+    fn parse_positive_number(&mut self) -> Result<f64, SceneError> {
+        let number = self.parse_number()?;
+
+        if number <= 0.0 {
+            return Err(self.tokens[self.position - 1]
+                .error(format!("expected a number bigger than 0, found {}", number)));
+        }
+
+        Ok(number)
+    }
+
+    /// parses an integer bigger than zero
+    fn parse_positive_integer(&mut self) -> Result<u32, SceneError> {
+        let number = self.parse_number()?;
+
+        if number < 1.0 || number.fract() != 0.0 || number > f64::from(u32::MAX) {
+            return Err(self.tokens[self.position - 1]
+                .error(format!("expected a positive integer, found {}", number)));
+        }
+
+        Ok(number as u32)
+    }
+
+    /// parses three numbers as a vector
+    fn parse_vec3(&mut self) -> Result<Vec3, SceneError> {
+        Ok(Vec3::new(
+            self.parse_number()?,
+            self.parse_number()?,
+            self.parse_number()?,
+        ))
+    }
+
+    /// parses two numbers as texture coordinates
+    fn parse_uv(&mut self) -> Result<(f64, f64), SceneError> {
+        Ok((self.parse_number()?, self.parse_number()?))
+    }
+
+    /// parses a non zero vector (used for directions and normals)
+    fn parse_direction(&mut self) -> Result<Vec3, SceneError> {
+        let start = self.position;
+        let direction = self.parse_vec3()?;
+
+        if direction.get_length() == 0.0 {
+            return Err(self.tokens[start].error(String::from("a direction can't be all zeros")));
+        }
+
+        Ok(direction)
+    }
+
+    /// parses an 8 bit sRGB color (three integers between 0 and 255)
+    fn parse_color(&mut self) -> Result<Color, SceneError> {
+        let mut channels = [0u8; 3];
+
+        for channel in &mut channels {
+            let number = self.parse_number()?;
+
+            if !(0.0..=255.0).contains(&number) || number.fract() != 0.0 {
+                return Err(self.tokens[self.position - 1].error(format!(
+                    "a color channel must be an integer between 0 and 255, found {}",
+                    number
+                )));
+            }
+
+            *channel = number as u8;
+        }
+
+        Ok(Color::from(RGB::new(channels[0], channels[1], channels[2])))
+    }
+
+    /// parses a path string resolving it from the scene file's directory, the file has to exist
+    fn parse_path(&mut self) -> Result<String, SceneError> {
+        let token = self.expect_token("a path")?;
+
+        let TokenKind::String(path) = &token.kind else {
+            return Err(token.error(format!("expected a quoted path, found {}", token.kind)));
+        };
+
+        let resolved = self.base_directory.join(path);
+
+        if !resolved.is_file() {
+            return Err(token.error(format!("the file '{}' doesn't exist", resolved.display())));
+        }
+
+        Ok(resolved.to_string_lossy().into_owned())
+    }
+
+    /// parses the path of an image and loads it, `is_srgb` tells if the image holds colors (see
+    /// `Texture::load`) or data (see `Texture::load_raw`)
+    fn parse_image(&mut self, is_srgb: bool) -> Result<Texture, SceneError> {
+        let position = self.position;
+        let path = self.parse_path()?;
+        let texture = if is_srgb {
+            Texture::load(&path)
+        } else {
+            Texture::load_raw(&path)
+        };
+
+        texture.map_err(|error| {
+            self.tokens[position].error(format!("couldn't load the image '{}': {}", path, error))
+        })
+    }
+
+    /// starts a block checking for its '{'
+    fn open_block(&mut self) -> Result<(), SceneError> {
+        let token = self.expect_token("'{'")?;
+
+        if token.kind != TokenKind::OpenBrace {
+            return Err(token.error(format!("expected '{{', found {}", token.kind)));
+        }
+
+        Ok(())
+    }
+
+    /// retrieves the next property of a block, None is returned when the block ends
+    fn next_property(&mut self) -> Result<Option<(String, Token)>, SceneError> {
+        let token = self.expect_token("a property or '}'")?;
+
+        match &token.kind {
+            TokenKind::CloseBrace => Ok(None),
+            TokenKind::Identifier(name) => Ok(Some((name.clone(), token))),
+            _ => Err(token.error(format!("expected a property, found {}", token.kind))),
+        }
+    }
+
+    /// parses the `canvas` block
+    fn parse_canvas(
+        &mut self,
+        scene: &mut SceneDescription,
+        block: &Token,
+    ) -> Result<(), SceneError> {
+        let mut width = None;
+        let mut height = None;
+
+        self.open_block()?;
+
+        while let Some((name, property)) = self.next_property()? {
+            match name.as_str() {
+                "width" => set_once(&mut width, self.parse_positive_integer()?, &property)?,
+                "height" => set_once(&mut height, self.parse_positive_integer()?, &property)?,
+                _ => return Err(unknown_property(&property, block)),
+            }
+        }
+
+        scene.canvas_width = required(width, "width", block)?;
+        scene.canvas_height = required(height, "height", block)?;
+
+        Ok(())
+    }
+
+    /// parses the `camera` block, missing properties keep their default
+    fn parse_camera(
+        &mut self,
+        scene: &mut SceneDescription,
+        block: &Token,
+    ) -> Result<(), SceneError> {
+        let mut position = None;
+        let mut look_at = None;
+        let mut up = None;
+        let mut viewport_width = None;
+
+        self.open_block()?;
+
+        while let Some((name, property)) = self.next_property()? {
+            match name.as_str() {
+                "position" => set_once(&mut position, self.parse_vec3()?, &property)?,
+                "look_at" => set_once(&mut look_at, self.parse_direction()?, &property)?,
+                "up" => set_once(&mut up, self.parse_direction()?, &property)?,
+                "viewport_width" => set_once(
+                    &mut viewport_width,
+                    self.parse_positive_number()?,
+                    &property,
+                )?,
+                _ => return Err(unknown_property(&property, block)),
+            }
+        }
+
+        scene.camera_position = position.unwrap_or(scene.camera_position);
+        scene.camera_look_at = look_at.unwrap_or(scene.camera_look_at);
+        scene.camera_up = up.unwrap_or(scene.camera_up);
+        scene.viewport_width = viewport_width.unwrap_or(scene.viewport_width);
+
+        Ok(())
+    }
+
+    /// parses the properties of a material block (the '{' included)
+    fn parse_material_block(&mut self, block: &Token) -> Result<Material, SceneError> {
+        let mut color = None;
+        let mut texture = None;
+        let mut procedural_texture = None;
+        let (mut normal_map, mut bump_map, mut bump_strength) = (None, None, None);
+        let mut texture_scale = None;
+        let mut texture_offset = None;
+        let mut texture_rotation = None;
+        let mut texture_wrap = None;
+        let mut texture_filter = None;
+        let (mut metallic, mut roughness, mut ior, mut transmission) = (None, None, None, None);
+        let (mut metallic_map, mut roughness_map, mut transmission_map) = (None, None, None);
+        let (mut absorption_color, mut absorption_distance) = (None, None);
+        let (mut emission, mut emission_strength) = (None, None);
+        let mut reflectiveness = None;
+        let mut specularity = None;
+        let mut refraction = None;
+        let mut transparency = None;
+
+        self.open_block()?;
+
+        while let Some((name, property)) = self.next_property()? {
+            match name.as_str() {
+                "color" => set_once(&mut color, self.parse_color()?, &property)?,
+                "texture" => set_once(&mut texture, self.parse_image(true)?, &property)?,
+                "normal_map" => set_once(&mut normal_map, self.parse_image(false)?, &property)?,
+                "bump_map" => set_once(&mut bump_map, self.parse_image(false)?, &property)?,
+                "bump_strength" => set_once(&mut bump_strength, self.parse_number()?, &property)?,
+                "texture_scale" => set_once(&mut texture_scale, self.parse_uv()?, &property)?,
+                "texture_offset" => set_once(&mut texture_offset, self.parse_uv()?, &property)?,
+                "texture_rotation" => {
+                    set_once(&mut texture_rotation, self.parse_number()?, &property)?
+                }
+                "texture_wrap" => {
+                    set_once(&mut texture_wrap, self.parse_texture_wrap()?, &property)?
+                }
+                "texture_filter" => {
+                    set_once(&mut texture_filter, self.parse_texture_filter()?, &property)?
+                }
+                "metallic" => set_once(&mut metallic, self.parse_number()?, &property)?,
+                "roughness" => set_once(&mut roughness, self.parse_number()?, &property)?,
+                "ior" => set_once(&mut ior, self.parse_positive_number()?, &property)?,
+                "transmission" => set_once(&mut transmission, self.parse_number()?, &property)?,
+                "metallic_map" => set_once(&mut metallic_map, self.parse_image(false)?, &property)?,
+                "roughness_map" => {
+                    set_once(&mut roughness_map, self.parse_image(false)?, &property)?
+                }
+                "transmission_map" => {
+                    set_once(&mut transmission_map, self.parse_image(false)?, &property)?
+                }
+                "absorption_color" => {
+                    set_once(&mut absorption_color, self.parse_color()?, &property)?
+                }
+                "absorption_distance" => set_once(
+                    &mut absorption_distance,
+                    self.parse_positive_number()?,
+                    &property,
+                )?,
+                "emission" => set_once(&mut emission, self.parse_color()?, &property)?,
+                "emission_strength" => {
+                    set_once(&mut emission_strength, self.parse_number()?, &property)?
+                }
+                "reflectiveness" => set_once(&mut reflectiveness, self.parse_number()?, &property)?,
+                "specularity" => set_once(&mut specularity, self.parse_number()?, &property)?,
+                "refraction" => {
+                    set_once(&mut refraction, self.parse_positive_number()?, &property)?
+                }
+                "transparency" => set_once(&mut transparency, self.parse_number()?, &property)?,
+                _ => match self.parse_procedural_texture(&name, &property)? {
+                    Some(new_texture) => set_once(&mut procedural_texture, new_texture, &property)?,
+                    None => return Err(unknown_property(&property, block)),
+                },
+            }
+        }
+
+        let has_maps = [
+            normal_map.is_some(),
+            bump_map.is_some(),
+            metallic_map.is_some(),
+            roughness_map.is_some(),
+            transmission_map.is_some(),
+        ]
+        .contains(&true);
+
+        let mut builder = MaterialBuilder::new();
+
+        if let Some(color) = color {
+            builder.set_color(color);
+        }
+
+        if let Some(procedural_texture) = procedural_texture {
+            if texture.is_some() {
+                return Err(block.error(String::from(
+                    "a material has either an image or a procedural texture",
+                )));
+            }
+
+            builder.set_texture_map(procedural_texture);
+        }
+
+        if let Some(normal_map) = normal_map {
+            builder.set_normal_map(normal_map);
+        }
+
+        if let Some(bump_map) = bump_map {
+            builder.set_bump_map(bump_map);
+        } else if bump_strength.is_some() {
+            return Err(block.error(String::from(
+                "the bump_strength of a material needs a bump_map",
+            )));
+        }
+
+        if let Some(bump_strength) = bump_strength {
+            builder.set_bump_strength(bump_strength);
+        }
+
+        if let Some(metallic) = metallic {
+            builder.set_metallic(metallic);
+        }
+
+        if let Some(roughness) = roughness {
+            builder.set_roughness(roughness);
+        }
+
+        if let Some(ior) = ior {
+            builder.set_ior(ior);
+        }
+
+        if let Some(transmission) = transmission {
+            builder.set_transmission(transmission);
+        }
+
+        if let Some(metallic_map) = metallic_map {
+            builder.set_metallic_map(metallic_map);
+        }
+
+        if let Some(roughness_map) = roughness_map {
+            builder.set_roughness_map(roughness_map);
+        }
+
+        if let Some(transmission_map) = transmission_map {
+            builder.set_transmission_map(transmission_map);
+        }
+
+        if let Some(absorption_color) = absorption_color {
+            builder.set_absorption(absorption_color, absorption_distance.unwrap_or(1.0));
+        } else if absorption_distance.is_some() {
+            return Err(block.error(String::from(
+                "the absorption_distance of a material needs an absorption_color",
+            )));
+        }
+
+        if let Some(emission) = emission {
+            builder.set_emission(emission, emission_strength.unwrap_or(1.0));
+        } else if emission_strength.is_some() {
+            return Err(block.error(String::from(
+                "the emission_strength of a material needs an emission",
+            )));
+        }
+
+        if let Some(texture) = texture {
+            builder.set_texture(texture);
+        } else if !has_maps
+            && (texture_scale.is_some()
+                || texture_offset.is_some()
+                || texture_rotation.is_some()
+                || texture_wrap.is_some()
+                || texture_filter.is_some())
+        {
+            return Err(block.error(String::from(
+                "the texture_ properties of a material need an image texture or a map",
+            )));
+        }
+
+        if let Some((scale_u, scale_v)) = texture_scale {
+            builder.set_texture_scale(scale_u, scale_v);
+        }
+
+        if let Some((offset_u, offset_v)) = texture_offset {
+            builder.set_texture_offset(offset_u, offset_v);
+        }
+
+        if let Some(rotation) = texture_rotation {
+            builder.set_texture_rotation(rotation.to_radians());
+        }
+
+        if let Some(wrap) = texture_wrap {
+            builder.set_texture_wrap(wrap);
+        }
+
+        if let Some(filter) = texture_filter {
+            builder.set_texture_filter(filter);
+        }
+
+        if let Some(reflectiveness) = reflectiveness {
+            builder.set_reflectiveness(reflectiveness);
+        }
+
+        if let Some(specularity) = specularity {
+            builder.set_specularity(specularity);
+        }
+
+        if let Some(refraction) = refraction {
+            builder.set_refraction(refraction);
+        }
+
+        if let Some(transparency) = transparency {
+            builder.set_transparency(transparency);
+        }
+
+        Ok(builder.build())
+    }
+
+    /// parses the block of the procedural texture `kind`, None is returned if `kind` isn't a
+    /// procedural texture
+    fn parse_procedural_texture(
+        &mut self,
+        kind: &str,
+        block: &Token,
+    ) -> Result<Option<Arc<dyn TextureMap>>, SceneError> {
+        if !matches!(
+            kind,
+            "checker" | "noise" | "turbulence" | "marble" | "wood" | "gradient" | "voronoi"
+        ) {
+            return Ok(None);
+        }
+
+        let (mut first_color, mut second_color) = (None, None);
+        let (mut scale, mut octaves, mut turbulence) = (None, None, None);
+        let mut direction = None;
+        let mut space = None;
+
+        self.open_block()?;
+
+        while let Some((name, property)) = self.next_property()? {
+            match (kind, name.as_str()) {
+                (_, "color_1") => set_once(&mut first_color, self.parse_color()?, &property)?,
+                (_, "color_2") => set_once(&mut second_color, self.parse_color()?, &property)?,
+                (_, "space") => set_once(&mut space, self.parse_texture_space()?, &property)?,
+                ("gradient", "direction") => {
+                    set_once(&mut direction, self.parse_vec3()?, &property)?
+                }
+                ("gradient", _) => return Err(unknown_property(&property, block)),
+                (_, "scale") => set_once(&mut scale, self.parse_positive_number()?, &property)?,
+                ("noise" | "turbulence", "octaves") => {
+                    set_once(&mut octaves, self.parse_positive_integer()?, &property)?
+                }
+                ("marble" | "wood", "turbulence") => {
+                    set_once(&mut turbulence, self.parse_number()?, &property)?
+                }
+                _ => return Err(unknown_property(&property, block)),
+            }
+        }
+
+        let first_color = first_color.unwrap_or(Color::new(1.0, 1.0, 1.0));
+        let second_color = second_color.unwrap_or(Color::new(0.0, 0.0, 0.0));
+        let scale = scale.unwrap_or(1.0);
+        let octaves = octaves.unwrap_or(4) as usize;
+        let turbulence = turbulence.unwrap_or(1.0);
+
+        let texture = match kind {
+            "checker" => ProceduralTextures::Checker(first_color, second_color, scale),
+            "noise" => ProceduralTextures::Noise(first_color, second_color, scale, octaves),
+            "turbulence" => {
+                ProceduralTextures::Turbulence(first_color, second_color, scale, octaves)
+            }
+            "marble" => ProceduralTextures::Marble(first_color, second_color, scale, turbulence),
+            "wood" => ProceduralTextures::Wood(first_color, second_color, scale, turbulence),
+            "gradient" => ProceduralTextures::Gradient(
+                first_color,
+                second_color,
+                required(direction, "direction", block)?,
+            ),
+            _ => ProceduralTextures::Voronoi(first_color, second_color, scale),
+        };
+
+        Ok(Some(Arc::from(ProceduralTextures::create_texture(
+            texture,
+            space.unwrap_or_default(),
+        ))))
+    }
+
+    /// parses the name of a texture space (`uv` or `world`)
+    fn parse_texture_space(&mut self) -> Result<TextureSpace, SceneError> {
+        let token = self.expect_token("a texture space")?;
+
+        if let TokenKind::Identifier(name) = &token.kind {
+            match name.as_str() {
+                "uv" => return Ok(TextureSpace::Uv),
+                "world" => return Ok(TextureSpace::World),
+                _ => {}
+            }
+        }
+
+        Err(token.error(format!(
+            "expected a texture space ('uv' or 'world'), found {}",
+            token.kind
+        )))
+    }
+
+    /// parses the name of a texture wrap mode (`repeat`, `clamp` or `mirror`)
+    fn parse_texture_wrap(&mut self) -> Result<TextureWrap, SceneError> {
+        let token = self.expect_token("a texture wrap mode")?;
+
+        if let TokenKind::Identifier(name) = &token.kind {
+            match name.as_str() {
+                "repeat" => return Ok(TextureWrap::Repeat),
+                "clamp" => return Ok(TextureWrap::Clamp),
+                "mirror" => return Ok(TextureWrap::Mirror),
+                _ => {}
+            }
+        }
+
+        Err(token.error(format!(
+            "expected a texture wrap mode ('repeat', 'clamp' or 'mirror'), found {}",
+            token.kind
+        )))
+    }
+
+    /// parses the name of a texture filter (`nearest`, `bilinear` or `trilinear`)
+    fn parse_texture_filter(&mut self) -> Result<TextureFilter, SceneError> {
+        let token = self.expect_token("a texture filter")?;
+
+        if let TokenKind::Identifier(name) = &token.kind {
+            match name.as_str() {
+                "nearest" => return Ok(TextureFilter::Nearest),
+                "bilinear" => return Ok(TextureFilter::Bilinear),
+                "trilinear" => return Ok(TextureFilter::Trilinear),
+                _ => {}
+            }
+        }
+
+        Err(token.error(format!(
+            "expected a texture filter ('nearest', 'bilinear' or 'trilinear'), found {}",
+            token.kind
+        )))
+    }
+
+    /// parses the name of an integrator (`whitted` or `path`)
+    fn parse_integrator(&mut self) -> Result<IntegratorKind, SceneError> {
+        let token = self.expect_token("an integrator")?;
+
+        if let TokenKind::Identifier(name) = &token.kind
+            && let Some(integrator) = IntegratorKind::from_name(name)
+        {
+            return Ok(integrator);
+        }
+
+        Err(token.error(format!(
+            "expected an integrator ('whitted' or 'path'), found {}",
+            token.kind
+        )))
+    }
+
+    /// parses a `material <name> { ... }` statement
+    fn parse_named_material(&mut self) -> Result<(), SceneError> {
+        let token = self.expect_token("a material name")?;
+
+        let TokenKind::Identifier(name) = &token.kind else {
+            return Err(token.error(format!("expected a material name, found {}", token.kind)));
+        };
+
+        if self.materials.contains_key(name) {
+            return Err(token.error(format!("the material '{}' is already defined", name)));
+        }
+
+        let material = self.parse_material_block(&token)?;
+        self.materials.insert(name.clone(), material);
+
+        Ok(())
+    }
+
+    /// parses the value of an object's `material` property: either the name of a material defined
+    /// before or a material block
+    fn parse_material_value(&mut self) -> Result<Material, SceneError> {
+        let token = self.expect_token("a material")?;
+
+        match &token.kind {
+            TokenKind::Identifier(name) => match self.materials.get(name) {
+                Some(material) => Ok(material.clone()),
+                None => Err(token.error(format!("unknown material '{}'", name))),
+            },
+            TokenKind::OpenBrace => {
+                // the block starts at this token
+                self.position -= 1;
+                self.parse_material_block(&token)
+            }
+            _ => Err(token.error(format!("expected a material, found {}", token.kind))),
+        }
+    }
+
+    /// parses the properties of a transform block (the '{' included), the operations are applied
+    /// in the order they're written and the angles are in degrees
+    fn parse_transform_block(&mut self, block: &Token) -> Result<Transform, SceneError> {
+        let mut transform = Transform::identity();
+
+        self.open_block()?;
+
+        while let Some((name, property)) = self.next_property()? {
+            let next = match name.as_str() {
+                "translate" => Transform::translate(self.parse_vec3()?),
+                "scale" => Transform::scale(self.parse_vec3()?),
+                "rotate_x" => Transform::rotate_x(self.parse_number()?.to_radians()),
+                "rotate_y" => Transform::rotate_y(self.parse_number()?.to_radians()),
+                "rotate_z" => Transform::rotate_z(self.parse_number()?.to_radians()),
+                "rotate" => {
+                    let axis = self.parse_direction()?;
+
+                    Transform::rotate(axis, self.parse_number()?.to_radians())
+                }
+                _ => return Err(unknown_property(&property, block)),
+            };
+
+            transform = transform.then(&next);
+        }
+
+        Ok(transform)
+    }
+
+    /// parses the block of the object statement `name`, None is returned if `name` isn't an
+    /// object
+    fn parse_object(
+        &mut self,
+        name: &str,
+        statement: &Token,
+    ) -> Result<Option<Vec<Box<dyn Object>>>, SceneError> {
+        let object = match name {
+            "sphere" => self.parse_sphere(statement)?,
+            "triangle" => self.parse_triangle(statement)?,
+            "panel" => self.parse_panel(statement)?,
+            "mesh" => return self.parse_mesh(statement).map(Some),
+            "box" => self.parse_box(statement)?,
+            "oriented_box" => self.parse_oriented_box(statement)?,
+            "cylinder" | "cone" => self.parse_axial_shape(statement, name)?,
+            "disk" | "annulus" => self.parse_disk(statement, name)?,
+            "torus" => self.parse_torus(statement)?,
+            "plane" => self.parse_plane(statement)?,
+            "sdf" => self.parse_sdf(statement)?,
+            "union" | "intersection" | "difference" => self.parse_csg(statement, name)?,
+            _ => return Ok(None),
+        };
+
+        Ok(Some(vec![object]))
+    }
+
+    /// parses a `union`, `intersection` or `difference` block (`kind` is the block's name), the
+    /// objects inside of it are combined one after the other
+    fn parse_csg(&mut self, block: &Token, kind: &str) -> Result<Box<dyn Object>, SceneError> {
+        let operation = match kind {
+            "union" => CsgOperation::Union,
+            "intersection" => CsgOperation::Intersection,
+            _ => CsgOperation::Difference,
+        };
+        let mut objects: Vec<Box<dyn Object>> = vec![];
+        let mut transform = None;
+
+        self.open_block()?;
+
+        while let Some((name, property)) = self.next_property()? {
+            if name == "transform" {
+                set_once(
+                    &mut transform,
+                    self.parse_transform_block(&property)?,
+                    &property,
+                )?;
+                continue;
+            }
+
+            match self.parse_object(&name, &property)? {
+                Some(new_objects) => objects.extend(new_objects),
+                None => return Err(unknown_property(&property, block)),
+            }
+        }
+
+        let mut objects = objects.into_iter();
+
+        let (Some(first), Some(second)) = (objects.next(), objects.next()) else {
+            return Err(block.error(format!("a {} needs at least two objects", kind)));
+        };
+
+        let combined = objects.fold(
+            Objects::create_object(Objects::Csg(operation, Arc::from(first), Arc::from(second))),
+            |combined, next| {
+                Objects::create_object(Objects::Csg(
+                    operation,
+                    Arc::from(combined),
+                    Arc::from(next),
+                ))
+            },
+        );
+
+        Ok(transform_object(combined, transform))
+    }
+
+    /// parses a `sphere` block
+    fn parse_sphere(&mut self, block: &Token) -> Result<Box<dyn Object>, SceneError> {
+        let mut position = None;
+        let mut radius = None;
+        let mut material = None;
+        let mut transform = None;
+
+        self.open_block()?;
+
+        while let Some((name, property)) = self.next_property()? {
+            match name.as_str() {
+                "position" => set_once(&mut position, self.parse_vec3()?, &property)?,
+                "radius" => set_once(&mut radius, self.parse_positive_number()?, &property)?,
+                "material" => set_once(&mut material, self.parse_material_value()?, &property)?,
+                "transform" => set_once(
+                    &mut transform,
+                    self.parse_transform_block(&property)?,
+                    &property,
+                )?,
+                _ => return Err(unknown_property(&property, block)),
+            }
+        }
+
+        let sphere = Objects::create_object(Objects::Sphere(
+            required(position, "position", block)?,
+            required(radius, "radius", block)?,
+            material.unwrap_or_else(default_material),
+        ));
+
+        Ok(transform_object(sphere, transform))
+    }
+
+    /// parses a `triangle` block
+    fn parse_triangle(&mut self, block: &Token) -> Result<Box<dyn Object>, SceneError> {
+        let mut vertices = [None, None, None];
+        let mut normals = [None, None, None];
+        let mut uvs = [None, None, None];
+        let mut material = None;
+        let mut transform = None;
+
+        self.open_block()?;
+
+        while let Some((name, property)) = self.next_property()? {
+            match name.as_str() {
+                "vertex_1" => set_once(&mut vertices[0], self.parse_vec3()?, &property)?,
+                "vertex_2" => set_once(&mut vertices[1], self.parse_vec3()?, &property)?,
+                "vertex_3" => set_once(&mut vertices[2], self.parse_vec3()?, &property)?,
+                "normal_1" => set_once(&mut normals[0], self.parse_direction()?, &property)?,
+                "normal_2" => set_once(&mut normals[1], self.parse_direction()?, &property)?,
+                "normal_3" => set_once(&mut normals[2], self.parse_direction()?, &property)?,
+                "uv_1" => set_once(&mut uvs[0], self.parse_uv()?, &property)?,
+                "uv_2" => set_once(&mut uvs[1], self.parse_uv()?, &property)?,
+                "uv_3" => set_once(&mut uvs[2], self.parse_uv()?, &property)?,
+                "material" => set_once(&mut material, self.parse_material_value()?, &property)?,
+                "transform" => set_once(
+                    &mut transform,
+                    self.parse_transform_block(&property)?,
+                    &property,
+                )?,
+                _ => return Err(unknown_property(&property, block)),
+            }
+        }
+
+        let [vertex_1, vertex_2, vertex_3] = vertices;
+
+        // the normals and the UVs are given for every vertex or for none of them
+        let normals = match normals {
+            [Some(normal_1), Some(normal_2), Some(normal_3)] => {
+                Some([normal_1, normal_2, normal_3])
+            }
+            [None, None, None] => None,
+            _ => {
+                return Err(block.error(String::from(
+                    "a triangle needs a normal for every vertex or for none of them",
+                )));
+            }
+        };
+        let uvs = match uvs {
+            [Some(uv_1), Some(uv_2), Some(uv_3)] => Some([uv_1, uv_2, uv_3]),
+            [None, None, None] => None,
+            _ => {
+                return Err(block.error(String::from(
+                    "a triangle needs a uv for every vertex or for none of them",
+                )));
+            }
+        };
+
+        let triangle = Objects::create_object(Objects::InterpolatedTriangle(
+            [
+                required(vertex_1, "vertex_1", block)?,
+                required(vertex_2, "vertex_2", block)?,
+                required(vertex_3, "vertex_3", block)?,
+            ],
+            normals,
+            uvs,
+            material.unwrap_or_else(default_material),
+        ));
+
+        Ok(transform_object(triangle, transform))
+    }
+
+    /// parses a `panel` block
+    fn parse_panel(&mut self, block: &Token) -> Result<Box<dyn Object>, SceneError> {
+        let mut origin = None;
+        let mut width = None;
+        let mut height = None;
+        let mut normal = None;
+        let mut material = None;
+        let mut transform = None;
+
+        self.open_block()?;
+
+        while let Some((name, property)) = self.next_property()? {
+            match name.as_str() {
+                "origin" => set_once(&mut origin, self.parse_vec3()?, &property)?,
+                "width" => set_once(&mut width, self.parse_positive_number()?, &property)?,
+                "height" => set_once(&mut height, self.parse_positive_number()?, &property)?,
+                "normal" => set_once(&mut normal, self.parse_direction()?, &property)?,
+                "material" => set_once(&mut material, self.parse_material_value()?, &property)?,
+                "transform" => set_once(
+                    &mut transform,
+                    self.parse_transform_block(&property)?,
+                    &property,
+                )?,
+                _ => return Err(unknown_property(&property, block)),
+            }
+        }
+
+        let panel = Objects::create_object(Objects::Panel(
+            required(origin, "origin", block)?,
+            required(width, "width", block)?,
+            required(height, "height", block)?,
+            required(normal, "normal", block)?,
+            material.unwrap_or_else(default_material),
+        ));
+
+        Ok(transform_object(panel, transform))
+    }
+
+    /// parses a `mesh` block, with `split_groups` every group of the OBJ file becomes its own
+    /// object
+    fn parse_mesh(&mut self, block: &Token) -> Result<Vec<Box<dyn Object>>, SceneError> {
+        let mut file = None;
+        let mut split_groups = None;
+        let mut material = None;
+        let mut transform = None;
+
+        self.open_block()?;
+
+        while let Some((name, property)) = self.next_property()? {
+            match name.as_str() {
+                "file" => {
+                    let path_index = self.position;
+                    let path = self.parse_path()?;
+                    set_once(
+                        &mut file,
+                        (path, self.tokens[path_index].clone()),
+                        &property,
+                    )?
+                }
+                "split_groups" => set_once(&mut split_groups, true, &property)?,
+                "material" => set_once(&mut material, self.parse_material_value()?, &property)?,
+                "transform" => set_once(
+                    &mut transform,
+                    self.parse_transform_block(&property)?,
+                    &property,
+                )?,
+                _ => return Err(unknown_property(&property, block)),
+            }
+        }
+
+        let (path, path_token) = required(file, "file", block)?;
+        let material = material.unwrap_or_else(default_material);
+        let obj_error = |error: ObjError| path_token.error(format!("{}: {}", path, error));
+
+        let meshes = if split_groups.unwrap_or(false) {
+            Mesh::load_obj_groups(&path, material).map_err(obj_error)?
+        } else {
+            vec![Mesh::load_obj(&path, material).map_err(obj_error)?]
+        };
+
+        Ok(meshes
+            .into_iter()
+            .map(|mesh| transform_object(Box::new(mesh), transform))
+            .collect())
+    }
+
+    /// parses a `box` block (an axis aligned box between two corners)
+    fn parse_box(&mut self, block: &Token) -> Result<Box<dyn Object>, SceneError> {
+        let mut min = None;
+        let mut max = None;
+        let mut material = None;
+        let mut transform = None;
+
+        self.open_block()?;
+
+        while let Some((name, property)) = self.next_property()? {
+            match name.as_str() {
+                "min" => set_once(&mut min, self.parse_vec3()?, &property)?,
+                "max" => set_once(&mut max, self.parse_vec3()?, &property)?,
+                "material" => set_once(&mut material, self.parse_material_value()?, &property)?,
+                "transform" => set_once(
+                    &mut transform,
+                    self.parse_transform_block(&property)?,
+                    &property,
+                )?,
+                _ => return Err(unknown_property(&property, block)),
+            }
+        }
+
+        let cuboid = Objects::create_object(Objects::Cuboid(
+            required(min, "min", block)?,
+            required(max, "max", block)?,
+            material.unwrap_or_else(default_material),
+        ));
+
+        Ok(transform_object(cuboid, transform))
+    }
+
+    /// parses an `oriented_box` block
+    fn parse_oriented_box(&mut self, block: &Token) -> Result<Box<dyn Object>, SceneError> {
+        let mut center = None;
+        let mut size = None;
+        let mut x_axis = None;
+        let mut y_axis = None;
+        let mut material = None;
+        let mut transform = None;
+
+        self.open_block()?;
+
+        while let Some((name, property)) = self.next_property()? {
+            match name.as_str() {
+                "center" => set_once(&mut center, self.parse_vec3()?, &property)?,
+                "size" => set_once(&mut size, self.parse_vec3()?, &property)?,
+                "x_axis" => set_once(&mut x_axis, self.parse_direction()?, &property)?,
+                "y_axis" => set_once(&mut y_axis, self.parse_direction()?, &property)?,
+                "material" => set_once(&mut material, self.parse_material_value()?, &property)?,
+                "transform" => set_once(
+                    &mut transform,
+                    self.parse_transform_block(&property)?,
+                    &property,
+                )?,
+                _ => return Err(unknown_property(&property, block)),
+            }
+        }
+
+        let cuboid = Objects::create_object(Objects::OrientedCuboid(
+            required(center, "center", block)?,
+            required(size, "size", block)?,
+            required(x_axis, "x_axis", block)?,
+            required(y_axis, "y_axis", block)?,
+            material.unwrap_or_else(default_material),
+        ));
+
+        Ok(transform_object(cuboid, transform))
+    }
+
+    /// parses a `cylinder` or a `cone` block (`kind` is the block's name)
+    fn parse_axial_shape(
+        &mut self,
+        block: &Token,
+        kind: &str,
+    ) -> Result<Box<dyn Object>, SceneError> {
+        let mut base = None;
+        let mut axis = None;
+        let mut radius = None;
+        let mut height = None;
+        let mut material = None;
+        let mut transform = None;
+
+        self.open_block()?;
+
+        while let Some((name, property)) = self.next_property()? {
+            match name.as_str() {
+                "base" => set_once(&mut base, self.parse_vec3()?, &property)?,
+                "axis" => set_once(&mut axis, self.parse_direction()?, &property)?,
+                "radius" => set_once(&mut radius, self.parse_positive_number()?, &property)?,
+                "height" => set_once(&mut height, self.parse_positive_number()?, &property)?,
+                "material" => set_once(&mut material, self.parse_material_value()?, &property)?,
+                "transform" => set_once(
+                    &mut transform,
+                    self.parse_transform_block(&property)?,
+                    &property,
+                )?,
+                _ => return Err(unknown_property(&property, block)),
+            }
+        }
+
+        let base = required(base, "base", block)?;
+        let axis = axis.unwrap_or(Vec3::new(0.0, 1.0, 0.0));
+        let radius = required(radius, "radius", block)?;
+        let height = required(height, "height", block)?;
+        let material = material.unwrap_or_else(default_material);
+
+        let shape = Objects::create_object(if kind == "cone" {
+            Objects::Cone(base, axis, radius, height, material)
+        } else {
+            Objects::Cylinder(base, axis, radius, height, material)
+        });
+
+        Ok(transform_object(shape, transform))
+    }
+
+    /// parses a `disk` or an `annulus` block (`kind` is the block's name)
+    fn parse_disk(&mut self, block: &Token, kind: &str) -> Result<Box<dyn Object>, SceneError> {
+        let mut center = None;
+        let mut normal = None;
+        let mut radius = None;
+        let mut inner_radius = None;
+        let mut material = None;
+        let mut transform = None;
+
+        self.open_block()?;
+
+        while let Some((name, property)) = self.next_property()? {
+            match name.as_str() {
+                "center" => set_once(&mut center, self.parse_vec3()?, &property)?,
+                "normal" => set_once(&mut normal, self.parse_direction()?, &property)?,
+                "radius" => set_once(&mut radius, self.parse_positive_number()?, &property)?,
+                "inner_radius" if kind == "annulus" => {
+                    set_once(&mut inner_radius, self.parse_positive_number()?, &property)?
+                }
+                "material" => set_once(&mut material, self.parse_material_value()?, &property)?,
+                "transform" => set_once(
+                    &mut transform,
+                    self.parse_transform_block(&property)?,
+                    &property,
+                )?,
+                _ => return Err(unknown_property(&property, block)),
+            }
+        }
+
+        let center = required(center, "center", block)?;
+        let normal = required(normal, "normal", block)?;
+        let radius = required(radius, "radius", block)?;
+        let material = material.unwrap_or_else(default_material);
+
+        let disk = Objects::create_object(if kind == "annulus" {
+            let inner_radius = required(inner_radius, "inner_radius", block)?;
+
+            if inner_radius >= radius {
+                return Err(block.error(String::from(
+                    "the inner_radius of an annulus must be smaller than its radius",
+                )));
+            }
+
+            Objects::Annulus(center, normal, inner_radius, radius, material)
+        } else {
+            Objects::Disk(center, normal, radius, material)
+        });
+
+        Ok(transform_object(disk, transform))
+    }
+
+    /// parses a `plane` block
+    fn parse_plane(&mut self, block: &Token) -> Result<Box<dyn Object>, SceneError> {
+        let mut point = None;
+        let mut normal = None;
+        let mut material = None;
+        let mut transform = None;
+
+        self.open_block()?;
+
+        while let Some((name, property)) = self.next_property()? {
+            match name.as_str() {
+                "point" => set_once(&mut point, self.parse_vec3()?, &property)?,
+                "normal" => set_once(&mut normal, self.parse_direction()?, &property)?,
+                "material" => set_once(&mut material, self.parse_material_value()?, &property)?,
+                "transform" => set_once(
+                    &mut transform,
+                    self.parse_transform_block(&property)?,
+                    &property,
+                )?,
+                _ => return Err(unknown_property(&property, block)),
+            }
+        }
+
+        let plane = Objects::create_object(Objects::Plane(
+            required(point, "point", block)?,
+            required(normal, "normal", block)?,
+            material.unwrap_or_else(default_material),
+        ));
+
+        Ok(transform_object(plane, transform))
+    }
+
+    /// parses an `sdf` block, it contains a single distance field block
+    fn parse_sdf(&mut self, block: &Token) -> Result<Box<dyn Object>, SceneError> {
+        let mut field = None;
+        let mut material = None;
+        let mut transform = None;
+
+        self.open_block()?;
+
+        while let Some((name, property)) = self.next_property()? {
+            match name.as_str() {
+                "material" => set_once(&mut material, self.parse_material_value()?, &property)?,
+                "transform" => set_once(
+                    &mut transform,
+                    self.parse_transform_block(&property)?,
+                    &property,
+                )?,
+                _ => match self.parse_distance_field(&name, &property)? {
+                    Some(new_field) => set_once(&mut field, new_field, &property)?,
+                    None => return Err(unknown_property(&property, block)),
+                },
+            }
+        }
+
+        let sdf = Objects::create_object(Objects::Sdf(
+            required(field, "distance field", block)?,
+            material.unwrap_or_else(default_material),
+        ));
+
+        Ok(transform_object(sdf, transform))
+    }
+
+    /// parses the block of the distance field `kind`, None is returned if `kind` isn't a distance
+    /// field; the operators contain the distance fields they work on
+    fn parse_distance_field(
+        &mut self,
+        kind: &str,
+        block: &Token,
+    ) -> Result<Option<Arc<dyn DistanceField>>, SceneError> {
+        let is_operator = match kind {
+            "sphere" | "box" | "torus" | "capsule" | "mandelbulb" => false,
+            "union" | "intersection" | "difference" | "repeat" | "twist" | "displace" => true,
+            _ => return Ok(None),
+        };
+        let is_combination = matches!(kind, "union" | "intersection" | "difference");
+
+        let mut fields: Vec<Arc<dyn DistanceField>> = vec![];
+        let (mut radius, mut major_radius, mut minor_radius) = (None, None, None);
+        let (mut size, mut rounding) = (None, None);
+        let (mut start, mut end) = (None, None);
+        let (mut power, mut iterations) = (None, None);
+        let (mut smoothness, mut period, mut rate) = (None, None, None);
+        let (mut amplitude, mut frequency) = (None, None);
+        let mut transform = None;
+
+        self.open_block()?;
+
+        while let Some((name, property)) = self.next_property()? {
+            match (kind, name.as_str()) {
+                ("sphere" | "capsule", "radius") => {
+                    set_once(&mut radius, self.parse_positive_number()?, &property)?
+                }
+                ("box", "size") => set_once(&mut size, self.parse_vec3()?, &property)?,
+                ("box", "rounding") => {
+                    set_once(&mut rounding, self.parse_positive_number()?, &property)?
+                }
+                ("torus", "major_radius") => {
+                    set_once(&mut major_radius, self.parse_positive_number()?, &property)?
+                }
+                ("torus", "minor_radius") => {
+                    set_once(&mut minor_radius, self.parse_positive_number()?, &property)?
+                }
+                ("capsule", "start") => set_once(&mut start, self.parse_vec3()?, &property)?,
+                ("capsule", "end") => set_once(&mut end, self.parse_vec3()?, &property)?,
+                ("mandelbulb", "power") => {
+                    set_once(&mut power, self.parse_positive_number()?, &property)?
+                }
+                ("mandelbulb", "iterations") => {
+                    set_once(&mut iterations, self.parse_positive_integer()?, &property)?
+                }
+                ("union" | "intersection" | "difference", "smoothness") => {
+                    set_once(&mut smoothness, self.parse_positive_number()?, &property)?
+                }
+                ("repeat", "period") => set_once(&mut period, self.parse_vec3()?, &property)?,
+                ("twist", "rate") => set_once(&mut rate, self.parse_number()?, &property)?,
+                ("displace", "amplitude") => {
+                    set_once(&mut amplitude, self.parse_number()?, &property)?
+                }
+                ("displace", "frequency") => {
+                    set_once(&mut frequency, self.parse_positive_number()?, &property)?
+                }
+                (_, "transform") => set_once(
+                    &mut transform,
+                    self.parse_transform_block(&property)?,
+                    &property,
+                )?,
+                _ if is_operator => match self.parse_distance_field(&name, &property)? {
+                    Some(_) if !is_combination && !fields.is_empty() => {
+                        return Err(
+                            property.error(format!("a {} contains a single distance field", kind))
+                        );
+                    }
+                    Some(field) => fields.push(field),
+                    None => return Err(unknown_property(&property, block)),
+                },
+                _ => return Err(unknown_property(&property, block)),
+            }
+        }
+
+        let mut fields = fields.into_iter();
+
+        let field = match kind {
+            "sphere" => DistanceFields::Sphere(required(radius, "radius", block)?),
+            "box" => DistanceFields::Box(
+                required(size, "size", block)? * 0.5,
+                rounding.unwrap_or(0.0),
+            ),
+            "torus" => DistanceFields::Torus(
+                required(major_radius, "major_radius", block)?,
+                required(minor_radius, "minor_radius", block)?,
+            ),
+            "capsule" => DistanceFields::Capsule(
+                required(start, "start", block)?,
+                required(end, "end", block)?,
+                required(radius, "radius", block)?,
+            ),
+            "mandelbulb" => {
+                DistanceFields::Mandelbulb(power.unwrap_or(8.0), iterations.unwrap_or(10) as usize)
+            }
+            "union" | "intersection" | "difference" => {
+                let operation = match kind {
+                    "union" => CsgOperation::Union,
+                    "intersection" => CsgOperation::Intersection,
+                    _ => CsgOperation::Difference,
+                };
+                let smoothness = smoothness.unwrap_or(0.0);
+
+                let (Some(first), Some(second)) = (fields.next(), fields.next()) else {
+                    return Err(
+                        block.error(format!("a {} needs at least two distance fields", kind))
+                    );
+                };
+
+                fields.fold(
+                    DistanceFields::SmoothCombine(operation, first, second, smoothness),
+                    |combined, next| {
+                        DistanceFields::SmoothCombine(
+                            operation,
+                            Arc::from(DistanceFields::create_distance_field(combined)),
+                            next,
+                            smoothness,
+                        )
+                    },
+                )
+            }
+            _ => {
+                let Some(inner) = fields.next() else {
+                    return Err(block.error(format!("a {} needs a distance field", kind)));
+                };
+
+                match kind {
+                    "repeat" => {
+                        DistanceFields::Repetition(inner, required(period, "period", block)?)
+                    }
+                    "twist" => {
+                        DistanceFields::Twist(inner, required(rate, "rate", block)?.to_radians())
+                    }
+                    _ => DistanceFields::Displacement(
+                        inner,
+                        required(amplitude, "amplitude", block)?,
+                        required(frequency, "frequency", block)?,
+                    ),
+                }
+            }
+        };
+
+        let mut field = Arc::from(DistanceFields::create_distance_field(field));
+
+        if let Some(transform) = transform {
+            field = Arc::from(DistanceFields::create_distance_field(
+                DistanceFields::Transformed(field, transform),
+            ));
+        }
+
+        Ok(Some(field))
+    }
+
+    /// parses a `torus` block
+    fn parse_torus(&mut self, block: &Token) -> Result<Box<dyn Object>, SceneError> {
+        let mut center = None;
+        let mut axis = None;
+        let mut major_radius = None;
+        let mut minor_radius = None;
+        let mut material = None;
+        let mut transform = None;
+
+        self.open_block()?;
+
+        while let Some((name, property)) = self.next_property()? {
+            match name.as_str() {
+                "center" => set_once(&mut center, self.parse_vec3()?, &property)?,
+                "axis" => set_once(&mut axis, self.parse_direction()?, &property)?,
+                "major_radius" => {
+                    set_once(&mut major_radius, self.parse_positive_number()?, &property)?
+                }
+                "minor_radius" => {
+                    set_once(&mut minor_radius, self.parse_positive_number()?, &property)?
+                }
+                "material" => set_once(&mut material, self.parse_material_value()?, &property)?,
+                "transform" => set_once(
+                    &mut transform,
+                    self.parse_transform_block(&property)?,
+                    &property,
+                )?,
+                _ => return Err(unknown_property(&property, block)),
+            }
+        }
+
+        let torus = Objects::create_object(Objects::Torus(
+            required(center, "center", block)?,
+            axis.unwrap_or(Vec3::new(0.0, 1.0, 0.0)),
+            required(major_radius, "major_radius", block)?,
+            required(minor_radius, "minor_radius", block)?,
+            material.unwrap_or_else(default_material),
+        ));
+
+        Ok(transform_object(torus, transform))
+    }
+
+    /// parses an `ambient_light` block
+    fn parse_ambient_light(&mut self, block: &Token) -> Result<Box<dyn Light>, SceneError> {
+        let mut intensity = None;
+
+        self.open_block()?;
+
+        while let Some((name, property)) = self.next_property()? {
+            match name.as_str() {
+                "intensity" => set_once(&mut intensity, self.parse_number()?, &property)?,
+                _ => return Err(unknown_property(&property, block)),
+            }
+        }
+
+        Ok(Lights::create_light(Lights::AmbientLight(required(
+            intensity,
+            "intensity",
+            block,
+        )?)))
+    }
+
+    /// parses a `directional_light` block
+    fn parse_directional_light(&mut self, block: &Token) -> Result<Box<dyn Light>, SceneError> {
+        let mut direction = None;
+        let mut intensity = None;
+
+        self.open_block()?;
+
+        while let Some((name, property)) = self.next_property()? {
+            match name.as_str() {
+                "direction" => set_once(&mut direction, self.parse_direction()?, &property)?,
+                "intensity" => set_once(&mut intensity, self.parse_number()?, &property)?,
+                _ => return Err(unknown_property(&property, block)),
+            }
+        }
+
+        Ok(Lights::create_light(Lights::DirectionalLight(
+            required(direction, "direction", block)?,
+            required(intensity, "intensity", block)?,
+        )))
+    }
+
+    /// parses a `panel_light` block
+    fn parse_panel_light(&mut self, block: &Token) -> Result<Box<dyn Light>, SceneError> {
+        let mut origin = None;
+        let mut width = None;
+        let mut height = None;
+        let mut normal = None;
+        let mut intensity = None;
+        let mut sample_gap = None;
+        let mut color = None;
+
+        self.open_block()?;
+
+        while let Some((name, property)) = self.next_property()? {
+            match name.as_str() {
+                "origin" => set_once(&mut origin, self.parse_vec3()?, &property)?,
+                "width" => set_once(&mut width, self.parse_positive_number()?, &property)?,
+                "height" => set_once(&mut height, self.parse_positive_number()?, &property)?,
+                "normal" => set_once(&mut normal, self.parse_direction()?, &property)?,
+                "intensity" => set_once(&mut intensity, self.parse_number()?, &property)?,
+                "sample_gap" => {
+                    set_once(&mut sample_gap, self.parse_positive_number()?, &property)?
+                }
+                "color" => set_once(&mut color, self.parse_color()?, &property)?,
+                _ => return Err(unknown_property(&property, block)),
+            }
+        }
+
+        Ok(Lights::create_light(Lights::PanelLight(
+            required(origin, "origin", block)?,
+            required(width, "width", block)?,
+            required(height, "height", block)?,
+            required(normal, "normal", block)?,
+            required(intensity, "intensity", block)?,
+            required(sample_gap, "sample_gap", block)?,
+            color,
+        )))
+    }
+
+    /// parses a `point_light` block
+    fn parse_point_light(&mut self, block: &Token) -> Result<Box<dyn Light>, SceneError> {
+        let mut position = None;
+        let mut intensity = None;
+        let mut color = None;
+
+        self.open_block()?;
+
+        while let Some((name, property)) = self.next_property()? {
+            match name.as_str() {
+                "position" => set_once(&mut position, self.parse_vec3()?, &property)?,
+                "intensity" => set_once(&mut intensity, self.parse_number()?, &property)?,
+                "color" => set_once(&mut color, self.parse_color()?, &property)?,
+                _ => return Err(unknown_property(&property, block)),
+            }
+        }
+
+        Ok(Lights::create_light(Lights::PointLight(
+            required(position, "position", block)?,
+            required(intensity, "intensity", block)?,
+            color,
+        )))
+    }
+
+    /// parses a `spot_light` block, the angles are in degrees
+    fn parse_spot_light(&mut self, block: &Token) -> Result<Box<dyn Light>, SceneError> {
+        let (mut position, mut direction) = (None, None);
+        let (mut inner_angle, mut outer_angle) = (None, None);
+        let mut intensity = None;
+        let mut color = None;
+        let mut gobo = None;
+
+        self.open_block()?;
+
+        while let Some((name, property)) = self.next_property()? {
+            match name.as_str() {
+                "position" => set_once(&mut position, self.parse_vec3()?, &property)?,
+                "direction" => set_once(&mut direction, self.parse_direction()?, &property)?,
+                "inner_angle" => set_once(&mut inner_angle, self.parse_number()?, &property)?,
+                "outer_angle" => {
+                    set_once(&mut outer_angle, self.parse_positive_number()?, &property)?
+                }
+                "intensity" => set_once(&mut intensity, self.parse_number()?, &property)?,
+                "color" => set_once(&mut color, self.parse_color()?, &property)?,
+                "gobo" => set_once(&mut gobo, self.parse_image(true)?, &property)?,
+                _ => return Err(unknown_property(&property, block)),
+            }
+        }
+
+        let inner_angle = required(inner_angle, "inner_angle", block)?;
+        let outer_angle = required(outer_angle, "outer_angle", block)?;
+
+        if outer_angle >= 90.0 {
+            return Err(block.error(String::from(
+                "the outer_angle of a spot_light must be smaller than 90 degrees",
+            )));
+        }
+
+        if !(0.0..=outer_angle).contains(&inner_angle) {
+            return Err(block.error(String::from(
+                "the inner_angle of a spot_light must be between 0 and its outer_angle",
+            )));
+        }
+
+        Ok(Lights::create_light(Lights::SpotLight(
+            required(position, "position", block)?,
+            required(direction, "direction", block)?,
+            inner_angle.to_radians(),
+            outer_angle.to_radians(),
+            required(intensity, "intensity", block)?,
+            color,
+            gobo,
+        )))
+    }
+}
+
+/// wraps the object in a transformed instance if the object's block has a transform
+fn transform_object(object: Box<dyn Object>, transform: Option<Transform>) -> Box<dyn Object> {
+    match transform {
+        Some(transform) => Objects::create_object(Objects::Instance(Arc::from(object), transform)),
+        None => object,
+    }
+}
+
+/// the material of objects that don't specify one
+fn default_material() -> Material {
+    MaterialBuilder::default().build()
+}
+
+/// creates the error for a property that doesn't belong to the block
+fn unknown_property(property: &Token, block: &Token) -> SceneError {
+    property.error(format!(
+        "unknown property {} for {}",
+        property.kind, block.kind
+    ))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// parses the scene expecting an error, returning its `line:column: message` text
+    fn parse_error(source: &str) -> String {
+        match SceneDescription::parse(source, PathBuf::new()) {
+            Ok(_) => panic!("the scene should have an error:\n{}", source),
+            Err(error) => error.to_string(),
+        }
+    }
+
+    #[test]
+    fn parses_a_minimal_scene() {
+        let source = "
+            # a single sphere lit by a point light
+            canvas { width 4 height 3 }
+            camera { position 0 0 0 look_at 0 0 1 up 0 1 0 viewport_width 2 }
+            background 53 81 92
+            sphere { position 0 0 5 radius 1 material { color 200 60 60 } }
+            point_light { position 0 3 0 intensity 1 }
+        ";
+
+        let scene = match SceneDescription::parse(source, PathBuf::new()) {
+            Ok(scene) => scene,
+            Err(error) => panic!("the scene should be valid: {}", error),
+        };
+
+        assert_eq!((scene.canvas_width, scene.canvas_height), (4, 3));
+        assert_eq!(scene.objects.len(), 1);
+        assert_eq!(scene.lights.len(), 1);
+    }
+
+    #[test]
+    fn reports_an_unknown_property() {
+        assert_eq!(
+            parse_error("sphere {\n    position 0 0 5\n    radius 1\n    shininess 3\n}"),
+            "4:5: unknown property 'shininess' for 'sphere'"
+        );
+    }
+
+    #[test]
+    fn reports_a_duplicate_property() {
+        assert_eq!(
+            parse_error("sphere { position 0 0 5 radius 1 radius 2 }"),
+            "1:34: 'radius' is given more than once"
+        );
+    }
+
+    #[test]
+    fn reports_a_missing_required_property() {
+        assert_eq!(
+            parse_error("\nsphere { position 0 0 5 }"),
+            "2:1: 'sphere' is missing the 'radius' property"
+        );
+    }
+
+    #[test]
+    fn reports_an_out_of_range_color_channel() {
+        assert_eq!(
+            parse_error("background 0 300 0"),
+            "1:14: a color channel must be an integer between 0 and 255, found 300"
+        );
+    }
+
+    #[test]
+    fn reports_a_missing_file() {
+        assert_eq!(
+            parse_error("material tiles {\n    texture \"missing_texture.png\"\n}"),
+            "2:13: the file 'missing_texture.png' doesn't exist"
+        );
+    }
+}
@@ -0,0 +1,209 @@
+use crate::{Ray, Vec3};
+
+/// An object abstracting an axis aligned bounding box
+///
+/// Trough the use of this object we can quickly check if a ray could hit the objects contained
+/// inside of the box before doing the (more expensive) intersection with the objects themselves
+///
+/// # Examples
+///
+/// Create the bounding box of two points and merge it with another one
+///
+/// ```no_run
+/// # use world::{BoundingBox, Vec3};
+/// let box1 = BoundingBox::new(Vec3::new(0.0, 0.0, 0.0), Vec3::new(1.0, 1.0, 1.0));
+/// let box2 = BoundingBox::new(Vec3::new(2.0, 2.0, 2.0), Vec3::new(3.0, 3.0, 3.0));
+///
+/// let merged_box = box1.union(&box2);
+///
+/// println!("{:?}", merged_box.get_surface_area());
+/// ```
+#[derive(Debug, Copy, Clone)]
+pub struct BoundingBox {
+    min: Vec3,
+    max: Vec3,
+}
+
+impl BoundingBox {
+    /// creates a new bounding box containing both of the given points
+    pub fn new(point_1: Vec3, point_2: Vec3) -> Self {
+        Self {
+            min: Vec3::new(
+                point_1.get_x().min(*point_2.get_x()),
+                point_1.get_y().min(*point_2.get_y()),
+                point_1.get_z().min(*point_2.get_z()),
+            ),
+            max: Vec3::new(
+                point_1.get_x().max(*point_2.get_x()),
+                point_1.get_y().max(*point_2.get_y()),
+                point_1.get_z().max(*point_2.get_z()),
+            ),
+        }
+    }
+
+    /// creates an empty bounding box, the union of an empty box with another box is always the
+    /// other box
+    pub fn empty() -> Self {
+        Self {
+            min: Vec3::new(f64::INFINITY, f64::INFINITY, f64::INFINITY),
+            max: Vec3::new(f64::NEG_INFINITY, f64::NEG_INFINITY, f64::NEG_INFINITY),
+        }
+    }
+
+    /// creates a bounding box containing the whole space (for unbounded objects like planes)
+    pub fn infinite() -> Self {
+        Self {
+            min: Vec3::new(f64::NEG_INFINITY, f64::NEG_INFINITY, f64::NEG_INFINITY),
+            max: Vec3::new(f64::INFINITY, f64::INFINITY, f64::INFINITY),
+        }
+    }
+
+    /// creates the smallest bounding box containing all of the given points
+    pub fn from_points(points: &[Vec3]) -> Self {
+        points.iter().fold(Self::empty(), |bounding_box, point| {
+            bounding_box.union(&Self::new(*point, *point))
+        })
+    }
+
+    /// retrieves the corner of the box with the smallest coordinates
+    pub fn get_min(&self) -> &Vec3 {
+        &self.min
+    }
+
+    /// retrieves the corner of the box with the biggest coordinates
+    pub fn get_max(&self) -> &Vec3 {
+        &self.max
+    }
+
+    /// tells if the box is bounded (an unbounded box can't be stored in a bounding volume
+    /// hierarchy)
+    pub fn is_finite(&self) -> bool {
+        (0..3)
+            .all(|axis| self.min.get_axis(axis).is_finite() && self.max.get_axis(axis).is_finite())
+    }
+
+    /// returns the smallest bounding box containing both `self` and `other`
+    pub fn union(&self, other: &Self) -> Self {
+        Self {
+            min: Vec3::new(
+                self.min.get_x().min(*other.min.get_x()),
+                self.min.get_y().min(*other.min.get_y()),
+                self.min.get_z().min(*other.min.get_z()),
+            ),
+            max: Vec3::new(
+                self.max.get_x().max(*other.max.get_x()),
+                self.max.get_y().max(*other.max.get_y()),
+                self.max.get_z().max(*other.max.get_z()),
+            ),
+        }
+    }
+
+    /// returns the biggest bounding box contained in both `self` and `other` (empty if they don't
+    /// overlap)
+    pub fn intersection(&self, other: &Self) -> Self {
+        Self {
+            min: Vec3::new(
+                self.min.get_x().max(*other.min.get_x()),
+                self.min.get_y().max(*other.min.get_y()),
+                self.min.get_z().max(*other.min.get_z()),
+            ),
+            max: Vec3::new(
+                self.max.get_x().min(*other.max.get_x()),
+                self.max.get_y().min(*other.max.get_y()),
+                self.max.get_z().min(*other.max.get_z()),
+            ),
+        }
+    }
+
+    /// returns a copy of the bounding box grown by `padding` on every side (useful for flat
+    /// objects like panels and triangles)
+    pub fn pad(&self, padding: f64) -> Self {
+        Self {
+            min: self.min - padding,
+            max: self.max + padding,
+        }
+    }
+
+    /// returns the center of the bounding box
+    pub fn get_centroid(&self) -> Vec3 {
+        (self.min + self.max) * 0.5
+    }
+
+    /// returns the surface area of the bounding box (used by the surface area heuristic)
+    pub fn get_surface_area(&self) -> f64 {
+        let extent = self.max - self.min;
+
+        if *extent.get_x() < 0.0 || *extent.get_y() < 0.0 || *extent.get_z() < 0.0 {
+            return 0.0;
+        }
+
+        2.0 * (extent.get_x() * extent.get_y()
+            + extent.get_y() * extent.get_z()
+            + extent.get_z() * extent.get_x())
+    }
+
+    /// returns the index of the axis (0 = x, 1 = y, 2 = z) along which the box is the longest
+    pub fn get_longest_axis(&self) -> usize {
+        let extent = self.max - self.min;
+
+        if extent.get_x() >= extent.get_y() && extent.get_x() >= extent.get_z() {
+            0
+        } else if extent.get_y() >= extent.get_z() {
+            1
+        } else {
+            2
+        }
+    }
+
+    /// checks if the given point is inside of the bounding box (or on its surface)
+    pub fn contains(&self, point: &Vec3) -> bool {
+        (0..3).all(|axis| {
+            let coordinate = point.get_axis(axis);
+
+            coordinate >= self.min.get_axis(axis) && coordinate <= self.max.get_axis(axis)
+        })
+    }
+
+    /// checks if the ray hits the bounding box between `min_t` and `max_t` (the `inverse_direction`
+    /// is given by the caller since it's the same for every box the ray is checked against)
+    pub fn is_hit(&self, ray: &Ray, inverse_direction: &Vec3, min_t: f64, max_t: f64) -> bool {
+        self.get_hit_range(ray, inverse_direction, min_t, max_t)
+            .is_some()
+    }
+
+    /// returns the `t` at which the ray enters the bounding box and the one at which it leaves it
+    /// (clamped between `min_t` and `max_t`), None if the ray misses the box
+    pub fn get_hit_range(
+        &self,
+        ray: &Ray,
+        inverse_direction: &Vec3,
+        min_t: f64,
+        max_t: f64,
+    ) -> Option<(f64, f64)> {
+        let mut t_enter = min_t;
+        let mut t_exit = max_t;
+
+        for axis in 0..3 {
+            let origin = ray.get_position().get_axis(axis);
+            let inverse = inverse_direction.get_axis(axis);
+
+            let mut t0 = (self.min.get_axis(axis) - origin) * inverse;
+            let mut t1 = (self.max.get_axis(axis) - origin) * inverse;
+
+            if inverse < 0.0 {
+                std::mem::swap(&mut t0, &mut t1);
+            }
+
+            // `max`/`min` ignore NaNs (0 * inf), so a ray lying on a slab's plane doesn't get
+            // discarded
+            t_enter = t_enter.max(t0);
+            t_exit = t_exit.min(t1);
+
+            if t_exit < t_enter {
+                return None;
+            }
+        }
+
+        Some((t_enter, t_exit))
+    }
+}
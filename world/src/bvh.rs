@@ -0,0 +1,408 @@
+use crate::{BoundingBox, Ray, Vec3};
+
+/// number of buckets the primitives are split into when evaluating the surface area heuristic
+const SAH_BUCKETS: usize = 12;
+/// cost of traversing an interior node relative to intersecting a primitive
+const TRAVERSAL_COST: f64 = 0.125;
+/// leaves with more primitives than this are always split (if possible)
+const MAX_LEAF_PRIMITIVES: usize = 4;
+
+/// A node of the bounding volume hierarchy, nodes are stored in a flat vector where the left
+/// child of an interior node always comes right after its parent
+#[derive(Debug)]
+struct BvhNode {
+    bounding_box: BoundingBox,
+    /// for a leaf it's the offset of its first primitive, for an interior node it's the index of
+    /// its right child
+    offset: usize,
+    /// the number of primitives in the leaf (0 for an interior node)
+    primitive_count: usize,
+    /// the axis the node was split on (used to visit the nearest child first)
+    axis: usize,
+}
+
+/// An object abstracting a bounding volume hierarchy built with the surface area heuristic
+///
+/// The hierarchy doesn't own the primitives: it is built from their bounding boxes and reports
+/// back the index (in the slice given to `Bvh::new`) of the primitives a ray should be checked
+/// against
+///
+/// # Examples
+///
+/// Build a hierarchy over two boxes and look for the closest one hit by a ray
+///
+/// ```no_run
+/// # use world::{BoundingBox, Bvh, Ray, Vec3};
+/// let boxes = [
+///     BoundingBox::new(Vec3::new(-1.0, -1.0, 4.0), Vec3::new(1.0, 1.0, 5.0)),
+///     BoundingBox::new(Vec3::new(-1.0, -1.0, 8.0), Vec3::new(1.0, 1.0, 9.0)),
+/// ];
+/// let bvh = Bvh::new(&boxes);
+/// let ray = Ray::new(Vec3::new(0.0, 0.0, 0.0), Vec3::new(0.0, 0.0, 1.0));
+///
+/// // every primitive is "hit" at the distance of its box
+/// let closest = bvh.find_closest(&ray, 0.0, f64::MAX, |index, _min_t, _max_t| {
+///     Some(*boxes[index].get_min().get_z())
+/// });
+///
+/// println!("{:?}", closest);
+/// ```
+#[derive(Debug, Default)]
+pub struct Bvh {
+    nodes: Vec<BvhNode>,
+    primitive_indices: Vec<usize>,
+}
+
+impl Bvh {
+    /// builds a new hierarchy over the given bounding boxes
+    pub fn new(bounding_boxes: &[BoundingBox]) -> Self {
+        let mut bvh = Self {
+            nodes: Vec::with_capacity(bounding_boxes.len() * 2),
+            primitive_indices: (0..bounding_boxes.len()).collect(),
+        };
+
+        if !bounding_boxes.is_empty() {
+            let centroids: Vec<Vec3> = bounding_boxes
+                .iter()
+                .map(|bounding_box| bounding_box.get_centroid())
+                .collect();
+
+            bvh.build_node(bounding_boxes, &centroids, 0, bounding_boxes.len());
+        }
+
+        bvh
+    }
+
+    /// returns true if the hierarchy doesn't contain any primitive
+    pub fn is_empty(&self) -> bool {
+        self.nodes.is_empty()
+    }
+
+    /// recursively builds the node containing the primitives between `start` and `end` and
+    /// returns its index
+    fn build_node(
+        &mut self,
+        bounding_boxes: &[BoundingBox],
+        centroids: &[Vec3],
+        start: usize,
+        end: usize,
+    ) -> usize {
+        let node_index = self.nodes.len();
+        let primitive_count = end - start;
+
+        let mut node_box = BoundingBox::empty();
+        let mut centroid_box = BoundingBox::empty();
+
+        for &primitive in &self.primitive_indices[start..end] {
+            node_box = node_box.union(&bounding_boxes[primitive]);
+            centroid_box = centroid_box.union(&BoundingBox::new(
+                centroids[primitive],
+                centroids[primitive],
+            ));
+        }
+
+        self.nodes.push(BvhNode {
+            bounding_box: node_box,
+            offset: start,
+            primitive_count,
+            axis: 0,
+        });
+
+        if primitive_count <= 1 {
+            return node_index;
+        }
+
+        let axis = centroid_box.get_longest_axis();
+        let axis_min = centroid_box.get_min().get_axis(axis);
+        let axis_extent = centroid_box.get_max().get_axis(axis) - axis_min;
+
+        // every centroid is in the same place, there is no way to split them
+        if axis_extent <= 0.0 {
+            return node_index;
+        }
+
+        let bucket_of = |primitive: usize| -> usize {
+            let relative = (centroids[primitive].get_axis(axis) - axis_min) / axis_extent;
+
+            ((relative * SAH_BUCKETS as f64) as usize).min(SAH_BUCKETS - 1)
+        };
+
+        let mut bucket_counts = [0usize; SAH_BUCKETS];
+        let mut bucket_boxes = [BoundingBox::empty(); SAH_BUCKETS];
+
+        for &primitive in &self.primitive_indices[start..end] {
+            let bucket = bucket_of(primitive);
+            bucket_counts[bucket] += 1;
+            bucket_boxes[bucket] = bucket_boxes[bucket].union(&bounding_boxes[primitive]);
+        }
+
+        // the cost of splitting after every bucket (except the last one)
+        let mut best_bucket = 0;
+        let mut best_cost = f64::MAX;
+        let node_area = node_box.get_surface_area();
+
+        for split in 0..(SAH_BUCKETS - 1) {
+            let mut left_box = BoundingBox::empty();
+            let mut right_box = BoundingBox::empty();
+            let mut left_count = 0;
+            let mut right_count = 0;
+
+            for bucket in 0..=split {
+                left_box = left_box.union(&bucket_boxes[bucket]);
+                left_count += bucket_counts[bucket];
+            }
+
+            for bucket in (split + 1)..SAH_BUCKETS {
+                right_box = right_box.union(&bucket_boxes[bucket]);
+                right_count += bucket_counts[bucket];
+            }
+
+            if left_count == 0 || right_count == 0 {
+                continue;
+            }
+
+            let cost = TRAVERSAL_COST
+                + (left_box.get_surface_area() * left_count as f64
+                    + right_box.get_surface_area() * right_count as f64)
+                    / node_area.max(f64::EPSILON);
+
+            if cost < best_cost {
+                best_cost = cost;
+                best_bucket = split;
+            }
+        }
+
+        // if splitting costs more than intersecting every primitive we keep the node as a leaf
+        if best_cost == f64::MAX
+            || (best_cost >= primitive_count as f64 && primitive_count <= MAX_LEAF_PRIMITIVES)
+        {
+            return node_index;
+        }
+
+        // partitioning the primitives around the chosen bucket
+        let mut middle = start;
+
+        for i in start..end {
+            if bucket_of(self.primitive_indices[i]) <= best_bucket {
+                self.primitive_indices.swap(i, middle);
+                middle += 1;
+            }
+        }
+
+        self.build_node(bounding_boxes, centroids, start, middle);
+        let right_child = self.build_node(bounding_boxes, centroids, middle, end);
+
+        let node = &mut self.nodes[node_index];
+        node.offset = right_child;
+        node.primitive_count = 0;
+        node.axis = axis;
+
+        node_index
+    }
+
+    /// walks the hierarchy looking for the closest primitive hit by the ray
+    ///
+    /// `hit_primitive` is called with the index of a primitive and the current `min_t` and `max_t`
+    /// and should return the `t` at which the primitive is hit (if it's hit between them); the
+    /// function returns the index and `t` of the closest hit primitive
+    pub fn find_closest<F>(
+        &self,
+        ray: &Ray,
+        min_t: f64,
+        max_t: f64,
+        mut hit_primitive: F,
+    ) -> Option<(usize, f64)>
+    where
+        F: FnMut(usize, f64, f64) -> Option<f64>,
+    {
+        if self.nodes.is_empty() {
+            return None;
+        }
+
+        let direction = ray.get_direction();
+        let inverse_direction = Vec3::new(
+            1.0 / direction.get_x(),
+            1.0 / direction.get_y(),
+            1.0 / direction.get_z(),
+        );
+
+        let mut closest: Option<(usize, f64)> = None;
+        let mut closest_t = max_t;
+        let mut stack: Vec<usize> = Vec::with_capacity(64);
+        stack.push(0);
+
+        while let Some(node_index) = stack.pop() {
+            let node = &self.nodes[node_index];
+
+            if !node
+                .bounding_box
+                .is_hit(ray, &inverse_direction, min_t, closest_t)
+            {
+                continue;
+            }
+
+            if node.primitive_count > 0 {
+                for &primitive in
+                    &self.primitive_indices[node.offset..(node.offset + node.primitive_count)]
+                {
+                    if let Some(t) = hit_primitive(primitive, min_t, closest_t)
+                        && t > min_t
+                        && t < closest_t
+                    {
+                        closest_t = t;
+                        closest = Some((primitive, t));
+                    }
+                }
+            } else if direction.get_axis(node.axis) < 0.0 {
+                // the right child is nearer so it gets visited first
+                stack.push(node_index + 1);
+                stack.push(node.offset);
+            } else {
+                stack.push(node.offset);
+                stack.push(node_index + 1);
+            }
+        }
+
+        closest
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{MaterialBuilder, Object, Objects};
+    use rand::rngs::StdRng;
+    use rand::{Rng, SeedableRng};
+
+    /// creates spheres scattered (and overlapping) in a cube 20 units wide
+    fn spheres(count: usize, rng: &mut StdRng) -> Vec<Box<dyn Object>> {
+        (0..count)
+            .map(|_| {
+                Objects::create_object(Objects::Sphere(
+                    random_vector(rng) * 10.0,
+                    rng.random_range(0.1..1.5),
+                    MaterialBuilder::default().build(),
+                ))
+            })
+            .collect()
+    }
+
+    /// creates a vector whose coordinates are between -1 and 1
+    fn random_vector(rng: &mut StdRng) -> Vec3 {
+        Vec3::new(
+            rng.random_range(-1.0..1.0),
+            rng.random_range(-1.0..1.0),
+            rng.random_range(-1.0..1.0),
+        )
+    }
+
+    /// finds the closest hit trough the hierarchy built over the objects
+    fn bvh_closest(objects: &[Box<dyn Object>], bvh: &Bvh, ray: &Ray) -> Option<(usize, f64)> {
+        bvh.find_closest(ray, 0.0, f64::MAX, |index, min_t, max_t| {
+            objects[index]
+                .intersect(ray, min_t, max_t)
+                .map(|hit| hit.get_t())
+        })
+    }
+
+    /// finds the closest hit checking every object
+    fn linear_closest(objects: &[Box<dyn Object>], ray: &Ray) -> Option<(usize, f64)> {
+        objects
+            .iter()
+            .enumerate()
+            .filter_map(|(index, object)| {
+                object
+                    .intersect(ray, 0.0, f64::MAX)
+                    .map(|hit| (index, hit.get_t()))
+            })
+            .min_by(|(_, t), (_, other)| t.total_cmp(other))
+    }
+
+    fn build(objects: &[Box<dyn Object>]) -> Bvh {
+        let boxes: Vec<BoundingBox> = objects
+            .iter()
+            .map(|object| object.get_bounding_box())
+            .collect();
+
+        Bvh::new(&boxes)
+    }
+
+    #[test]
+    fn finds_the_same_hit_as_a_linear_scan() {
+        let mut rng = StdRng::seed_from_u64(0);
+        let objects = spheres(200, &mut rng);
+        let bvh = build(&objects);
+
+        for _ in 0..2000 {
+            let ray = Ray::new(random_vector(&mut rng) * 15.0, random_vector(&mut rng));
+
+            assert_eq!(
+                bvh_closest(&objects, &bvh, &ray),
+                linear_closest(&objects, &ray)
+            );
+        }
+    }
+
+    #[test]
+    fn finds_hits_along_the_axes() {
+        let mut rng = StdRng::seed_from_u64(1);
+        let objects = spheres(100, &mut rng);
+        let bvh = build(&objects);
+
+        // the directions with zero coordinates have infinite inverses
+        for axis in 0..3 {
+            for sign in [-1.0, 1.0] {
+                let mut direction = [0.0; 3];
+                direction[axis] = sign;
+
+                for _ in 0..200 {
+                    let ray = Ray::new(
+                        random_vector(&mut rng) * 10.0,
+                        Vec3::new(direction[0], direction[1], direction[2]),
+                    );
+
+                    assert_eq!(
+                        bvh_closest(&objects, &bvh, &ray),
+                        linear_closest(&objects, &ray)
+                    );
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn handles_primitives_with_the_same_centroid() {
+        let objects: Vec<Box<dyn Object>> = (1..=20)
+            .map(|radius| {
+                Objects::create_object(Objects::Sphere(
+                    Vec3::new(0.0, 0.0, 5.0),
+                    f64::from(radius) * 0.1,
+                    MaterialBuilder::default().build(),
+                ))
+            })
+            .collect();
+        let bvh = build(&objects);
+        let ray = Ray::new(Vec3::new(0.0, 0.0, 0.0), Vec3::new(0.0, 0.0, 1.0));
+
+        assert_eq!(
+            bvh_closest(&objects, &bvh, &ray),
+            linear_closest(&objects, &ray)
+        );
+        assert_eq!(
+            bvh_closest(&objects, &bvh, &ray).map(|(index, _)| index),
+            Some(19)
+        );
+    }
+
+    #[test]
+    fn empty_hierarchy_finds_nothing() {
+        let bvh = Bvh::new(&[]);
+        let ray = Ray::new(Vec3::new(0.0, 0.0, 0.0), Vec3::new(0.0, 0.0, 1.0));
+
+        assert!(bvh.is_empty());
+        assert_eq!(
+            bvh.find_closest(&ray, 0.0, f64::MAX, |_, _, _| Some(1.0)),
+            None
+        );
+    }
+}
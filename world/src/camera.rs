@@ -29,6 +29,7 @@ pub enum ViewportAngles {
 /// Create a new Camera
 ///
 /// ```no_run
+/// # use world::{Camera, Vec3};
 ///     let camera = Camera::new(
 ///         Vec3::new(0.0, 0.0, 0.0),
 ///         Vec3::new( 0.0, 0.0, 1.0),
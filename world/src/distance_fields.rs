@@ -0,0 +1,101 @@
+use std::sync::Arc;
+
+use crate::{BoundingBox, CsgOperation, Transform, Vec3};
+
+/// trough this trait we can implement every signed distance field we may need to describe the
+/// implicit shapes of our ray traced world (see the `Sdf` object)
+///
+/// a distance field tells how far a point is from the shape's surface, the distance is negative
+/// inside of the shape; distance fields are shared between the rendering threads so they have to
+/// be `Send` and `Sync`
+pub trait DistanceField: Send + Sync {
+    /// this method should return the signed distance of the point from the surface, it can be
+    /// smaller than the real distance but never bigger (or else the surface gets skipped)
+    fn get_distance(&self, point: &Vec3) -> f64;
+
+    /// this method should return a box containing the whole surface (an infinite box for
+    /// unbounded fields)
+    fn get_bounding_box(&self) -> BoundingBox;
+
+    /// this method returns how much faster than the distance itself the field can change,
+    /// operators that bend the space (like a twist) return more than 1 so that the sphere tracing
+    /// takes smaller steps
+    fn get_lipschitz_bound(&self) -> f64 {
+        1.0
+    }
+}
+
+/// module implementing the operators combining and deforming distance fields
+mod operators;
+/// module implementing the distance fields of the basic shapes
+mod primitives;
+
+// extracting everything we may need
+use operators::{Combination, Displacement, Repetition, Transformed, Twist};
+use primitives::{Capsule, Cuboid, Mandelbulb, Sphere, Torus};
+
+/// enum containing all of the distance fields we can create, the shapes are centered on the
+/// origin and are placed with a `Transformed` field:
+/// - `Sphere`: the radius
+/// - `Box`: the half size along every axis and the radius of the rounded edges
+/// - `Torus`: the major (center to tube) and the minor (tube) radius, it lies on the xz plane
+/// - `Capsule`: the two ends of the segment and the radius around it
+/// - `Mandelbulb`: the power of the fractal and the number of iterations
+/// - `Combine`: the operation combining the two fields
+/// - `SmoothCombine`: like `Combine` but blending the fields over the given distance
+/// - `Repetition`: the field repeated infinitely with the given period along every axis
+/// - `Twist`: the (bounded) field twisted around the y axis by the given angle (radians) per unit
+/// - `Displacement`: the surface moved by a sine wave with the given amplitude and frequency
+/// - `Transformed`: the field moved by the transform (non uniform scales are supported but
+///   make the sphere tracing slower)
+#[allow(clippy::large_enum_variant)]
+pub enum DistanceFields {
+    Sphere(f64),
+    Box(Vec3, f64),
+    Torus(f64, f64),
+    Capsule(Vec3, Vec3, f64),
+    Mandelbulb(f64, usize),
+    Combine(CsgOperation, Arc<dyn DistanceField>, Arc<dyn DistanceField>),
+    SmoothCombine(
+        CsgOperation,
+        Arc<dyn DistanceField>,
+        Arc<dyn DistanceField>,
+        f64,
+    ),
+    Repetition(Arc<dyn DistanceField>, Vec3),
+    Twist(Arc<dyn DistanceField>, f64),
+    Displacement(Arc<dyn DistanceField>, f64, f64),
+    Transformed(Arc<dyn DistanceField>, Transform),
+}
+
+impl DistanceFields {
+    pub fn create_distance_field(field: DistanceFields) -> Box<dyn DistanceField> {
+        match field {
+            DistanceFields::Sphere(radius) => Box::new(Sphere::new(radius)),
+            DistanceFields::Box(half_size, rounding) => Box::new(Cuboid::new(half_size, rounding)),
+            DistanceFields::Torus(major_radius, minor_radius) => {
+                Box::new(Torus::new(major_radius, minor_radius))
+            }
+            DistanceFields::Capsule(start, end, radius) => {
+                Box::new(Capsule::new(start, end, radius))
+            }
+            DistanceFields::Mandelbulb(power, iterations) => {
+                Box::new(Mandelbulb::new(power, iterations))
+            }
+            DistanceFields::Combine(operation, first, second) => {
+                Box::new(Combination::new(operation, first, second, 0.0))
+            }
+            DistanceFields::SmoothCombine(operation, first, second, smoothness) => {
+                Box::new(Combination::new(operation, first, second, smoothness))
+            }
+            DistanceFields::Repetition(field, period) => Box::new(Repetition::new(field, period)),
+            DistanceFields::Twist(field, rate) => Box::new(Twist::new(field, rate)),
+            DistanceFields::Displacement(field, amplitude, frequency) => {
+                Box::new(Displacement::new(field, amplitude, frequency))
+            }
+            DistanceFields::Transformed(field, transform) => {
+                Box::new(Transformed::new(field, transform))
+            }
+        }
+    }
+}
@@ -0,0 +1,299 @@
+use std::sync::Arc;
+
+use super::DistanceField;
+use crate::{BoundingBox, CsgOperation, Transform, Vec3};
+
+/// the number of power iterations used to find how much a transform can stretch the space
+const STRETCH_ITERATIONS: usize = 32;
+
+/// the union, intersection or difference of two distance fields, optionally blended together
+pub struct Combination {
+    operation: CsgOperation,
+    first: Arc<dyn DistanceField>,
+    second: Arc<dyn DistanceField>,
+    smoothness: f64,
+}
+
+impl Combination {
+    /// creates a new combination, the fields are blended over the `smoothness` distance (a
+    /// sharp combination has a smoothness of 0)
+    pub fn new(
+        operation: CsgOperation,
+        first: Arc<dyn DistanceField>,
+        second: Arc<dyn DistanceField>,
+        smoothness: f64,
+    ) -> Self {
+        Self {
+            operation,
+            first,
+            second,
+            smoothness: smoothness.max(0.0),
+        }
+    }
+
+    /// the polynomial smooth minimum of the two distances
+    fn smooth_min(&self, distance: f64, other: f64) -> f64 {
+        if self.smoothness <= 0.0 {
+            return distance.min(other);
+        }
+
+        let blend = (self.smoothness - (distance - other).abs()).max(0.0) / self.smoothness;
+
+        distance.min(other) - blend * blend * self.smoothness / 4.0
+    }
+}
+
+impl DistanceField for Combination {
+    fn get_distance(&self, point: &Vec3) -> f64 {
+        let distance = self.first.get_distance(point);
+        let other = self.second.get_distance(point);
+
+        // the intersection and the difference are unions of the fields' complements
+        match self.operation {
+            CsgOperation::Union => self.smooth_min(distance, other),
+            CsgOperation::Intersection => -self.smooth_min(-distance, -other),
+            CsgOperation::Difference => -self.smooth_min(-distance, other),
+        }
+    }
+
+    fn get_bounding_box(&self) -> BoundingBox {
+        let first_box = self.first.get_bounding_box();
+        let second_box = self.second.get_bounding_box();
+
+        // a smooth union grows the surface between the fields by at most a quarter of the
+        // smoothness
+        match self.operation {
+            CsgOperation::Union => first_box.union(&second_box).pad(self.smoothness / 4.0),
+            CsgOperation::Intersection => first_box.intersection(&second_box),
+            CsgOperation::Difference => first_box,
+        }
+    }
+
+    fn get_lipschitz_bound(&self) -> f64 {
+        self.first
+            .get_lipschitz_bound()
+            .max(self.second.get_lipschitz_bound())
+    }
+}
+
+/// a distance field repeated infinitely along every axis, the field should fit inside of a
+/// single period
+pub struct Repetition {
+    field: Arc<dyn DistanceField>,
+    period: Vec3,
+}
+
+impl Repetition {
+    /// creates a new repetition, an axis with a period of 0 isn't repeated
+    pub fn new(field: Arc<dyn DistanceField>, period: Vec3) -> Self {
+        Self { field, period }
+    }
+}
+
+impl DistanceField for Repetition {
+    fn get_distance(&self, point: &Vec3) -> f64 {
+        // every point is moved in the period around the origin
+        let repeat = |coordinate: f64, period: f64| {
+            if period > 0.0 {
+                coordinate - period * (coordinate / period).round()
+            } else {
+                coordinate
+            }
+        };
+
+        self.field.get_distance(&Vec3::new(
+            repeat(*point.get_x(), *self.period.get_x()),
+            repeat(*point.get_y(), *self.period.get_y()),
+            repeat(*point.get_z(), *self.period.get_z()),
+        ))
+    }
+
+    fn get_bounding_box(&self) -> BoundingBox {
+        let field_box = self.field.get_bounding_box();
+        let (min, max) = (field_box.get_min(), field_box.get_max());
+
+        // only the repeated axes are unbounded
+        let bound = |axis: usize, value: f64, infinity: f64| {
+            if self.period.get_axis(axis) > 0.0 {
+                infinity
+            } else {
+                value
+            }
+        };
+
+        BoundingBox::new(
+            Vec3::new(
+                bound(0, *min.get_x(), f64::NEG_INFINITY),
+                bound(1, *min.get_y(), f64::NEG_INFINITY),
+                bound(2, *min.get_z(), f64::NEG_INFINITY),
+            ),
+            Vec3::new(
+                bound(0, *max.get_x(), f64::INFINITY),
+                bound(1, *max.get_y(), f64::INFINITY),
+                bound(2, *max.get_z(), f64::INFINITY),
+            ),
+        )
+    }
+
+    fn get_lipschitz_bound(&self) -> f64 {
+        self.field.get_lipschitz_bound()
+    }
+}
+
+/// a distance field twisted around the y axis
+pub struct Twist {
+    field: Arc<dyn DistanceField>,
+    rate: f64,
+    radius: f64,
+}
+
+impl Twist {
+    /// creates a new twist rotating the field by `rate` radians for every unit along the y axis
+    pub fn new(field: Arc<dyn DistanceField>, rate: f64) -> Self {
+        let field_box = field.get_bounding_box();
+        let (min, max) = (field_box.get_min(), field_box.get_max());
+
+        // the farthest the field gets from the y axis
+        let radius = (min.get_x().abs().max(max.get_x().abs()).powi(2)
+            + min.get_z().abs().max(max.get_z().abs()).powi(2))
+        .sqrt();
+
+        Self {
+            field,
+            rate,
+            radius,
+        }
+    }
+}
+
+impl DistanceField for Twist {
+    fn get_distance(&self, point: &Vec3) -> f64 {
+        let angle = self.rate * point.get_y();
+        let (sin, cos) = angle.sin_cos();
+
+        self.field.get_distance(&Vec3::new(
+            cos * point.get_x() - sin * point.get_z(),
+            *point.get_y(),
+            sin * point.get_x() + cos * point.get_z(),
+        ))
+    }
+
+    fn get_bounding_box(&self) -> BoundingBox {
+        let field_box = self.field.get_bounding_box();
+
+        BoundingBox::new(
+            Vec3::new(-self.radius, *field_box.get_min().get_y(), -self.radius),
+            Vec3::new(self.radius, *field_box.get_max().get_y(), self.radius),
+        )
+    }
+
+    fn get_lipschitz_bound(&self) -> f64 {
+        // the twist stretches the space the most at the farthest point from the axis
+        self.field.get_lipschitz_bound() * (1.0 + (self.rate * self.radius).powi(2)).sqrt()
+    }
+}
+
+/// a distance field whose surface is moved by a sine wave
+pub struct Displacement {
+    field: Arc<dyn DistanceField>,
+    amplitude: f64,
+    frequency: f64,
+}
+
+impl Displacement {
+    pub fn new(field: Arc<dyn DistanceField>, amplitude: f64, frequency: f64) -> Self {
+        Self {
+            field,
+            amplitude,
+            frequency,
+        }
+    }
+}
+
+impl DistanceField for Displacement {
+    fn get_distance(&self, point: &Vec3) -> f64 {
+        let wave = (self.frequency * point.get_x()).sin()
+            * (self.frequency * point.get_y()).sin()
+            * (self.frequency * point.get_z()).sin();
+
+        self.field.get_distance(point) + self.amplitude * wave
+    }
+
+    fn get_bounding_box(&self) -> BoundingBox {
+        self.field.get_bounding_box().pad(self.amplitude.abs())
+    }
+
+    fn get_lipschitz_bound(&self) -> f64 {
+        // the steepest the wave can get
+        self.field.get_lipschitz_bound()
+            + self.amplitude.abs() * self.frequency.abs() * 3.0_f64.sqrt()
+    }
+}
+
+/// a distance field moved by a transform
+pub struct Transformed {
+    field: Arc<dyn DistanceField>,
+    transform: Transform,
+    inverse: Transform,
+    stretch: f64,
+}
+
+impl Transformed {
+    pub fn new(field: Arc<dyn DistanceField>, transform: Transform) -> Self {
+        // the field is evaluated in its own space, a world distance can become up to `stretch`
+        // times longer there (the biggest singular value of the inverse transform found by
+        // power iteration)
+        let inverse = transform.get_inverse();
+        let mut vector = Vec3::new(1.0, 1.0, 1.0);
+        let mut stretch = 1.0;
+
+        for _ in 0..STRETCH_ITERATIONS {
+            let stretched = inverse.transform_vector(&vector);
+            let length = stretched.get_length();
+
+            if length <= 0.0 {
+                break;
+            }
+
+            stretch = length / vector.get_length();
+
+            // going back with the transposed matrix converges to the most stretched direction
+            let matrix = inverse.get_matrix();
+            vector = Vec3::new(
+                (0..3)
+                    .map(|row| matrix[row][0] * stretched.get_axis(row))
+                    .sum(),
+                (0..3)
+                    .map(|row| matrix[row][1] * stretched.get_axis(row))
+                    .sum(),
+                (0..3)
+                    .map(|row| matrix[row][2] * stretched.get_axis(row))
+                    .sum(),
+            );
+            vector.make_unit();
+        }
+
+        Self {
+            field,
+            transform,
+            inverse,
+            stretch,
+        }
+    }
+}
+
+impl DistanceField for Transformed {
+    fn get_distance(&self, point: &Vec3) -> f64 {
+        self.field
+            .get_distance(&self.inverse.transform_point(point))
+    }
+
+    fn get_bounding_box(&self) -> BoundingBox {
+        self.transform
+            .transform_bounding_box(&self.field.get_bounding_box())
+    }
+
+    fn get_lipschitz_bound(&self) -> f64 {
+        self.field.get_lipschitz_bound() * self.stretch
+    }
+}
@@ -0,0 +1,202 @@
+use super::DistanceField;
+use crate::{BoundingBox, Vec3};
+
+/// the radius of the sphere containing the mandelbulb (for powers up to about 10)
+const MANDELBULB_RADIUS: f64 = 1.2;
+/// the distance from the origin after which a point of the mandelbulb's iteration escapes
+const MANDELBULB_BAILOUT: f64 = 2.0;
+
+/// the distance field of a sphere centered on the origin
+pub struct Sphere {
+    radius: f64,
+}
+
+impl Sphere {
+    pub fn new(radius: f64) -> Self {
+        Self { radius }
+    }
+}
+
+impl DistanceField for Sphere {
+    fn get_distance(&self, point: &Vec3) -> f64 {
+        point.get_length() - self.radius
+    }
+
+    fn get_bounding_box(&self) -> BoundingBox {
+        BoundingBox::new(
+            Vec3::new(-self.radius, -self.radius, -self.radius),
+            Vec3::new(self.radius, self.radius, self.radius),
+        )
+    }
+}
+
+/// the distance field of a box (with rounded edges) centered on the origin
+pub struct Cuboid {
+    half_size: Vec3,
+    rounding: f64,
+}
+
+impl Cuboid {
+    /// creates a new box, the edges are rounded by `rounding` without growing the box
+    pub fn new(half_size: Vec3, rounding: f64) -> Self {
+        let smallest_half_size = half_size
+            .get_x()
+            .min(*half_size.get_y())
+            .min(*half_size.get_z());
+
+        Self {
+            half_size,
+            rounding: rounding.clamp(0.0, smallest_half_size),
+        }
+    }
+}
+
+impl DistanceField for Cuboid {
+    fn get_distance(&self, point: &Vec3) -> f64 {
+        let inner_half_size = self.half_size - self.rounding;
+        let offset = Vec3::new(
+            point.get_x().abs() - inner_half_size.get_x(),
+            point.get_y().abs() - inner_half_size.get_y(),
+            point.get_z().abs() - inner_half_size.get_z(),
+        );
+
+        let outside = Vec3::new(
+            offset.get_x().max(0.0),
+            offset.get_y().max(0.0),
+            offset.get_z().max(0.0),
+        );
+        let inside = offset
+            .get_x()
+            .max(*offset.get_y())
+            .max(*offset.get_z())
+            .min(0.0);
+
+        outside.get_length() + inside - self.rounding
+    }
+
+    fn get_bounding_box(&self) -> BoundingBox {
+        BoundingBox::new(self.half_size.get_inverse(), self.half_size)
+    }
+}
+
+/// the distance field of a torus centered on the origin and lying on the xz plane
+pub struct Torus {
+    major_radius: f64,
+    minor_radius: f64,
+}
+
+impl Torus {
+    pub fn new(major_radius: f64, minor_radius: f64) -> Self {
+        Self {
+            major_radius,
+            minor_radius,
+        }
+    }
+}
+
+impl DistanceField for Torus {
+    fn get_distance(&self, point: &Vec3) -> f64 {
+        let radial_distance =
+            (point.get_x().powi(2) + point.get_z().powi(2)).sqrt() - self.major_radius;
+
+        (radial_distance.powi(2) + point.get_y().powi(2)).sqrt() - self.minor_radius
+    }
+
+    fn get_bounding_box(&self) -> BoundingBox {
+        let radius = self.major_radius + self.minor_radius;
+
+        BoundingBox::new(
+            Vec3::new(-radius, -self.minor_radius, -radius),
+            Vec3::new(radius, self.minor_radius, radius),
+        )
+    }
+}
+
+/// the distance field of a capsule, the points closer than its radius to a segment
+pub struct Capsule {
+    start: Vec3,
+    end: Vec3,
+    radius: f64,
+}
+
+impl Capsule {
+    pub fn new(start: Vec3, end: Vec3, radius: f64) -> Self {
+        Self { start, end, radius }
+    }
+}
+
+impl DistanceField for Capsule {
+    fn get_distance(&self, point: &Vec3) -> f64 {
+        let segment = self.end - self.start;
+        let offset = (*point) - self.start;
+
+        // the closest point of the segment as a fraction of its length
+        let length_squared = segment.dot_product(&segment);
+        let fraction = if length_squared > 0.0 {
+            (offset.dot_product(&segment) / length_squared).clamp(0.0, 1.0)
+        } else {
+            0.0
+        };
+
+        (offset - segment * fraction).get_length() - self.radius
+    }
+
+    fn get_bounding_box(&self) -> BoundingBox {
+        BoundingBox::new(self.start, self.end).pad(self.radius)
+    }
+}
+
+/// the (estimated) distance field of the mandelbulb fractal
+pub struct Mandelbulb {
+    power: f64,
+    iterations: usize,
+}
+
+impl Mandelbulb {
+    pub fn new(power: f64, iterations: usize) -> Self {
+        Self { power, iterations }
+    }
+}
+
+impl DistanceField for Mandelbulb {
+    fn get_distance(&self, point: &Vec3) -> f64 {
+        let mut z = *point;
+        let mut derivative = 1.0;
+        let mut radius = z.get_length();
+
+        for _ in 0..self.iterations {
+            // the origin is a fixed point of the iteration (and has no angles)
+            if radius > MANDELBULB_BAILOUT || radius <= 0.0 {
+                break;
+            }
+
+            // z = z^power + point, raising to a power in spherical coordinates
+            let theta = (z.get_z() / radius).clamp(-1.0, 1.0).acos() * self.power;
+            let phi = z.get_y().atan2(*z.get_x()) * self.power;
+
+            derivative = radius.powf(self.power - 1.0) * self.power * derivative + 1.0;
+
+            let scaled_radius = radius.powf(self.power);
+            z = Vec3::new(
+                theta.sin() * phi.cos(),
+                theta.sin() * phi.sin(),
+                theta.cos(),
+            ) * scaled_radius
+                + (*point);
+            radius = z.get_length();
+        }
+
+        if radius <= 0.0 {
+            return 0.0;
+        }
+
+        0.5 * radius.ln() * radius / derivative
+    }
+
+    fn get_bounding_box(&self) -> BoundingBox {
+        BoundingBox::new(
+            Vec3::new(-MANDELBULB_RADIUS, -MANDELBULB_RADIUS, -MANDELBULB_RADIUS),
+            Vec3::new(MANDELBULB_RADIUS, MANDELBULB_RADIUS, MANDELBULB_RADIUS),
+        )
+    }
+}
@@ -0,0 +1,305 @@
+use crate::{Bsdf, Light, LightSample, Material, Object, ObjectRayIntersection, Ray, Scene, Vec3};
+use canvas::Color;
+use rand::RngCore;
+
+/// trough this trait we can implement every way of computing the light seen along a ray
+///
+/// an integrator is the only place where the materials are shaded, the lights are only asked
+/// for samples of the light they send to a point (see [`crate::LightSample`]); integrators are
+/// shared between the rendering threads so they have to be `Send` and `Sync`
+pub trait Integrator: Send + Sync {
+    /// this function should return the light (radiance) coming along the ray, the objects closer
+    /// than `min_t` are ignored
+    fn compute_color(&self, ray: Ray, scene: &Scene, min_t: f64, rng: &mut dyn RngCore) -> Color;
+}
+
+/// module to implement a Monte Carlo path tracer
+mod path_integrator;
+/// module to implement a Whitted ray tracer
+mod whitted_integrator;
+
+// extracting everything we may need
+use path_integrator::PathIntegrator;
+use whitted_integrator::WhittedIntegrator;
+
+/// how far from a surface the rays leaving it start to be checked (to avoid hitting the surface
+/// itself)
+const SURFACE_OFFSET: f64 = 0.001;
+
+/// enum containing all of the integrator's types we can create, every integrator takes the
+/// maximum number of bounces of a ray and the color seen by the rays leaving the scene
+pub enum Integrators {
+    PathTracing(u8, Color),
+    Whitted(u8, Color),
+}
+
+impl Integrators {
+    pub fn create_integrator(integrator: Integrators) -> Box<dyn Integrator> {
+        match integrator {
+            Integrators::PathTracing(max_depth, background_color) => {
+                Box::new(PathIntegrator::new(max_depth, background_color))
+            }
+            Integrators::Whitted(max_depth, background_color) => {
+                Box::new(WhittedIntegrator::new(max_depth, background_color))
+            }
+        }
+    }
+}
+
+/// a medium a ray is travelling trough, the inside of a refractive object
+#[derive(Clone, Copy)]
+struct Medium<'a> {
+    /// the id of the object (see `HitRecord::get_object_id`)
+    object_id: usize,
+    material: &'a Material,
+    ior: f64,
+}
+
+/// the media containing a ray, from the outermost to the innermost one
+///
+/// a ray refracted into a material enters its medium and a ray refracted out of it leaves it, so
+/// media can be nested (an ice cube in a glass of water): the index of refraction outside of a
+/// surface is the one of the innermost other medium and the light is absorbed by the innermost
+/// medium only; media are told apart by the id of their object, since instances of the same
+/// object share its material
+#[derive(Clone, Default)]
+struct MediumStack<'a> {
+    media: Vec<Medium<'a>>,
+}
+
+impl<'a> MediumStack<'a> {
+    /// the ray enters the object made of the material, whose inside has the given index of
+    /// refraction
+    fn enter(&mut self, object_id: usize, material: &'a Material, ior: f64) {
+        self.media.push(Medium {
+            object_id,
+            material,
+            ior,
+        });
+    }
+
+    /// the ray leaves the object (nothing happens if it wasn't inside of it)
+    fn exit(&mut self, object_id: usize) {
+        if let Some(index) = self
+            .media
+            .iter()
+            .rposition(|medium| medium.object_id == object_id)
+        {
+            self.media.remove(index);
+        }
+    }
+
+    /// enters or leaves the object depending on the side of the surface the ray refracted from
+    fn refract(&mut self, object_id: usize, material: &'a Material, ior: f64, entering: bool) {
+        if entering {
+            self.enter(object_id, material, ior);
+        } else {
+            self.exit(object_id);
+        }
+    }
+
+    /// retrieves the index of refraction around the object's surface (the one of the innermost
+    /// other medium, 1 if there's none)
+    fn get_outside_ior(&self, object_id: usize) -> f64 {
+        self.media
+            .iter()
+            .rev()
+            .find(|medium| medium.object_id != object_id)
+            .map_or(1.0, |medium| medium.ior)
+    }
+
+    /// retrieves the fraction of the light left after travelling `distance` units in the
+    /// innermost medium
+    fn get_transmittance(&self, distance: f64) -> Color {
+        self.media
+            .last()
+            .map_or(Color::new(1.0, 1.0, 1.0), |medium| {
+                medium.material.get_transmittance(distance)
+            })
+    }
+}
+
+/// samples the light reaching the point from the given light, None is returned if the light
+/// can't reach the point or if something casts a shadow on it
+fn sample_unoccluded_light(
+    light: &dyn Light,
+    scene: &Scene,
+    point: &Vec3,
+    rng: &mut dyn RngCore,
+) -> Option<LightSample> {
+    let sample = light.sample_illumination(point, rng)?;
+
+    is_unoccluded(&sample, scene, point).then_some(sample)
+}
+
+/// samples the light reaching the point from a point of the emissive object's surface, None is
+/// returned if the chosen point faces away from the point or if something casts a shadow on it
+fn sample_unoccluded_emitter(
+    object: &dyn Object,
+    scene: &Scene,
+    point: &Vec3,
+    rng: &mut dyn RngCore,
+) -> Option<LightSample> {
+    let emission = object.get_material().get_emission()?;
+    let (light_point, light_normal) = object.sample_surface(rng)?;
+    let light_direction = light_point - (*point);
+    let light_length = light_direction.get_length();
+
+    // only the front face glows
+    let light_cos = -light_normal.dot_product(&light_direction) / light_length;
+
+    if light_cos <= 0.0 || light_length == 0.0 {
+        return None;
+    }
+
+    // the pdf is converted from the surface's area to the solid angle seen by the point
+    let sample = LightSample::new(
+        point,
+        light_point,
+        emission,
+        object.get_surface_pdf(&light_point, &light_normal) * (light_length * light_length)
+            / light_cos,
+    );
+
+    is_unoccluded(&sample, scene, point).then_some(sample)
+}
+
+/// checks that the light of the sample reaches the point, the light is blocked by anything
+/// between them (but the light objects when the sample says so)
+fn is_unoccluded(sample: &LightSample, scene: &Scene, point: &Vec3) -> bool {
+    if sample.get_pdf() <= 0.0 {
+        return false;
+    }
+
+    let shadow_ray = Ray::new(*point, *sample.get_direction());
+    let max_t = (sample.get_distance() - SURFACE_OFFSET).min(f64::MAX);
+
+    if sample.is_blocked_by_lights() {
+        ObjectRayIntersection::check_intersection(shadow_ray, scene, SURFACE_OFFSET, max_t)
+            .is_none()
+    } else {
+        ObjectRayIntersection::check_object_intersection(shadow_ray, scene, SURFACE_OFFSET, max_t)
+            .is_none()
+    }
+}
+
+/// retrieves the light emitted by the hit object towards the ray (emissive materials glow on the
+/// front face only)
+fn get_emitted_light(intersection: &ObjectRayIntersection) -> Option<Color> {
+    if !intersection.get_hit_record().is_front_face() {
+        return None;
+    }
+
+    intersection.get_hit_object().get_material().get_emission()
+}
+
+/// flips the (unit) normal so that it points against the ray's direction
+fn facing_normal(normal: &Vec3, direction: &Vec3) -> Vec3 {
+    if direction.dot_product(normal) > 0.0 {
+        normal.get_inverse()
+    } else {
+        *normal
+    }
+}
+
+/// refracts the (unit) direction trough a surface with the given refraction index, the normal
+/// can point to either side of the surface; None is returned on total internal reflection
+fn refract(direction: &Vec3, normal: &Vec3, refraction: f64) -> Option<Vec3> {
+    // check where the ray is coming from (inside the material or outside of it)
+    let refraction_index = if direction.dot_product(normal) < 0.0 {
+        1.0 / refraction
+    } else {
+        refraction
+    };
+    let normal = facing_normal(normal, direction);
+
+    let cos_incident = (-direction.dot_product(&normal)).min(1.0);
+    let sin_refracted_squared =
+        refraction_index * refraction_index * (1.0 - cos_incident * cos_incident).max(0.0);
+
+    if sin_refracted_squared >= 1.0 {
+        return None;
+    }
+
+    Some(
+        (*direction) * refraction_index
+            + normal * (refraction_index * cos_incident - (1.0 - sin_refracted_squared).sqrt()),
+    )
+}
+
+/// the fraction of the light reflected by a surface with the given refraction index (the inside
+/// over the outside) with the Fresnel equations for unpolarized light, the normal can point to
+/// either side of the surface; total internal reflection reflects everything
+fn get_fresnel_reflectance(direction: &Vec3, normal: &Vec3, refraction: f64) -> f64 {
+    // the index of refraction of the side the light goes to over the one it comes from
+    let relative_ior = if direction.dot_product(normal) < 0.0 {
+        refraction
+    } else {
+        1.0 / refraction
+    };
+
+    let cos_incident = direction.dot_product(normal).abs().min(1.0);
+    let sin_refracted_squared = (1.0 - cos_incident * cos_incident) / (relative_ior * relative_ior);
+
+    if sin_refracted_squared >= 1.0 {
+        return 1.0;
+    }
+
+    let cos_refracted = (1.0 - sin_refracted_squared).sqrt();
+    let perpendicular = (cos_incident - relative_ior * cos_refracted)
+        / (cos_incident + relative_ior * cos_refracted);
+    let parallel = (relative_ior * cos_incident - cos_refracted)
+        / (relative_ior * cos_incident + cos_refracted);
+
+    (perpendicular * perpendicular + parallel * parallel) / 2.0
+}
+
+/// clamps the channels of a surface's color to 1, a reflectance can't be bigger than that (a
+/// texture is added to the color)
+fn get_albedo(color: &Color) -> Color {
+    Color::new(
+        color.get_red().clamp(0.0, 1.0),
+        color.get_green().clamp(0.0, 1.0),
+        color.get_blue().clamp(0.0, 1.0),
+    )
+}
+
+/// the light of the sample scattered by the bsdf towards the `outgoing` direction (pointing
+/// away from the surface)
+fn get_scattered_light(bsdf: &Bsdf, outgoing: &Vec3, normal: &Vec3, sample: &LightSample) -> Color {
+    let light_cos = normal.dot_product(sample.get_direction()).abs();
+
+    (*sample.get_radiance())
+        * bsdf.evaluate(outgoing, sample.get_direction())
+        * (light_cos / sample.get_pdf())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::MaterialBuilder;
+
+    #[test]
+    fn media_sharing_a_material_are_told_apart() {
+        // two instances of the same glass object, the second one inside of the first one
+        let glass = MaterialBuilder::default().set_refraction(1.5).build();
+        let water = MaterialBuilder::default().set_refraction(1.33).build();
+        let mut media = MediumStack::default();
+
+        media.enter(1, &water, 1.33);
+        media.enter(2, &glass, 1.5);
+        assert_eq!(media.get_outside_ior(2), 1.33);
+
+        // the surface of the inner instance is surrounded by the outer one
+        media.enter(3, &glass, 1.5);
+        assert_eq!(media.get_outside_ior(3), 1.5);
+
+        media.exit(2);
+        assert_eq!(media.get_outside_ior(3), 1.33);
+
+        media.exit(3);
+        media.exit(3);
+        assert_eq!(media.get_outside_ior(3), 1.33);
+        assert_eq!(media.get_outside_ior(1), 1.0);
+    }
+}
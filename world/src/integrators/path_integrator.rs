@@ -0,0 +1,367 @@
+use super::{
+    MediumStack, SURFACE_OFFSET, facing_normal, get_albedo, get_emitted_light,
+    get_fresnel_reflectance, get_scattered_light, refract, sample_unoccluded_emitter,
+    sample_unoccluded_light,
+};
+use crate::{Bsdf, BsdfLobe, Integrator, LightSample, ObjectRayIntersection, Ray, Scene, Vec3};
+use canvas::Color;
+use rand::{Rng, RngCore};
+use std::f64::consts::PI;
+
+/// the number of bounces after which paths may be terminated by russian roulette
+const ROULETTE_START_DEPTH: u8 = 3;
+
+/// Object abstracting a Monte Carlo path tracer
+///
+/// trough the use of this object we can compute the light reaching the camera with global
+/// illumination: diffuse surfaces bounce the light in a random (cosine weighted) direction and
+/// are lit directly by sampling the scene's lights (next event estimation), mirrors and
+/// refractive materials are followed like in the Whitted integrator; long paths are terminated
+/// with russian roulette
+///
+/// The material's color is used as the diffuse reflectance, its reflectiveness as the chance of
+/// a mirror reflection and its transparency as the chance of a refraction (split between
+/// reflection and refraction with the Fresnel equations); ambient lights and the specularity
+/// are ignored since the indirect light already accounts for them
+///
+/// Physically based materials (see [`crate::Bsdf`]) ignore those parameters: the direction of
+/// every bounce is sampled from their bsdf and the lights are sampled for every lobe that isn't a
+/// perfect mirror or refraction
+///
+/// Objects with an emissive material are sampled like the lights (if their surface can be
+/// sampled, see [`crate::Object::sample_surface`]) and are seen when hit by the path
+///
+/// The path keeps track of the refractive objects it's inside of, so nested media refract with
+/// the right ratio of indices and the light travelling trough a material is absorbed by it
+pub struct PathIntegrator {
+    max_depth: u8,
+    background_color: Color,
+}
+
+impl PathIntegrator {
+    /// creates a new path integrator, a path is never longer than `max_depth` bounces
+    pub fn new(max_depth: u8, background_color: Color) -> Self {
+        Self {
+            max_depth,
+            background_color,
+        }
+    }
+
+    /// estimates the light reflected by a diffuse surface (with a white albedo) coming directly
+    /// from the scene's lights
+    fn sample_lights(
+        &self,
+        scene: &Scene,
+        point: &Vec3,
+        normal: &Vec3,
+        rng: &mut dyn RngCore,
+    ) -> Color {
+        let mut direct_color = Color::new(0.0, 0.0, 0.0);
+
+        for sample in self.sample_every_light(scene, point, rng) {
+            let light_cos = normal.dot_product(sample.get_direction());
+
+            if light_cos > 0.0 {
+                direct_color += (*sample.get_radiance()) * (light_cos / (PI * sample.get_pdf()));
+            }
+        }
+
+        direct_color
+    }
+
+    /// estimates the light coming directly from the scene's lights scattered by the bsdf
+    /// towards the `outgoing` direction
+    fn sample_bsdf_lights(
+        &self,
+        scene: &Scene,
+        point: &Vec3,
+        bsdf: &Bsdf,
+        outgoing: &Vec3,
+        normal: &Vec3,
+        rng: &mut dyn RngCore,
+    ) -> Color {
+        let mut direct_color = Color::new(0.0, 0.0, 0.0);
+
+        for sample in self.sample_every_light(scene, point, rng) {
+            direct_color += get_scattered_light(bsdf, outgoing, normal, &sample);
+        }
+
+        direct_color
+    }
+
+    /// samples every light and every emissive object once, the samples blocked by something are
+    /// left out
+    fn sample_every_light(
+        &self,
+        scene: &Scene,
+        point: &Vec3,
+        rng: &mut dyn RngCore,
+    ) -> Vec<LightSample> {
+        let mut samples = vec![];
+
+        for light in scene.get_lights() {
+            samples.extend(sample_unoccluded_light(light.as_ref(), scene, point, rng));
+        }
+
+        for emitter in scene.get_emitters() {
+            samples.extend(sample_unoccluded_emitter(emitter, scene, point, rng));
+        }
+
+        samples
+    }
+}
+
+impl Integrator for PathIntegrator {
+    fn compute_color(&self, ray: Ray, scene: &Scene, min_t: f64, rng: &mut dyn RngCore) -> Color {
+        let mut final_color = Color::new(0.0, 0.0, 0.0);
+        // how much of the light found along the path reaches the camera
+        let mut throughput = Color::new(1.0, 1.0, 1.0);
+        let mut ray = ray;
+        let mut min_t = min_t;
+        // the light objects are already sampled directly after a diffuse bounce, so they're
+        // counted when hit only by the camera or after a mirror or refraction
+        let mut count_lights = true;
+        // the refractive objects the path is inside of
+        let mut media = MediumStack::default();
+
+        for depth in 0..=self.max_depth {
+            let Some(intersection) =
+                ObjectRayIntersection::check_intersection(ray, scene, min_t, f64::MAX)
+            else {
+                final_color += throughput * self.background_color;
+                break;
+            };
+
+            let point = *intersection.get_hit_point();
+            let object = intersection.get_hit_object();
+            let direction = *intersection.get_ray().get_direction();
+
+            let hit = intersection.get_hit_record();
+            let normal = *hit.get_shading_normal();
+            // whether the ray comes from outside of the hit object
+            let entering = direction.dot_product(&normal) < 0.0;
+
+            // the light is absorbed along the way trough the medium
+            throughput = throughput * media.get_transmittance(hit.get_t());
+
+            // the emitters that can be sampled are already sampled directly like the lights
+            if let Some(emission) = get_emitted_light(&intersection)
+                && (count_lights || object.get_area() <= 0.0)
+            {
+                final_color += throughput * emission;
+            }
+
+            if intersection.is_light_hit() {
+                // lights shine only on the side their normal points to
+                if count_lights && hit.is_front_face() {
+                    final_color += throughput * intersection.get_hit_color();
+                }
+
+                break;
+            }
+
+            if depth == self.max_depth {
+                break;
+            }
+
+            let material = object.get_material();
+
+            let refraction = *material.get_refraction();
+            // the transparency is meaningful only for refractive materials
+            let transparency = refraction.and(*material.get_transparency()).unwrap_or(0.0);
+            let reflectiveness = material.get_reflectiveness().unwrap_or(0.0);
+            let choice: f64 = rng.random();
+
+            let next_direction = if let Some(bsdf) = material.get_bsdf(
+                hit,
+                get_albedo(&intersection.get_hit_color()),
+                media.get_outside_ior(hit.get_object_id()),
+            ) {
+                let outgoing = direction.get_inverse();
+
+                final_color += throughput
+                    * self.sample_bsdf_lights(scene, &point, &bsdf, &outgoing, &normal, rng);
+
+                // the sampled direction may be absorbed by the surface
+                let Some(sample) = bsdf.sample(&outgoing, rng, true) else {
+                    break;
+                };
+
+                throughput = throughput * (*sample.get_weight());
+                // the lights are already sampled for every lobe but the perfect ones
+                count_lights = sample.is_specular();
+
+                if sample.get_lobe() == BsdfLobe::Transmission
+                    && let Some(ior) = material.get_ior()
+                {
+                    media.refract(hit.get_object_id(), material, ior, entering);
+                }
+
+                *sample.get_direction()
+            } else if let Some(refraction) = refraction
+                && choice < transparency
+            {
+                count_lights = true;
+
+                let relative_refraction = refraction / media.get_outside_ior(hit.get_object_id());
+
+                match refract_or_reflect(&direction, &normal, relative_refraction, rng) {
+                    Some(refracted) => {
+                        media.refract(hit.get_object_id(), material, refraction, entering);
+
+                        refracted
+                    }
+                    None => direction
+                        .get_inverse()
+                        .reflect(&facing_normal(&normal, &direction)),
+                }
+            } else if choice < transparency + (1.0 - transparency) * reflectiveness {
+                count_lights = true;
+
+                direction
+                    .get_inverse()
+                    .reflect(&facing_normal(&normal, &direction))
+            } else {
+                let normal = facing_normal(&normal, &direction);
+                let albedo = get_albedo(&intersection.get_hit_color());
+
+                final_color +=
+                    throughput * albedo * self.sample_lights(scene, &point, &normal, rng);
+
+                // with cosine weighted directions the lambertian brdf and the cosine cancel out
+                // with the pdf, leaving only the albedo
+                throughput = throughput * albedo;
+                count_lights = false;
+
+                sample_cosine_direction(&normal, rng)
+            };
+
+            // russian roulette: dim paths are terminated and the surviving ones are brightened to
+            // keep the estimate unbiased
+            if depth >= ROULETTE_START_DEPTH {
+                let survival = throughput.get_max_channel().clamp(0.05, 0.95);
+
+                if rng.random::<f64>() >= survival {
+                    break;
+                }
+
+                throughput = throughput / survival;
+            }
+
+            ray = Ray::new(point, next_direction);
+            min_t = SURFACE_OFFSET;
+        }
+
+        final_color
+    }
+}
+
+/// chooses between refracting and reflecting the direction with the Fresnel equations, the
+/// refracted direction is returned (None for a reflection)
+fn refract_or_reflect(
+    direction: &Vec3,
+    normal: &Vec3,
+    refraction: f64,
+    rng: &mut dyn RngCore,
+) -> Option<Vec3> {
+    if rng.random::<f64>() < get_fresnel_reflectance(direction, normal, refraction) {
+        return None;
+    }
+
+    refract(direction, normal, refraction)
+}
+
+/// chooses a random direction on the hemisphere around the normal, directions close to the
+/// normal are more likely (with a pdf of cos / pi)
+fn sample_cosine_direction(normal: &Vec3, rng: &mut dyn RngCore) -> Vec3 {
+    let radius_squared: f64 = rng.random();
+    let angle = 2.0 * PI * rng.random::<f64>();
+    let radius = radius_squared.sqrt();
+
+    // an orthonormal basis around the normal
+    let tangent = normal.get_perpendicular();
+    let bitangent = normal.cross_product(&tangent);
+
+    tangent * (radius * angle.cos())
+        + bitangent * (radius * angle.sin())
+        + (*normal) * (1.0 - radius_squared).max(0.0).sqrt()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{MaterialBuilder, Object, Objects, Transform};
+    use rand::SeedableRng;
+    use rand::rngs::StdRng;
+    use std::sync::Arc;
+
+    const SAMPLES: usize = 200_000;
+
+    /// a diffuse floor lit by a glowing sphere stretched into an ellipsoid
+    fn scene() -> Scene {
+        let mut scene = Scene::new();
+        let sphere: Arc<dyn Object> = Arc::from(Objects::create_object(Objects::Sphere(
+            Vec3::new(0.0, 0.0, 0.0),
+            1.0,
+            MaterialBuilder::default()
+                .set_emission(Color::new(1.0, 1.0, 1.0), 1.0)
+                .build(),
+        )));
+
+        scene.add_object(Objects::create_object(Objects::Plane(
+            Vec3::new(0.0, 0.0, 0.0),
+            Vec3::new(0.0, 1.0, 0.0),
+            MaterialBuilder::default().build(),
+        )));
+        scene.add_object(Objects::create_object(Objects::Instance(
+            sphere,
+            Transform::scale(Vec3::new(3.0, 0.25, 1.0))
+                .then(&Transform::rotate_z(0.5))
+                .then(&Transform::translate(Vec3::new(1.0, 2.0, 0.0))),
+        )));
+        scene.build_bvh();
+
+        scene
+    }
+
+    /// estimates the light reflected by a white diffuse point of the floor, either sampling the
+    /// emitter directly or following cosine weighted directions until they hit it
+    fn reflected_light(next_event_estimation: bool) -> f64 {
+        let scene = scene();
+        let integrator = PathIntegrator::new(1, Color::new(0.0, 0.0, 0.0));
+        let point = Vec3::new(0.0, 0.0, 0.0);
+        let normal = Vec3::new(0.0, 1.0, 0.0);
+        let mut rng = StdRng::seed_from_u64(0);
+        let mut total = 0.0;
+
+        for _ in 0..SAMPLES {
+            total += if next_event_estimation {
+                integrator
+                    .sample_lights(&scene, &point, &normal, &mut rng)
+                    .get_red()
+            } else {
+                let ray = Ray::new(point, sample_cosine_direction(&normal, &mut rng));
+
+                // the lambertian brdf and the cosine cancel out with the pdf
+                ObjectRayIntersection::check_intersection(ray, &scene, SURFACE_OFFSET, f64::MAX)
+                    .and_then(|intersection| get_emitted_light(&intersection))
+                    .map_or(0.0, |emission| emission.get_red())
+            };
+        }
+
+        total / SAMPLES as f64
+    }
+
+    #[test]
+    fn stretched_emitter_is_sampled_without_bias() {
+        let sampled = reflected_light(true);
+        let hit = reflected_light(false);
+
+        assert!(
+            (sampled - hit).abs() < 0.01 * hit,
+            "sampling the emitter gives {}, hitting it gives {}",
+            sampled,
+            hit
+        );
+    }
+}
@@ -0,0 +1,284 @@
+use super::{
+    MediumStack, SURFACE_OFFSET, facing_normal, get_albedo, get_emitted_light,
+    get_fresnel_reflectance, get_scattered_light, refract, sample_unoccluded_light,
+};
+use crate::{Bsdf, BsdfLobe, Integrator, ObjectRayIntersection, Ray, Scene};
+use canvas::Color;
+use rand::RngCore;
+use std::f64::consts::PI;
+
+/// Object abstracting a Whitted ray tracer
+///
+/// trough the use of this object every light shades the hit point directly (with a diffuse and a
+/// Phong specular term), mirrors and refractive materials are followed recursively up to
+/// `max_depth` times; refractive materials split the light between the reflection and the
+/// refraction with the Fresnel equations and absorb the light travelling trough them (nested
+/// materials are tracked like in the path tracer)
+///
+/// Emissive objects are seen glowing (and in reflections and refractions) but only the scene's
+/// lights shade the other objects
+///
+/// Physically based materials (see [`crate::Bsdf`]) are lit by evaluating their bsdf for every
+/// light sample (the ambient light only reaches their diffuse color) and a single direction
+/// sampled from their reflection or transmission is followed recursively
+pub struct WhittedIntegrator {
+    max_depth: u8,
+    background_color: Color,
+}
+
+impl WhittedIntegrator {
+    /// creates a new Whitted integrator
+    pub fn new(max_depth: u8, background_color: Color) -> Self {
+        Self {
+            max_depth,
+            background_color,
+        }
+    }
+
+    /// computes the color seen by the ray, `light_bounces` is the number of reflections or
+    /// refractions that can still be followed and `media` are the materials the ray is inside of
+    fn shade<'a>(
+        &self,
+        ray: Ray,
+        scene: &'a Scene,
+        min_t: f64,
+        light_bounces: u8,
+        media: &MediumStack<'a>,
+        rng: &mut dyn RngCore,
+    ) -> Color {
+        let Some(intersection) =
+            ObjectRayIntersection::check_intersection(ray, scene, min_t, f64::MAX)
+        else {
+            return self.background_color;
+        };
+
+        // the light is absorbed along the way trough the medium
+        let transmittance = media.get_transmittance(intersection.get_hit_record().get_t());
+
+        // emissive objects are seen glowing but don't light the other objects
+        let emission = get_emitted_light(&intersection).unwrap_or(Color::new(0.0, 0.0, 0.0));
+
+        (self.shade_hit(&intersection, scene, light_bounces, media, rng) + emission) * transmittance
+    }
+
+    /// computes the color of the hit point seen along the ray
+    fn shade_hit<'a>(
+        &self,
+        intersection: &ObjectRayIntersection<'a>,
+        scene: &'a Scene,
+        light_bounces: u8,
+        media: &MediumStack<'a>,
+        rng: &mut dyn RngCore,
+    ) -> Color {
+        let point = *intersection.get_hit_point();
+        let object = intersection.get_hit_object();
+
+        // we return just the light's color
+        if intersection.is_light_hit() {
+            return intersection.get_hit_color();
+        }
+
+        let normal = *intersection.get_hit_record().get_shading_normal();
+        let object_id = intersection.get_hit_record().get_object_id();
+
+        let material = object.get_material();
+        let direction = *intersection.get_ray().get_direction();
+
+        if let Some(bsdf) = material.get_bsdf(
+            intersection.get_hit_record(),
+            get_albedo(&intersection.get_hit_color()),
+            media.get_outside_ior(object_id),
+        ) {
+            return self.shade_bsdf(&bsdf, intersection, scene, light_bounces, media, rng);
+        }
+
+        let viewing_vector = *intersection.get_viewing_vector();
+        let lit_normal = facing_normal(&normal, &direction);
+
+        let mut light_color = Color::new(0.0, 0.0, 0.0);
+
+        for light in scene.get_lights() {
+            light_color += light.get_ambient_color();
+
+            // area lights are sampled many times and the samples averaged
+            let sample_count = light.get_sample_count().max(1);
+            let mut samples_color = Color::new(0.0, 0.0, 0.0);
+
+            for _ in 0..sample_count {
+                let Some(sample) = sample_unoccluded_light(light.as_ref(), scene, &point, rng)
+                else {
+                    continue;
+                };
+
+                let light_normal_dotproduct = lit_normal.dot_product(sample.get_direction());
+
+                if light_normal_dotproduct <= 0.0 {
+                    continue;
+                }
+
+                let mut light_intensity = light_normal_dotproduct;
+
+                if let Some(specularity) = *material.get_specularity()
+                    && specularity >= 0.0
+                {
+                    let light_reflection = sample.get_direction().reflect(&lit_normal);
+                    let light_reflection_point_dot = light_reflection.dot_product(&viewing_vector);
+
+                    if light_reflection_point_dot > 0.0 {
+                        light_intensity += light_reflection_point_dot.powf(specularity);
+                    }
+                }
+
+                // the radiance is divided by pi like a diffuse surface would (a white surface
+                // facing a point light is as bright as the light's intensity)
+                samples_color +=
+                    (*sample.get_radiance()) * (light_intensity / (PI * sample.get_pdf()));
+            }
+
+            light_color += samples_color / (sample_count as f64);
+        }
+
+        let mut final_color = intersection.get_hit_color() * light_color;
+
+        // the refraction is split from the reflection with the Fresnel equations
+        if let Some(refraction) = *material.get_refraction() {
+            let relative_refraction = refraction / media.get_outside_ior(object_id);
+            let reflectance = get_fresnel_reflectance(&direction, &normal, relative_refraction);
+            let mut dielectric_color = Color::new(0.0, 0.0, 0.0);
+
+            if light_bounces > 0 {
+                if let Some(refracted_direction) = refract(&direction, &normal, relative_refraction)
+                {
+                    let mut refracted_media = media.clone();
+                    refracted_media.refract(
+                        object_id,
+                        material,
+                        refraction,
+                        direction.dot_product(&normal) < 0.0,
+                    );
+
+                    dielectric_color += self.shade(
+                        Ray::new(point, refracted_direction),
+                        scene,
+                        SURFACE_OFFSET,
+                        light_bounces - 1,
+                        &refracted_media,
+                        rng,
+                    ) * (1.0 - reflectance);
+                }
+
+                if reflectance > 0.0 {
+                    dielectric_color += self.shade(
+                        Ray::new(point, direction.get_inverse().reflect(&lit_normal)),
+                        scene,
+                        SURFACE_OFFSET,
+                        light_bounces - 1,
+                        media,
+                        rng,
+                    ) * reflectance;
+                }
+            }
+
+            let transparency = material.get_transparency().unwrap_or(1.0);
+
+            return final_color * (1.0 - transparency) + dielectric_color * transparency;
+        }
+
+        // even if we don't have light bounces we have to account for the object's reflectiveness
+        if let Some(reflection) = *material.get_reflectiveness() {
+            let mut reflected_color = Color::new(0.0, 0.0, 0.0);
+
+            if light_bounces > 0 {
+                reflected_color = self.shade(
+                    Ray::new(point, direction.get_inverse().reflect(&lit_normal)),
+                    scene,
+                    SURFACE_OFFSET,
+                    light_bounces - 1,
+                    media,
+                    rng,
+                );
+            }
+
+            final_color = (final_color * (1.0 - reflection)) + (reflected_color * reflection);
+        }
+
+        final_color
+    }
+
+    /// computes the color of a point of a physically based material seen along the ray
+    fn shade_bsdf<'a>(
+        &self,
+        bsdf: &Bsdf,
+        intersection: &ObjectRayIntersection<'a>,
+        scene: &'a Scene,
+        light_bounces: u8,
+        media: &MediumStack<'a>,
+        rng: &mut dyn RngCore,
+    ) -> Color {
+        let point = *intersection.get_hit_point();
+        let hit = intersection.get_hit_record();
+        let normal = *hit.get_shading_normal();
+        let direction = *intersection.get_ray().get_direction();
+        let outgoing = direction.get_inverse();
+        let mut final_color = Color::new(0.0, 0.0, 0.0);
+
+        for light in scene.get_lights() {
+            final_color += light.get_ambient_color() * bsdf.get_diffuse_color();
+
+            // area lights are sampled many times and the samples averaged
+            let sample_count = light.get_sample_count().max(1);
+            let mut samples_color = Color::new(0.0, 0.0, 0.0);
+
+            for _ in 0..sample_count {
+                if let Some(sample) = sample_unoccluded_light(light.as_ref(), scene, &point, rng) {
+                    samples_color += get_scattered_light(bsdf, &outgoing, &normal, &sample);
+                }
+            }
+
+            final_color += samples_color / (sample_count as f64);
+        }
+
+        if light_bounces > 0
+            && let Some(sample) = bsdf.sample(&outgoing, rng, false)
+        {
+            let mut sample_media = media.clone();
+            let material = intersection.get_hit_object().get_material();
+
+            if sample.get_lobe() == BsdfLobe::Transmission
+                && let Some(ior) = material.get_ior()
+            {
+                sample_media.refract(
+                    hit.get_object_id(),
+                    material,
+                    ior,
+                    direction.dot_product(&normal) < 0.0,
+                );
+            }
+
+            final_color += (*sample.get_weight())
+                * self.shade(
+                    Ray::new(point, *sample.get_direction()),
+                    scene,
+                    SURFACE_OFFSET,
+                    light_bounces - 1,
+                    &sample_media,
+                    rng,
+                );
+        }
+
+        final_color
+    }
+}
+
+impl Integrator for WhittedIntegrator {
+    fn compute_color(&self, ray: Ray, scene: &Scene, min_t: f64, rng: &mut dyn RngCore) -> Color {
+        self.shade(
+            ray,
+            scene,
+            min_t,
+            self.max_depth,
+            &MediumStack::default(),
+            rng,
+        )
+    }
+}
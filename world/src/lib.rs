@@ -1,5 +1,13 @@
+/// module implementing an axis aligned bounding box
+mod bounding_box;
+/// module implementing a bounding volume hierarchy
+mod bvh;
 /// module implementing a Camera struct
 mod camera;
+/// module implementing the signed distance fields of implicit shapes
+mod distance_fields;
+/// module implementing all of the ways the light seen by the camera can be computed
+mod integrators;
 /// module implementing all of the lights our scene can use
 mod lights;
 /// module implementing the materials our scene can render
@@ -8,13 +16,28 @@ mod materials;
 mod objects;
 /// module implementing a Ray struct
 mod ray;
+/// module implementing the Scene struct holding every object and light
+mod scene;
+/// module implementing the Transform struct (a 4x4 affine transform)
+mod transform;
 /// module implementing the Vec3 struct and all of the math behind it
 mod vec3;
 
 // extracting everything that is useful
+pub use bounding_box::BoundingBox;
+pub use bvh::Bvh;
 pub use camera::{Camera, ViewportAngles};
-pub use lights::{Light, Lights};
-pub use materials::{Material, MaterialBuilder};
-pub use objects::{Object, ObjectRayIntersection, Objects};
+pub use distance_fields::{DistanceField, DistanceFields};
+pub use integrators::{Integrator, Integrators};
+pub use lights::{Light, LightSample, Lights};
+pub use materials::{
+    Bsdf, BsdfLobe, BsdfSample, Material, MaterialBuilder, ProceduralTextures, Texture,
+    TextureFilter, TextureMap, TextureSpace, TextureWrap,
+};
+pub use objects::{
+    CsgOperation, HitRecord, Interval, Mesh, ObjError, Object, ObjectRayIntersection, Objects,
+};
 pub use ray::Ray;
+pub use scene::Scene;
+pub use transform::Transform;
 pub use vec3::Vec3;
@@ -1,21 +1,125 @@
 use crate::objects::Object;
-use crate::{ObjectRayIntersection, Vec3};
-use canvas::RGB;
+use crate::{Texture, Vec3};
+use canvas::Color;
+use rand::RngCore;
+
+/// A sample of the light a [`Light`] sends to a point, used by the integrators to estimate the
+/// direct illumination (next event estimation)
+///
+/// # Examples
+///
+/// Sample the light reaching the origin from every light of a scene
+///
+/// ```no_run
+/// # use world::{Scene, Vec3};
+/// # let scene = Scene::new();
+/// let mut rng = rand::rng();
+///
+/// for light in scene.get_lights() {
+///     if let Some(sample) = light.sample_illumination(&Vec3::new(0.0, 0.0, 0.0), &mut rng) {
+///         println!("{:?} from {:?}", sample.get_radiance(), sample.get_direction());
+///     }
+/// }
+/// ```
+#[derive(Debug, Clone, Copy)]
+pub struct LightSample {
+    position: Vec3,
+    direction: Vec3,
+    distance: f64,
+    radiance: Color,
+    pdf: f64,
+    is_blocked_by_lights: bool,
+}
+
+impl LightSample {
+    /// creates a new sample of the light coming from `position` to the lit `point`
+    pub fn new(point: &Vec3, position: Vec3, radiance: Color, pdf: f64) -> Self {
+        let mut direction = position - (*point);
+        let distance = direction.get_length();
+        direction.make_unit();
+
+        Self {
+            position,
+            direction,
+            distance,
+            radiance,
+            pdf,
+            is_blocked_by_lights: true,
+        }
+    }
+
+    /// creates a new sample of a light infinitely far away in the given direction (like the sun),
+    /// the light objects don't cast shadows for it
+    pub fn from_direction(point: &Vec3, mut direction: Vec3, radiance: Color) -> Self {
+        direction.make_unit();
+
+        Self {
+            position: (*point) + direction * f64::MAX,
+            direction,
+            distance: f64::INFINITY,
+            radiance,
+            pdf: 1.0,
+            is_blocked_by_lights: false,
+        }
+    }
+
+    /// retrieves the point of the light the sample comes from (for lights infinitely far away
+    /// it's just very far)
+    pub fn get_position(&self) -> &Vec3 {
+        &self.position
+    }
+
+    /// retrieves the (unit) direction from the lit point towards the light
+    pub fn get_direction(&self) -> &Vec3 {
+        &self.direction
+    }
+
+    /// retrieves the distance of the light from the lit point (infinite for directional lights)
+    pub fn get_distance(&self) -> f64 {
+        self.distance
+    }
+
+    /// retrieves the light arriving at the point from the sample's direction
+    pub fn get_radiance(&self) -> &Color {
+        &self.radiance
+    }
+
+    /// retrieves the probability density (over solid angle) of choosing the sample's direction,
+    /// it's 1 for lights that can only be reached from a single direction (point and directional
+    /// lights)
+    pub fn get_pdf(&self) -> f64 {
+        self.pdf
+    }
+
+    /// tells if the light objects (like the panel of a panel light) cast shadows for the sample
+    pub fn is_blocked_by_lights(&self) -> bool {
+        self.is_blocked_by_lights
+    }
+}
 
 /// trough this trait we can implement every type of light we may need for our ray traced world
-pub trait Light {
-    /// this function should return the current_object's color at the specified ray and t
-    fn compute_color(
-        &self,
-        ray_object: &ObjectRayIntersection,
-        other_objects: &[Box<dyn Object>],
-        other_lights: &[Box<dyn Light>],
-        light_bounces: u8,
-        background_color: RGB,
-    ) -> RGB;
+///
+/// lights don't shade the objects themselves, they only tell the integrators (see
+/// [`crate::Integrator`]) how much light they send to a point; lights are shared between the
+/// rendering threads so they have to be `Send` and `Sync`
+pub trait Light: Send + Sync {
+    /// samples the light reaching the given point (ignoring shadows), None if the light can't
+    /// reach it or if it doesn't have a position or direction (like an ambient light)
+    fn sample_illumination(&self, point: &Vec3, rng: &mut dyn RngCore) -> Option<LightSample>;
+
+    /// this function returns how many samples should be averaged to get a smooth result (area
+    /// lights need more than one)
+    fn get_sample_count(&self) -> usize {
+        1
+    }
+
+    /// this function returns the light reaching every point from every direction
+    fn get_ambient_color(&self) -> Color {
+        Color::new(0.0, 0.0, 0.0)
+    }
 
     /// this function returns the light's objects (necessary to implement area lights)
-    fn get_object(&self) -> Option<&Box<dyn Object>> {
+    fn get_object(&self) -> Option<&dyn Object> {
         None
     }
 }
@@ -28,19 +132,31 @@ mod directional_light;
 mod panel_light;
 /// module to implement a point light
 mod point_light;
+/// module to implement a spot light
+mod spot_light;
 
 // extracting everything we may need
 use ambient_light::AmbientLight;
 use directional_light::DirectionalLight;
 use panel_light::PanelLight;
 use point_light::PointLight;
+use spot_light::SpotLight;
+
+/// retrieves how much of the light reaches a point at the given distance
+fn get_attenuation(light_length: f64) -> f64 {
+    1.0 / (1.0 + 0.09 * light_length + 0.032 * light_length * light_length)
+}
 
 /// enum containing all of the light's types we can create
+///
+/// a `SpotLight` takes its position, its direction, the inner and outer angles of its cone (in
+/// radians, from the axis), its intensity, its color and the image it projects
 pub enum Lights {
     AmbientLight(f64),
     DirectionalLight(Vec3, f64),
-    PanelLight(Vec3, f64, f64, Vec3, f64, f64, Option<RGB>),
-    PointLight(Vec3, f64, Option<RGB>),
+    PanelLight(Vec3, f64, f64, Vec3, f64, f64, Option<Color>),
+    PointLight(Vec3, f64, Option<Color>),
+    SpotLight(Vec3, Vec3, f64, f64, f64, Option<Color>, Option<Texture>),
 }
 
 impl Lights {
@@ -70,6 +186,23 @@ impl Lights {
             Lights::PointLight(position, intensity, light_color) => {
                 Box::new(PointLight::new(position, intensity, light_color))
             }
+            Lights::SpotLight(
+                position,
+                direction,
+                inner_angle,
+                outer_angle,
+                intensity,
+                light_color,
+                gobo,
+            ) => Box::new(SpotLight::new(
+                position,
+                direction,
+                inner_angle,
+                outer_angle,
+                intensity,
+                light_color,
+                gobo,
+            )),
         }
     }
 }
@@ -1,5 +1,7 @@
-use crate::{Light, Object, ObjectRayIntersection};
-use canvas::RGB;
+use crate::lights::LightSample;
+use crate::{Light, Vec3};
+use canvas::Color;
+use rand::RngCore;
 
 /// Object abstracting an ambient light
 ///
@@ -16,20 +18,12 @@ impl AmbientLight {
 }
 
 impl Light for AmbientLight {
-    fn compute_color(
-        &self,
-        ray_object: &ObjectRayIntersection,
-        _other_objects: &[Box<dyn Object>],
-        _other_lights: &[Box<dyn Light>],
-        _light_bounces: u8,
-        _background_color: RGB,
-    ) -> RGB {
-        let point = *ray_object.get_hit_point();
-        let object_color = ray_object.get_hit_object().get_color(point);
+    fn sample_illumination(&self, _point: &Vec3, _rng: &mut dyn RngCore) -> Option<LightSample> {
+        // the light comes from every direction so there isn't anything to sample
+        None
+    }
 
-        match ray_object.get_hit_object().get_normal(point) {
-            Some(_) => (object_color) * self.intensity,
-            None => RGB::new(0, 0, 0),
-        }
+    fn get_ambient_color(&self) -> Color {
+        Color::new(1.0, 1.0, 1.0) * self.intensity
     }
 }
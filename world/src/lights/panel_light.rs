@@ -1,5 +1,8 @@
-use crate::{Light, Lights, MaterialBuilder, Object, ObjectRayIntersection, Objects, Vec3};
-use canvas::RGB;
+use crate::lights::LightSample;
+use crate::objects::Panel;
+use crate::{Light, MaterialBuilder, Object, Vec3};
+use canvas::Color;
+use rand::{Rng, RngCore};
 
 /// Object abstracting a panel area light
 ///
@@ -7,8 +10,14 @@ use canvas::RGB;
 /// trait `Light`
 pub struct PanelLight {
     panel: Box<dyn Object>,
-    intersection_points: Vec<Vec3>,
+    panel_origin: Vec3,
+    panel_u: Vec3,
+    panel_v: Vec3,
+    panel_normal: Vec3,
+    panel_area: f64,
+    sample_count: usize,
     intensity: f64,
+    light_color: Color,
 }
 
 impl PanelLight {
@@ -16,134 +25,85 @@ impl PanelLight {
         panel_origin: Vec3,
         panel_width: f64,
         panel_height: f64,
-        panel_normal: Vec3,
+        mut panel_normal: Vec3,
         mut intensity: f64,
         intersection_gap: f64,
-        light_color: Option<RGB>,
+        light_color: Option<Color>,
     ) -> Self {
+        panel_normal.make_unit();
+
         let light_color = match light_color {
             Some(light_color) => light_color,
-            None => RGB::new(255, 255, 255),
+            None => Color::new(1.0, 1.0, 1.0),
         };
 
-        // we calculate how many steps we have to take based upon the 'intesection_gap' variable
-        let width_steps: i32 = (panel_width / intersection_gap).floor() as i32;
-        let height_steps: i32 = (panel_height / intersection_gap).floor() as i32;
+        // the panel is sampled about once every 'intersection_gap' along its sides
+        let width_steps = (panel_width / intersection_gap).floor().max(1.0) as usize;
+        let height_steps = (panel_height / intersection_gap).floor().max(1.0) as usize;
 
         let panel_area = panel_width * panel_height;
         intensity /= panel_area;
 
-        // multiplying the light_color by the intensity to give the user some feedback
-        let panel = Objects::create_object(Objects::Panel(
+        // multiplying the light_color by the intensity to give the user some feedback (the color
+        // isn't clamped so a bright panel stays bright in the final image)
+        let panel = Panel::new(
             panel_origin,
             panel_width,
             panel_height,
             panel_normal,
             MaterialBuilder::new()
-                .set_color(light_color * (intensity.min(1.0)))
+                .set_color(light_color * intensity)
                 .build(),
-        ));
-
-        // TODO: remove this code duplication
-        // we calculate every point on the surface we have to check when doing light calculations
-        let mut panel_u = *panel_origin.cross_product(&panel_normal).make_unit();
-        let mut panel_v = panel_normal.cross_product(&panel_u);
-
-        // if the scalar_vectors are impossible then they are "normal"
-        if panel_u.get_x().is_nan() && panel_u.get_y().is_nan() && panel_u.get_z().is_nan()
-            || panel_v.get_x().is_nan() && panel_v.get_y().is_nan() && panel_v.get_z().is_nan()
-        {
-            panel_u = Vec3::new(1.0, 0.0, 0.0);
-            panel_v = Vec3::new(0.0, 1.0, 0.0);
-        }
-
-        panel_u = panel_u * (panel_width / 2.0);
-        panel_v = panel_v * (panel_height / 2.0);
-
-        // we start marking points on the top left of the panel
-        let mut intersection_points: Vec<Vec3> = vec![];
-        let panel_top_left = (panel_v - panel_u) + panel_origin;
-
-        for i in 1..(width_steps - 1) {
-            let i_step = (intersection_gap * f64::from(i)) / (panel_width / 2.0);
-            let u_step = panel_u * i_step;
-
-            for j in 1..(height_steps - 1) {
-                let j_step = (intersection_gap * f64::from(j)) / (panel_height / 2.0);
-                let v_step = panel_v.get_inverse() * j_step;
+        );
 
-                // to check if an object is behind the light source i move the intersection_point a
-                // little bit forward based upon the objects normal
-                intersection_points.push(panel_top_left + u_step + v_step + (panel_normal * 0.001));
-            }
-        }
+        // the panel's u and v vectors are needed to choose the points of the surface to sample
+        let (panel_u, panel_v) = (panel.get_u(), panel.get_v());
 
         Self {
-            panel,
-            intersection_points,
+            panel: Box::new(panel),
+            panel_origin,
+            panel_u,
+            panel_v,
+            panel_normal,
+            panel_area,
+            sample_count: width_steps * height_steps,
             intensity,
+            light_color,
         }
     }
 }
 
 impl Light for PanelLight {
-    fn compute_color(
-        &self,
-        ray_object: &ObjectRayIntersection,
-        other_objects: &[Box<dyn Object>],
-        other_lights: &[Box<dyn Light>],
-        light_bounces: u8,
-        background_color: RGB,
-    ) -> RGB {
-        // for every point on our panel light we create a special point light at that point, then
-        // we average the result and we will get the correct light (i think)
-        let point = *ray_object.get_hit_point();
-        let current_object = ray_object.get_hit_object();
-
-        match current_object.get_normal(point) {
-            Some(_) => {
-                let mut final_red: u32 = 0;
-                let mut final_green: u32 = 0;
-                let mut final_blue: u32 = 0;
-                for intersection_point in &self.intersection_points {
-                    let point_light = Lights::create_light(Lights::PointLight(
-                        *intersection_point,
-                        self.intensity,
-                        Some(*self.panel.get_material().get_color()),
-                    ));
-                    let calculated_color = point_light.compute_color(
-                        ray_object,
-                        other_objects,
-                        other_lights,
-                        light_bounces,
-                        background_color,
-                    );
-
-                    final_red = final_red.saturating_add(u32::from(calculated_color.get_red()));
-                    final_green =
-                        final_green.saturating_add(u32::from(calculated_color.get_green()));
-                    final_blue = final_blue.saturating_add(u32::from(calculated_color.get_blue()));
-                }
+    fn sample_illumination(&self, point: &Vec3, rng: &mut dyn RngCore) -> Option<LightSample> {
+        // a uniformly chosen point of the panel
+        let light_point = self.panel_origin
+            + self.panel_u * rng.random_range(-1.0..1.0)
+            + self.panel_v * rng.random_range(-1.0..1.0);
+        let light_direction = light_point - (*point);
+        let light_length = light_direction.get_length();
+
+        // the panel only shines on the side its normal points to
+        let light_cos = -self.panel_normal.dot_product(&light_direction) / light_length;
+
+        if light_cos <= 0.0 || light_length == 0.0 {
+            return None;
+        }
 
-                let intersection_samples_count: u32 = self.intersection_points.len() as u32;
+        // the same radiance seen by the camera when looking at the panel, the pdf is converted
+        // from the panel's area to the solid angle seen by the point
+        Some(LightSample::new(
+            point,
+            light_point,
+            self.light_color * self.intensity,
+            (light_length * light_length) / (self.panel_area * light_cos),
+        ))
+    }
 
-                RGB::new(
-                    final_red
-                        .saturating_div(intersection_samples_count)
-                        .min(255) as u8,
-                    final_green
-                        .saturating_div(intersection_samples_count)
-                        .min(255) as u8,
-                    final_blue
-                        .saturating_div(intersection_samples_count)
-                        .min(255) as u8,
-                )
-            }
-            None => RGB::new(0, 0, 0),
-        }
+    fn get_sample_count(&self) -> usize {
+        self.sample_count
     }
 
-    fn get_object(&self) -> Option<&Box<dyn Object>> {
-        Some(&self.panel)
+    fn get_object(&self) -> Option<&dyn Object> {
+        Some(self.panel.as_ref())
     }
 }
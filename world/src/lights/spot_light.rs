@@ -0,0 +1,127 @@
+use crate::lights::{LightSample, get_attenuation};
+use crate::{Light, Texture, Vec3};
+use canvas::Color;
+use rand::RngCore;
+use std::f64::consts::{FRAC_PI_2, PI};
+
+/// Object abstracting a spot light in space
+///
+/// trough the use of this object we can simulate a "Spot Light" by using the methods given by the
+/// trait `Light`: the light shines from its position inside a cone around its direction, at full
+/// strength within the inner angle and fading smoothly to nothing at the outer angle (both are
+/// measured from the cone's axis, in radians); the light can project an image (a gobo) like a
+/// slide projector, its top side pointing up (towards +y) when possible
+pub struct SpotLight {
+    position: Vec3,
+    direction: Vec3,
+    horizontal: Vec3,
+    vertical: Vec3,
+    cos_inner: f64,
+    cos_outer: f64,
+    tan_outer: f64,
+    intensity: f64,
+    light_color: Color,
+    gobo: Option<Texture>,
+}
+
+impl SpotLight {
+    /// create a new spot light shining along `direction`
+    pub fn new(
+        position: Vec3,
+        mut direction: Vec3,
+        inner_angle: f64,
+        outer_angle: f64,
+        intensity: f64,
+        light_color: Option<Color>,
+        gobo: Option<Texture>,
+    ) -> Self {
+        direction.make_unit();
+
+        // the cone can't open up to a half space (the gobo would be infinitely big)
+        let outer_angle = outer_angle.clamp(f64::EPSILON, FRAC_PI_2 - 1e-3);
+        let inner_angle = inner_angle.clamp(0.0, outer_angle);
+
+        // the gobo's sides, its vertical side follows the world's up unless the light points
+        // straight up or down
+        let mut horizontal = Vec3::new(0.0, 1.0, 0.0).cross_product(&direction);
+
+        if horizontal.get_length() < 1e-6 {
+            horizontal = direction.get_perpendicular();
+        }
+
+        horizontal.make_unit();
+        let vertical = direction.cross_product(&horizontal);
+
+        Self {
+            position,
+            direction,
+            horizontal,
+            vertical,
+            cos_inner: inner_angle.cos(),
+            cos_outer: outer_angle.cos(),
+            tan_outer: outer_angle.tan(),
+            intensity,
+            light_color: light_color.unwrap_or(Color::new(1.0, 1.0, 1.0)),
+            gobo,
+        }
+    }
+
+    /// retrieves how much of the light leaves the spot along the (unit) direction, it fades
+    /// smoothly between the inner and the outer cone
+    fn get_falloff(&self, cos: f64) -> f64 {
+        if cos <= self.cos_outer {
+            return 0.0;
+        }
+
+        if cos >= self.cos_inner {
+            return 1.0;
+        }
+
+        let t = (cos - self.cos_outer) / (self.cos_inner - self.cos_outer);
+
+        t * t * (3.0 - 2.0 * t)
+    }
+
+    /// retrieves the color of the gobo projected along the (unit) direction, the image fills the
+    /// square containing the outer cone at every distance
+    fn get_gobo_color(&self, gobo: &Texture, direction: &Vec3, cos: f64) -> Color {
+        let x = direction.dot_product(&self.horizontal) / (cos * self.tan_outer);
+        let y = direction.dot_product(&self.vertical) / (cos * self.tan_outer);
+
+        gobo.get_color(0.5 + 0.5 * x, 0.5 + 0.5 * y)
+    }
+}
+
+impl Light for SpotLight {
+    fn sample_illumination(&self, point: &Vec3, _rng: &mut dyn RngCore) -> Option<LightSample> {
+        let mut light_direction = (*point) - self.position;
+        let light_length = light_direction.get_length();
+
+        if light_length == 0.0 {
+            return None;
+        }
+
+        light_direction.make_unit();
+
+        let cos = light_direction.dot_product(&self.direction);
+        let falloff = self.get_falloff(cos);
+
+        if falloff <= 0.0 {
+            return None;
+        }
+
+        let mut light_color = self.light_color;
+
+        if let Some(gobo) = &self.gobo {
+            light_color = light_color * self.get_gobo_color(gobo, &light_direction, cos);
+        }
+
+        // the light is scaled by pi like a point light's
+        Some(LightSample::new(
+            point,
+            self.position,
+            light_color * (self.intensity * falloff * get_attenuation(light_length) * PI),
+            1.0,
+        ))
+    }
+}
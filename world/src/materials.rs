@@ -1,9 +1,64 @@
-use canvas::RGB;
+use std::fmt::Debug;
+use std::sync::Arc;
 
+use canvas::Color;
+
+use crate::{HitRecord, Vec3};
+
+/// trough this trait we can implement every texture we may need to color the objects of our ray
+/// traced world, an image (see `Texture`) or a pattern computed on the fly (see
+/// `ProceduralTextures`)
+///
+/// textures are shared between the materials and the rendering threads so they have to be `Send`
+/// and `Sync`
+pub trait TextureMap: Debug + Send + Sync {
+    /// this method should return the texture's color at the hit point (by its UVs or its
+    /// position)
+    fn get_color_at(&self, hit: &HitRecord) -> Color;
+
+    /// this method returns whether the texture is filtered over the area seen by the ray (see
+    /// `HitRecord::get_uv_footprint`), computing the area isn't free so it's skipped otherwise
+    fn uses_footprint(&self) -> bool {
+        false
+    }
+}
+
+/// module implementing the physically based scattering of the surfaces
+mod bsdf;
+/// module implementing the procedural textures
+mod procedural;
 /// module implementing a texture
 mod texture;
 
-use texture::Texture;
+pub use bsdf::{Bsdf, BsdfLobe, BsdfSample};
+pub use procedural::{ProceduralTextures, TextureSpace};
+pub use texture::{Texture, TextureFilter, TextureWrap};
+
+/// a parameter of the physically based model, its value is multiplied by the map's (luminance)
+/// at the hit point
+#[derive(Debug, Clone)]
+struct PhysicalParameter {
+    value: f64,
+    map: Option<Arc<dyn TextureMap>>,
+}
+
+impl PhysicalParameter {
+    fn get_value(&self, hit: &HitRecord) -> f64 {
+        match &self.map {
+            Some(map) => self.value * map.get_color_at(hit).get_luminance(),
+            None => self.value,
+        }
+    }
+}
+
+/// the parameters of the physically based (metallic roughness) model
+#[derive(Debug, Clone)]
+struct PhysicalParameters {
+    metallic: PhysicalParameter,
+    roughness: PhysicalParameter,
+    ior: f64,
+    transmission: PhysicalParameter,
+}
 
 /// an object abstracting the materials of the world's objects
 ///
@@ -16,8 +71,10 @@ use texture::Texture;
 /// and print all of it's properties
 ///
 /// ```no_run
+/// # use canvas::Color;
+/// # use world::Material;
 ///     let material: Material = Material::new(
-///         RGB::new(0, 0, 0),
+///         Color::new(0.0, 0.0, 0.0),
 ///         None,
 ///         Some(0.8),
 ///         None,
@@ -34,8 +91,14 @@ use texture::Texture;
 /// ```
 #[derive(Debug, Clone)]
 pub struct Material {
-    color: RGB,
-    texture: Option<Texture>,
+    color: Color,
+    texture: Option<Arc<dyn TextureMap>>,
+    normal_map: Option<Arc<Texture>>,
+    bump_map: Option<Arc<Texture>>,
+    bump_strength: f64,
+    physical: Option<PhysicalParameters>,
+    absorption: Option<Color>,
+    emission: Option<Color>,
     reflectiveness: Option<f64>,
     specularity: Option<f64>,
     refraction: Option<f64>,
@@ -45,15 +108,15 @@ pub struct Material {
 impl Material {
     /// creates a new Material
     pub fn new(
-        color: RGB,
-        texture_path: Option<&str>,
+        color: Color,
+        texture: Option<Texture>,
         reflectiveness: Option<f64>,
         specularity: Option<f64>,
         refraction: Option<f64>,
         transparency: Option<f64>,
     ) -> Self {
         let reflectiveness = reflectiveness.map(|reflectiveness| reflectiveness.clamp(0.0, 1.0));
-        let texture = texture_path.map(Texture::load);
+        let texture = texture.map(|texture| Arc::new(texture) as Arc<dyn TextureMap>);
 
         // if we have refraction then trasparency will be full only when `transparency` is not
         // given
@@ -66,6 +129,12 @@ impl Material {
         Self {
             color,
             texture,
+            normal_map: None,
+            bump_map: None,
+            bump_strength: 1.0,
+            physical: None,
+            absorption: None,
+            emission: None,
             reflectiveness,
             specularity,
             refraction,
@@ -74,10 +143,132 @@ impl Material {
     }
 
     /// retrieves the material's texture
-    pub fn get_texture(&self) -> &Option<Texture> {
+    pub fn get_texture(&self) -> &Option<Arc<dyn TextureMap>> {
         &self.texture
     }
 
+    /// retrieves the material's (tangent space) normal map
+    pub fn get_normal_map(&self) -> &Option<Arc<Texture>> {
+        &self.normal_map
+    }
+
+    /// retrieves the material's bump (height) map
+    pub fn get_bump_map(&self) -> &Option<Arc<Texture>> {
+        &self.bump_map
+    }
+
+    /// retrieves how much the bump map's heights bend the normal
+    pub fn get_bump_strength(&self) -> f64 {
+        self.bump_strength
+    }
+
+    /// retrieves the shading normal of the hit bent by the normal and the bump map, the maps are
+    /// oriented by the hit's tangents; None is returned when the material has no maps
+    pub fn get_mapped_normal(&self, hit: &HitRecord) -> Option<Vec3> {
+        if self.normal_map.is_none() && self.bump_map.is_none() {
+            return None;
+        }
+
+        let normal = *hit.get_shading_normal();
+        let (tangent, bitangent) = hit.get_tangents();
+
+        // the tangents are made perpendicular to the (interpolated) normal keeping v's side
+        let mut tangent = tangent - normal * normal.dot_product(&tangent);
+
+        if tangent.get_length() <= f64::EPSILON {
+            tangent = normal.get_perpendicular();
+        }
+
+        tangent.make_unit();
+
+        let mut frame_bitangent = normal.cross_product(&tangent);
+
+        if frame_bitangent.dot_product(&bitangent) < 0.0 {
+            frame_bitangent = frame_bitangent.get_inverse();
+        }
+
+        let (u, v) = hit.get_uv();
+        let mut mapped_normal = normal;
+
+        if let Some(normal_map) = &self.normal_map {
+            let local_normal = normal_map.get_normal(u, v, hit.get_uv_footprint());
+
+            mapped_normal = tangent * *local_normal.get_x()
+                + frame_bitangent * *local_normal.get_y()
+                + normal * *local_normal.get_z();
+        }
+
+        if let Some(bump_map) = &self.bump_map {
+            let (slope_u, slope_v) = bump_map.get_height_gradient(u, v);
+
+            // the normal leans away from the way the surface rises
+            mapped_normal = mapped_normal
+                - (tangent * slope_u + frame_bitangent * slope_v) * self.bump_strength;
+        }
+
+        Some(*mapped_normal.make_unit())
+    }
+
+    /// tells if the normals of the material's normal map are filtered over the area seen by the
+    /// ray (see `TextureMap::uses_footprint`)
+    pub fn normal_map_uses_footprint(&self) -> bool {
+        self.normal_map
+            .as_ref()
+            .is_some_and(|normal_map| normal_map.uses_footprint())
+    }
+
+    /// retrieves whether the material uses the physically based (metallic roughness) model, the
+    /// integrators ignore its legacy parameters (reflectiveness, specularity, refraction and
+    /// transparency) when it does
+    pub fn is_physically_based(&self) -> bool {
+        self.physical.is_some()
+    }
+
+    /// retrieves the physically based scattering of the material at the hit point, the base
+    /// color is the material's color (and texture) at the point and `outside_ior` is the index of
+    /// refraction of the medium around the material; None is returned if the material doesn't use
+    /// the physically based model
+    pub fn get_bsdf(&self, hit: &HitRecord, base_color: Color, outside_ior: f64) -> Option<Bsdf> {
+        let physical = self.physical.as_ref()?;
+
+        Some(Bsdf::new(
+            *hit.get_shading_normal(),
+            base_color,
+            physical.metallic.get_value(hit),
+            physical.roughness.get_value(hit),
+            physical.ior / outside_ior,
+            physical.transmission.get_value(hit),
+        ))
+    }
+
+    /// retrieves the index of refraction of the material's inside (the physically based ior or
+    /// the refraction), None is returned for opaque materials
+    pub fn get_ior(&self) -> Option<f64> {
+        match &self.physical {
+            Some(physical) => Some(physical.ior),
+            None => self.refraction,
+        }
+    }
+
+    /// retrieves the fraction of the light (for every channel) left after travelling `distance`
+    /// units inside the material, following the Beer-Lambert law
+    pub fn get_transmittance(&self, distance: f64) -> Color {
+        match &self.absorption {
+            Some(absorption) => Color::new(
+                (-absorption.get_red() * distance).exp(),
+                (-absorption.get_green() * distance).exp(),
+                (-absorption.get_blue() * distance).exp(),
+            ),
+            None => Color::new(1.0, 1.0, 1.0),
+        }
+    }
+
+    /// retrieves the light emitted by the material (on the front face of the objects), None if it
+    /// doesn't glow
+    pub fn get_emission(&self) -> Option<Color> {
+        self.emission
+    }
+
     /// retrieves the material's specularity value
     pub fn get_specularity(&self) -> &Option<f64> {
         &self.specularity
@@ -89,7 +280,7 @@ impl Material {
     }
 
     /// retrieves the material's color
-    pub fn get_color(&self) -> &RGB {
+    pub fn get_color(&self) -> &Color {
         &self.color
     }
 
@@ -107,27 +298,167 @@ impl Material {
 /// an object that makes it easy to "build" a material from scratch
 ///
 /// The end user can only use this object
-pub struct MaterialBuilder<'a> {
-    color: RGB,
-    texture_path: Option<&'a str>,
+pub struct MaterialBuilder {
+    color: Color,
+    texture: Option<Texture>,
+    texture_map: Option<Arc<dyn TextureMap>>,
+    normal_map: Option<Texture>,
+    bump_map: Option<Texture>,
+    bump_strength: f64,
+    metallic: Option<f64>,
+    roughness: Option<f64>,
+    ior: Option<f64>,
+    transmission: Option<f64>,
+    absorption: Option<(Color, f64)>,
+    emission: Option<Color>,
+    metallic_map: Option<Texture>,
+    roughness_map: Option<Texture>,
+    transmission_map: Option<Texture>,
+    texture_scale: (f64, f64),
+    texture_offset: (f64, f64),
+    texture_rotation: f64,
+    texture_wrap: TextureWrap,
+    texture_filter: TextureFilter,
     reflectiveness: Option<f64>,
     specularity: Option<f64>,
     refraction: Option<f64>,
     transparency: Option<f64>,
 }
 
-impl<'a> MaterialBuilder<'a> {
+impl MaterialBuilder {
     pub fn new() -> Self {
         Self::default()
     }
 
-    pub fn set_color(&mut self, color: RGB) -> &mut Self {
-        self.color = color;
+    /// sets the material's color, an 8 bit [`canvas::RGB`] is treated as sRGB and converted to
+    /// linear
+    pub fn set_color<C: Into<Color>>(&mut self, color: C) -> &mut Self {
+        self.color = color.into();
+        self
+    }
+
+    /// sets the image texture (see `Texture::load`)
+    pub fn set_texture(&mut self, texture: Texture) -> &mut Self {
+        self.texture = Some(texture);
+        self
+    }
+
+    /// sets a texture of any kind (like a procedural one), it replaces the image texture
+    pub fn set_texture_map(&mut self, texture: Arc<dyn TextureMap>) -> &mut Self {
+        self.texture_map = Some(texture);
+        self
+    }
+
+    /// sets the (tangent space) normal map bending the shading normal, the image shouldn't be
+    /// treated as sRGB (see `Texture::load_raw`)
+    pub fn set_normal_map(&mut self, normal_map: Texture) -> &mut Self {
+        self.normal_map = Some(normal_map);
+        self
+    }
+
+    /// sets the grayscale height map bending the shading normal, the image shouldn't be treated
+    /// as sRGB (see `Texture::load_raw`)
+    pub fn set_bump_map(&mut self, bump_map: Texture) -> &mut Self {
+        self.bump_map = Some(bump_map);
+        self
+    }
+
+    /// sets how much the bump map's heights bend the normal (1 by default)
+    pub fn set_bump_strength(&mut self, bump_strength: f64) -> &mut Self {
+        self.bump_strength = bump_strength;
+        self
+    }
+
+    /// sets how metallic the material is (from 0 to 1), it makes the material physically based
+    /// (see `Bsdf`)
+    pub fn set_metallic(&mut self, metallic: f64) -> &mut Self {
+        self.metallic = Some(metallic);
+        self
+    }
+
+    /// sets how rough the material's microfacets are (from 0, a mirror, to 1), it makes the
+    /// material physically based
+    pub fn set_roughness(&mut self, roughness: f64) -> &mut Self {
+        self.roughness = Some(roughness);
+        self
+    }
+
+    /// sets the index of refraction of the material's inside, it makes the material physically
+    /// based
+    pub fn set_ior(&mut self, ior: f64) -> &mut Self {
+        self.ior = Some(ior);
+        self
+    }
+
+    /// sets how much of the (non metallic) material lets the light trough (from 0 to 1), it makes
+    /// the material physically based
+    pub fn set_transmission(&mut self, transmission: f64) -> &mut Self {
+        self.transmission = Some(transmission);
+        self
+    }
+
+    /// sets the color the light takes after travelling `distance` units inside the material, the
+    /// farther it goes the darker it gets (with the Beer-Lambert law)
+    pub fn set_absorption(&mut self, color: Color, distance: f64) -> &mut Self {
+        self.absorption = Some((color, distance));
+        self
+    }
+
+    /// makes the material glow with the color times the strength, the objects using it are seen
+    /// even in the dark and light up the scene (the front face only, like a panel light)
+    pub fn set_emission(&mut self, color: Color, strength: f64) -> &mut Self {
+        self.emission = Some(color * strength);
+        self
+    }
+
+    /// sets the grayscale map multiplying the metallic value (1 when it isn't set), it makes the
+    /// material physically based
+    pub fn set_metallic_map(&mut self, metallic_map: Texture) -> &mut Self {
+        self.metallic_map = Some(metallic_map);
+        self
+    }
+
+    /// sets the grayscale map multiplying the roughness (1 when it isn't set), it makes the
+    /// material physically based
+    pub fn set_roughness_map(&mut self, roughness_map: Texture) -> &mut Self {
+        self.roughness_map = Some(roughness_map);
+        self
+    }
+
+    /// sets the grayscale map multiplying the transmission (1 when it isn't set), it makes the
+    /// material physically based
+    pub fn set_transmission_map(&mut self, transmission_map: Texture) -> &mut Self {
+        self.transmission_map = Some(transmission_map);
+        self
+    }
+
+    /// sets how many times the image texture (and the other maps) is repeated along u and v
+    pub fn set_texture_scale(&mut self, scale_u: f64, scale_v: f64) -> &mut Self {
+        self.texture_scale = (scale_u, scale_v);
+        self
+    }
+
+    /// sets the offset added to the texture's (scaled) UVs
+    pub fn set_texture_offset(&mut self, offset_u: f64, offset_v: f64) -> &mut Self {
+        self.texture_offset = (offset_u, offset_v);
+        self
+    }
+
+    /// sets the texture's rotation (in radians, counterclockwise)
+    pub fn set_texture_rotation(&mut self, rotation: f64) -> &mut Self {
+        self.texture_rotation = rotation;
+        self
+    }
+
+    /// sets how the texture is sampled outside of the [0, 1] UV range
+    pub fn set_texture_wrap(&mut self, wrap: TextureWrap) -> &mut Self {
+        self.texture_wrap = wrap;
         self
     }
 
-    pub fn set_texture(&mut self, texture_path: &'a str) -> &mut Self {
-        self.texture_path = Some(texture_path);
+    /// sets how the texture's texels are filtered
+    pub fn set_texture_filter(&mut self, filter: TextureFilter) -> &mut Self {
+        self.texture_filter = filter;
         self
     }
 
@@ -152,22 +483,117 @@ impl<'a> MaterialBuilder<'a> {
     }
 
     pub fn build(&self) -> Material {
-        Material::new(
+        let mut material = Material::new(
             self.color,
-            self.texture_path,
+            None,
             self.reflectiveness,
             self.specularity,
             self.refraction,
             self.transparency,
-        )
+        );
+
+        // the images share the same placement on the surface
+        let place = |mut texture: Texture| {
+            texture
+                .set_scale(self.texture_scale)
+                .set_offset(self.texture_offset)
+                .set_rotation(self.texture_rotation)
+                .set_wrap(self.texture_wrap)
+                .set_filter(self.texture_filter);
+
+            Arc::new(texture)
+        };
+
+        material.texture = if let Some(texture) = &self.texture_map {
+            Some(texture.clone())
+        } else {
+            self.texture
+                .clone()
+                .map(|texture| place(texture) as Arc<dyn TextureMap>)
+        };
+        material.normal_map = self.normal_map.clone().map(place);
+        material.bump_map = self.bump_map.clone().map(place);
+        material.bump_strength = self.bump_strength;
+
+        material.emission = self.emission;
+
+        // the absorption coefficients turning the white light into the color over the distance
+        material.absorption = self.absorption.map(|(color, distance)| {
+            let coefficient = |channel: f64| -channel.clamp(1e-6, 1.0).ln() / distance.max(1e-6);
+
+            Color::new(
+                coefficient(color.get_red()),
+                coefficient(color.get_green()),
+                coefficient(color.get_blue()),
+            )
+        });
+
+        let is_physically_based = [self.metallic, self.roughness, self.ior, self.transmission]
+            .iter()
+            .any(Option::is_some)
+            || [
+                &self.metallic_map,
+                &self.roughness_map,
+                &self.transmission_map,
+            ]
+            .iter()
+            .any(|map| map.is_some());
+
+        if is_physically_based {
+            let parameter = |value: f64, map: &Option<Texture>| PhysicalParameter {
+                value,
+                map: map.clone().map(|map| place(map) as Arc<dyn TextureMap>),
+            };
+
+            // like in glTF a map alone gives the parameter its full range
+            let factor = |value: Option<f64>, map: &Option<Texture>, default: f64| {
+                value.unwrap_or(if map.is_some() { 1.0 } else { default })
+            };
+
+            material.physical = Some(PhysicalParameters {
+                metallic: parameter(
+                    factor(self.metallic, &self.metallic_map, 0.0),
+                    &self.metallic_map,
+                ),
+                roughness: parameter(
+                    factor(self.roughness, &self.roughness_map, 0.5),
+                    &self.roughness_map,
+                ),
+                ior: self.ior.unwrap_or(1.5).max(1.0),
+                transmission: parameter(
+                    factor(self.transmission, &self.transmission_map, 0.0),
+                    &self.transmission_map,
+                ),
+            });
+        }
+
+        material
     }
 }
 
-impl Default for MaterialBuilder<'_> {
+impl Default for MaterialBuilder {
     fn default() -> Self {
         Self {
-            color: RGB::new(0, 0, 0),
-            texture_path: None,
+            color: Color::new(0.0, 0.0, 0.0),
+            texture: None,
+            texture_map: None,
+            normal_map: None,
+            bump_map: None,
+            bump_strength: 1.0,
+            metallic: None,
+            roughness: None,
+            ior: None,
+            transmission: None,
+            absorption: None,
+            emission: None,
+            metallic_map: None,
+            roughness_map: None,
+            transmission_map: None,
+            texture_scale: (1.0, 1.0),
+            texture_offset: (0.0, 0.0),
+            texture_rotation: 0.0,
+            texture_wrap: TextureWrap::default(),
+            texture_filter: TextureFilter::default(),
             reflectiveness: None,
             specularity: None,
             refraction: None,
@@ -175,3 +601,78 @@ impl Default for MaterialBuilder<'_> {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Ray;
+
+    /// creates a one texel map of the given (linear) gray
+    fn map(gray: f64) -> Texture {
+        Texture::from_pixels(1, 1, vec![Color::new(gray, gray, gray)])
+    }
+
+    fn hit() -> HitRecord {
+        let ray = Ray::new(Vec3::new(0.0, 1.0, 0.0), Vec3::new(0.0, -1.0, 0.0));
+
+        HitRecord::new(&ray, 1.0, Vec3::new(0.0, 1.0, 0.0), (0.5, 0.5))
+    }
+
+    /// retrieves the red channel of the diffuse color of a white material
+    fn diffuse_red(builder: &mut MaterialBuilder) -> f64 {
+        let material = builder.set_color(Color::new(1.0, 1.0, 1.0)).build();
+        let bsdf = material
+            .get_bsdf(&hit(), Color::new(1.0, 1.0, 1.0), 1.0)
+            .expect("the material should be physically based");
+
+        bsdf.get_diffuse_color().get_red()
+    }
+
+    #[test]
+    fn metallic_map_alone_makes_the_material_metallic() {
+        let diffuse = diffuse_red(MaterialBuilder::default().set_metallic_map(map(1.0)));
+
+        assert!(
+            diffuse.abs() < 1e-9,
+            "expected no diffuse color, found {}",
+            diffuse
+        );
+    }
+
+    #[test]
+    fn transmission_map_alone_makes_the_material_transmissive() {
+        let diffuse = diffuse_red(MaterialBuilder::default().set_transmission_map(map(1.0)));
+
+        assert!(
+            diffuse.abs() < 1e-9,
+            "expected no diffuse color, found {}",
+            diffuse
+        );
+    }
+
+    #[test]
+    fn map_multiplies_the_set_value() {
+        let diffuse = diffuse_red(
+            MaterialBuilder::default()
+                .set_metallic(0.5)
+                .set_metallic_map(map(0.5)),
+        );
+
+        assert!(
+            (diffuse - 0.75).abs() < 1e-9,
+            "expected 0.75, found {}",
+            diffuse
+        );
+    }
+
+    #[test]
+    fn legacy_material_has_no_bsdf() {
+        let material = MaterialBuilder::default().build();
+
+        assert!(
+            material
+                .get_bsdf(&hit(), Color::new(1.0, 1.0, 1.0), 1.0)
+                .is_none()
+        );
+    }
+}
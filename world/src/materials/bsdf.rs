@@ -0,0 +1,511 @@
+use std::f64::consts::PI;
+
+use canvas::Color;
+use rand::{Rng, RngCore};
+
+use crate::Vec3;
+
+/// the microfacets' roughness (alpha) under which a surface is treated as a perfect mirror (or a
+/// perfectly clear refraction)
+const SPECULAR_ALPHA: f64 = 1e-3;
+
+/// An enum containing the lobes a [`Bsdf`] scatters the light with
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum BsdfLobe {
+    /// the light enters the surface and leaves it in any direction (lambertian)
+    Diffuse,
+    /// the light is reflected by the microfacets
+    Reflection,
+    /// the light is refracted trough the microfacets
+    Transmission,
+}
+
+/// A direction sampled from a [`Bsdf`]
+///
+/// the weight is the bsdf times the cosine divided by the pdf of the direction, so it's all
+/// that's left to multiply the light coming from the direction by
+#[derive(Debug, Clone, Copy)]
+pub struct BsdfSample {
+    direction: Vec3,
+    weight: Color,
+    lobe: BsdfLobe,
+    is_specular: bool,
+}
+
+impl BsdfSample {
+    /// retrieves the (unit) direction the light comes from
+    pub fn get_direction(&self) -> &Vec3 {
+        &self.direction
+    }
+
+    /// retrieves the bsdf times the cosine divided by the pdf of the direction
+    pub fn get_weight(&self) -> &Color {
+        &self.weight
+    }
+
+    /// retrieves the lobe the direction was sampled from
+    pub fn get_lobe(&self) -> BsdfLobe {
+        self.lobe
+    }
+
+    /// retrieves whether the direction is the only one the lobe scatters to (a perfect mirror or
+    /// refraction), such directions can't be found by sampling the lights
+    pub fn is_specular(&self) -> bool {
+        self.is_specular
+    }
+}
+
+/// Object abstracting the physically based scattering of a surface point
+///
+/// Trough the use of this object the integrators shade a point with the metallic roughness
+/// model: a lambertian base under a dielectric coating, a metal or a (rough) glass, blended by
+/// `metallic` and `transmission`; the microfacets follow the GGX distribution with Smith's
+/// masking and the Fresnel reflectance uses Schlick's approximation
+///
+/// every direction points away from the surface: `outgoing` towards the viewer and `incoming`
+/// towards the light
+///
+/// # Examples
+///
+/// Shade a rough gold point lit from above
+///
+/// ```no_run
+/// # use canvas::Color;
+/// # use world::{Bsdf, Vec3};
+/// let bsdf = Bsdf::new(
+///     Vec3::new(0.0, 1.0, 0.0),
+///     Color::new(1.0, 0.78, 0.34),
+///     1.0,
+///     0.4,
+///     1.5,
+///     0.0,
+/// );
+///
+/// let outgoing = *Vec3::new(1.0, 1.0, 0.0).make_unit();
+/// let incoming = Vec3::new(0.0, 1.0, 0.0);
+///
+/// println!("{:?}", bsdf.evaluate(&outgoing, &incoming));
+/// ```
+#[derive(Debug, Clone)]
+pub struct Bsdf {
+    normal: Vec3,
+    tangent: Vec3,
+    bitangent: Vec3,
+    base_color: Color,
+    metallic: f64,
+    alpha: f64,
+    ior: f64,
+    transmission: f64,
+}
+
+impl Bsdf {
+    /// creates the bsdf of a point with the given (outward) shading normal, `ior` is the index of
+    /// refraction of the inside of the surface relative to the outside (smaller than 1 for a
+    /// bubble of air in water)
+    pub fn new(
+        mut normal: Vec3,
+        base_color: Color,
+        metallic: f64,
+        roughness: f64,
+        ior: f64,
+        transmission: f64,
+    ) -> Self {
+        normal.make_unit();
+        let tangent = normal.get_perpendicular();
+        let bitangent = normal.cross_product(&tangent);
+        let roughness = roughness.clamp(0.0, 1.0);
+
+        Self {
+            normal,
+            tangent,
+            bitangent,
+            base_color,
+            metallic: metallic.clamp(0.0, 1.0),
+            // the perceived roughness is linear in the square root of the distribution's alpha
+            alpha: roughness * roughness,
+            ior: ior.max(f64::EPSILON),
+            transmission: transmission.clamp(0.0, 1.0),
+        }
+    }
+
+    /// retrieves the color reflected diffusely (by the non metallic, opaque part)
+    pub fn get_diffuse_color(&self) -> Color {
+        self.base_color * ((1.0 - self.metallic) * (1.0 - self.transmission))
+    }
+
+    /// returns the bsdf of the pair of directions, the perfect mirrors and refractions are left
+    /// out since no pair of directions can be found on them by chance
+    pub fn evaluate(&self, outgoing: &Vec3, incoming: &Vec3) -> Color {
+        self.evaluate_lobes(&self.to_local(outgoing), &self.to_local(incoming), true)
+    }
+
+    /// returns the probability density of sampling `incoming` (see `sample`)
+    pub fn get_pdf(&self, outgoing: &Vec3, incoming: &Vec3, include_diffuse: bool) -> f64 {
+        self.get_local_pdf(
+            &self.to_local(outgoing),
+            &self.to_local(incoming),
+            include_diffuse,
+        )
+    }
+
+    /// samples the direction the light comes from (choosing a lobe by how much light it
+    /// scatters), without `include_diffuse` only the reflection and the transmission are
+    /// sampled; None is returned if the sampled direction is absorbed
+    pub fn sample(
+        &self,
+        outgoing: &Vec3,
+        rng: &mut dyn RngCore,
+        include_diffuse: bool,
+    ) -> Option<BsdfSample> {
+        let outgoing = self.to_local(outgoing);
+        let (diffuse, reflection, transmission) =
+            self.get_lobe_probabilities(&outgoing, include_diffuse);
+        let total = diffuse + reflection + transmission;
+
+        if total <= 0.0 {
+            return None;
+        }
+
+        let choice = rng.random::<f64>() * total;
+        let side = outgoing.get_z().signum();
+
+        let (lobe, incoming) = if choice < diffuse {
+            (BsdfLobe::Diffuse, sample_cosine(rng, side))
+        } else {
+            let microfacet = self.sample_microfacet(rng, side);
+
+            // the microfacets facing away from the viewer can't be seen
+            if microfacet.dot_product(&outgoing) <= 0.0 {
+                return None;
+            }
+
+            if choice < diffuse + reflection {
+                (BsdfLobe::Reflection, reflect(&outgoing, &microfacet))
+            } else if transmission > 0.0 {
+                (
+                    BsdfLobe::Transmission,
+                    refract(&outgoing, &microfacet, self.get_relative_ior(&outgoing))?,
+                )
+            } else {
+                return None;
+            }
+        };
+
+        // a microfacet can send the light to the wrong side of the surface (it's absorbed)
+        if (lobe == BsdfLobe::Transmission) == (incoming.get_z() * outgoing.get_z() > 0.0) {
+            return None;
+        }
+
+        let is_specular = lobe != BsdfLobe::Diffuse && self.alpha < SPECULAR_ALPHA;
+
+        let weight = if is_specular {
+            // a perfect mirror or refraction scatters everything in one direction, so its
+            // scattering is only divided by the chance of choosing it (for the refraction they
+            // are the same and only the change of medium is left)
+            let relative_ior = self.get_relative_ior(&outgoing);
+
+            if lobe == BsdfLobe::Reflection {
+                self.get_reflectance(outgoing.get_z().abs(), relative_ior) * (total / reflection)
+            } else {
+                self.base_color * (total / (relative_ior * relative_ior))
+            }
+        } else {
+            let pdf = self.get_local_pdf(&outgoing, &incoming, include_diffuse);
+
+            if pdf <= 0.0 {
+                return None;
+            }
+
+            self.evaluate_lobes(&outgoing, &incoming, include_diffuse)
+                * (incoming.get_z().abs() / pdf)
+        };
+
+        Some(BsdfSample {
+            direction: *self.to_world(&incoming).make_unit(),
+            weight,
+            lobe,
+            is_specular,
+        })
+    }
+
+    /// brings the direction into the frame of the normal (the normal is the z axis)
+    fn to_local(&self, direction: &Vec3) -> Vec3 {
+        Vec3::new(
+            direction.dot_product(&self.tangent),
+            direction.dot_product(&self.bitangent),
+            direction.dot_product(&self.normal),
+        )
+    }
+
+    /// brings the direction back from the frame of the normal
+    fn to_world(&self, direction: &Vec3) -> Vec3 {
+        self.tangent * *direction.get_x()
+            + self.bitangent * *direction.get_y()
+            + self.normal * *direction.get_z()
+    }
+
+    /// the index of refraction of the side the light goes to over the one it comes from
+    fn get_relative_ior(&self, outgoing: &Vec3) -> f64 {
+        if *outgoing.get_z() >= 0.0 {
+            self.ior
+        } else {
+            1.0 / self.ior
+        }
+    }
+
+    /// the reflectance of the dielectric at the given angle (Schlick's approximation with the
+    /// angle of the less dense medium), total internal reflection is accounted for
+    fn get_dielectric_reflectance(&self, cos_incident: f64, relative_ior: f64) -> f64 {
+        let sin_refracted_squared = (1.0 - cos_incident * cos_incident) / relative_ior.powi(2);
+
+        if sin_refracted_squared >= 1.0 {
+            return 1.0;
+        }
+
+        let cos = if relative_ior >= 1.0 {
+            cos_incident
+        } else {
+            (1.0 - sin_refracted_squared).sqrt()
+        };
+        let reflectance_zero = ((relative_ior - 1.0) / (relative_ior + 1.0)).powi(2);
+
+        reflectance_zero + (1.0 - reflectance_zero) * (1.0 - cos).powi(5)
+    }
+
+    /// the chance of the light getting trough the dielectric coating seen from `outgoing`
+    fn get_dielectric_transmittance(&self, outgoing: &Vec3) -> f64 {
+        1.0 - self
+            .get_dielectric_reflectance(outgoing.get_z().abs(), self.get_relative_ior(outgoing))
+    }
+
+    /// the reflectance of the surface at the given angle, the metal is tinted by the base color
+    fn get_reflectance(&self, cos: f64, relative_ior: f64) -> Color {
+        let white = Color::new(1.0, 1.0, 1.0);
+        let grazing = (1.0 - cos).powi(5);
+        let metal = self.base_color * (1.0 - grazing) + white * grazing;
+
+        metal * self.metallic
+            + white * ((1.0 - self.metallic) * self.get_dielectric_reflectance(cos, relative_ior))
+    }
+
+    /// the chances of sampling the diffuse, the reflection and the transmission lobes (they sum
+    /// to 1 when the diffuse lobe is included)
+    fn get_lobe_probabilities(&self, outgoing: &Vec3, include_diffuse: bool) -> (f64, f64, f64) {
+        let transmittance = self.get_dielectric_transmittance(outgoing);
+        let dielectric = 1.0 - self.metallic;
+
+        let diffuse = if include_diffuse {
+            dielectric * (1.0 - self.transmission) * transmittance
+        } else {
+            0.0
+        };
+
+        (
+            diffuse,
+            self.metallic + dielectric * (1.0 - transmittance),
+            dielectric * self.transmission * transmittance,
+        )
+    }
+
+    /// GGX's distribution of the microfacets' normals
+    fn get_distribution(&self, cos_microfacet: f64) -> f64 {
+        let alpha_squared = self.alpha * self.alpha;
+        let denominator = cos_microfacet * cos_microfacet * (alpha_squared - 1.0) + 1.0;
+
+        alpha_squared / (PI * denominator * denominator)
+    }
+
+    /// Smith's masking of the microfacets seen from the direction
+    fn get_masking(&self, cos: f64) -> f64 {
+        let cos = cos.abs();
+        let alpha_squared = self.alpha * self.alpha;
+
+        2.0 * cos / (cos + (alpha_squared + (1.0 - alpha_squared) * cos * cos).sqrt())
+    }
+
+    /// chooses a microfacet normal (pointing to the `side` of the surface) with a pdf of
+    /// `D(m) * cos(m)`
+    fn sample_microfacet(&self, rng: &mut dyn RngCore, side: f64) -> Vec3 {
+        if self.alpha < SPECULAR_ALPHA {
+            return Vec3::new(0.0, 0.0, side);
+        }
+
+        let choice: f64 = rng.random();
+        let angle = 2.0 * PI * rng.random::<f64>();
+        let tan_squared = self.alpha * self.alpha * choice / (1.0 - choice);
+        let cos = 1.0 / (1.0 + tan_squared).sqrt();
+        let sin = (1.0 - cos * cos).max(0.0).sqrt();
+
+        Vec3::new(sin * angle.cos(), sin * angle.sin(), side * cos)
+    }
+
+    /// the half vector of a refraction, the microfacet normal pointing out of the surface
+    fn get_refraction_microfacet(
+        &self,
+        outgoing: &Vec3,
+        incoming: &Vec3,
+        relative_ior: f64,
+    ) -> Option<Vec3> {
+        let mut microfacet = (*incoming) * relative_ior + (*outgoing);
+
+        if microfacet.get_length() <= 0.0 {
+            return None;
+        }
+
+        microfacet.make_unit();
+
+        if *microfacet.get_z() < 0.0 {
+            microfacet = microfacet.get_inverse();
+        }
+
+        // the directions have to be on the opposite sides of the microfacet too
+        if microfacet.dot_product(incoming) * incoming.get_z() < 0.0
+            || microfacet.dot_product(outgoing) * outgoing.get_z() < 0.0
+        {
+            return None;
+        }
+
+        Some(microfacet)
+    }
+
+    /// the bsdf of the (local) pair of directions without the specular lobes
+    fn evaluate_lobes(&self, outgoing: &Vec3, incoming: &Vec3, include_diffuse: bool) -> Color {
+        let mut color = Color::new(0.0, 0.0, 0.0);
+        let (cos_outgoing, cos_incoming) = (*outgoing.get_z(), *incoming.get_z());
+
+        if cos_outgoing == 0.0 || cos_incoming == 0.0 {
+            return color;
+        }
+
+        let relative_ior = self.get_relative_ior(outgoing);
+        let is_specular = self.alpha < SPECULAR_ALPHA;
+
+        if cos_outgoing * cos_incoming > 0.0 {
+            if include_diffuse {
+                color +=
+                    self.get_diffuse_color() * (self.get_dielectric_transmittance(outgoing) / PI);
+            }
+
+            if !is_specular {
+                let microfacet = *((*outgoing) + (*incoming)).make_unit();
+                let cos_microfacet = microfacet.dot_product(outgoing).abs();
+
+                color += self.get_reflectance(cos_microfacet, relative_ior)
+                    * (self.get_distribution(microfacet.get_z().abs())
+                        * self.get_masking(cos_outgoing)
+                        * self.get_masking(cos_incoming)
+                        / (4.0 * cos_outgoing.abs() * cos_incoming.abs()));
+            }
+        } else if !is_specular
+            && self.transmission > 0.0
+            && let Some(microfacet) =
+                self.get_refraction_microfacet(outgoing, incoming, relative_ior)
+        {
+            let cos_incoming_microfacet = microfacet.dot_product(incoming);
+            let cos_outgoing_microfacet = microfacet.dot_product(outgoing);
+            let denominator =
+                (cos_incoming_microfacet + cos_outgoing_microfacet / relative_ior).powi(2);
+            let transmittance =
+                1.0 - self.get_dielectric_reflectance(cos_outgoing_microfacet.abs(), relative_ior);
+
+            // the radiance is squeezed (or spread) by the change of medium
+            color += self.base_color
+                * ((1.0 - self.metallic)
+                    * self.transmission
+                    * transmittance
+                    * self.get_distribution(*microfacet.get_z())
+                    * self.get_masking(cos_outgoing)
+                    * self.get_masking(cos_incoming)
+                    * (cos_incoming_microfacet * cos_outgoing_microfacet).abs()
+                    / (denominator
+                        * cos_incoming.abs()
+                        * cos_outgoing.abs()
+                        * relative_ior.powi(2)));
+        }
+
+        color
+    }
+
+    /// the probability density of sampling the (local) incoming direction
+    fn get_local_pdf(&self, outgoing: &Vec3, incoming: &Vec3, include_diffuse: bool) -> f64 {
+        let (diffuse, reflection, transmission) =
+            self.get_lobe_probabilities(outgoing, include_diffuse);
+        let total = diffuse + reflection + transmission;
+
+        if total <= 0.0 || *outgoing.get_z() == 0.0 {
+            return 0.0;
+        }
+
+        let is_specular = self.alpha < SPECULAR_ALPHA;
+        let mut pdf = 0.0;
+
+        if outgoing.get_z() * incoming.get_z() > 0.0 {
+            pdf += diffuse * incoming.get_z().abs() / PI;
+
+            if !is_specular {
+                let microfacet = *((*outgoing) + (*incoming)).make_unit();
+
+                pdf += reflection
+                    * self.get_distribution(microfacet.get_z().abs())
+                    * microfacet.get_z().abs()
+                    / (4.0 * microfacet.dot_product(outgoing).abs());
+            }
+        } else if !is_specular
+            && transmission > 0.0
+            && let Some(microfacet) =
+                self.get_refraction_microfacet(outgoing, incoming, self.get_relative_ior(outgoing))
+        {
+            let relative_ior = self.get_relative_ior(outgoing);
+            let cos_incoming_microfacet = microfacet.dot_product(incoming);
+            let denominator =
+                (cos_incoming_microfacet + microfacet.dot_product(outgoing) / relative_ior).powi(2);
+
+            pdf += transmission
+                * self.get_distribution(*microfacet.get_z())
+                * microfacet.get_z()
+                * cos_incoming_microfacet.abs()
+                / denominator;
+        }
+
+        pdf / total
+    }
+}
+
+/// reflects the (local) direction around the microfacet normal
+fn reflect(direction: &Vec3, microfacet: &Vec3) -> Vec3 {
+    (*microfacet) * (2.0 * direction.dot_product(microfacet)) - (*direction)
+}
+
+/// refracts the (local) direction trough the microfacet, `relative_ior` is the index of
+/// refraction of the side the light goes to over the one it comes from; None is returned on total
+/// internal reflection
+fn refract(direction: &Vec3, microfacet: &Vec3, relative_ior: f64) -> Option<Vec3> {
+    let cos_incident = direction.dot_product(microfacet);
+    let sin_refracted_squared =
+        (1.0 - cos_incident * cos_incident).max(0.0) / (relative_ior * relative_ior);
+
+    if sin_refracted_squared >= 1.0 {
+        return None;
+    }
+
+    let cos_refracted = (1.0 - sin_refracted_squared).sqrt();
+
+    Some(
+        direction.get_inverse() / relative_ior
+            + (*microfacet) * (cos_incident / relative_ior - cos_refracted),
+    )
+}
+
+/// chooses a random (local) direction on the `side` of the surface, directions close to the
+/// normal are more likely (with a pdf of cos / pi)
+fn sample_cosine(rng: &mut dyn RngCore, side: f64) -> Vec3 {
+    let radius_squared: f64 = rng.random();
+    let angle = 2.0 * PI * rng.random::<f64>();
+    let radius = radius_squared.sqrt();
+
+    Vec3::new(
+        radius * angle.cos(),
+        radius * angle.sin(),
+        side * (1.0 - radius_squared).max(0.0).sqrt(),
+    )
+}
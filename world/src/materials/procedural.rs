@@ -0,0 +1,83 @@
+use canvas::Color;
+
+use crate::{HitRecord, TextureMap, Vec3};
+
+/// module implementing the noise functions behind the procedural textures
+mod noise;
+/// module implementing the patterns of the procedural textures
+mod patterns;
+
+// extracting everything we may need
+use patterns::{Checker, Gradient, Marble, Noise, Voronoi, Wood};
+
+/// An enum containing the spaces a procedural texture can be evaluated in
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub enum TextureSpace {
+    /// the pattern lies on the object's UVs (the point (u, v, 0)), it follows the surface
+    #[default]
+    Uv,
+    /// the pattern fills the world and the object is carved out of it
+    World,
+}
+
+impl TextureSpace {
+    /// retrieves the point of the hit the pattern is evaluated at
+    fn get_point(&self, hit: &HitRecord) -> Vec3 {
+        match self {
+            TextureSpace::Uv => {
+                let (u, v) = hit.get_uv();
+
+                Vec3::new(u, v, 0.0)
+            }
+            TextureSpace::World => *hit.get_point(),
+        }
+    }
+}
+
+/// enum containing all of the procedural textures we can create, every texture blends its two
+/// colors and the scale is the size of the pattern's features:
+/// - `Checker`: squares (cubes in the world) of the given size alternating the two colors
+/// - `Noise`: fractal Perlin noise (fBm) with the given number of octaves
+/// - `Turbulence`: like `Noise` but summing the absolute value of every octave
+/// - `Marble`: veins along the x axis (u) bent by the given amount of turbulence
+/// - `Wood`: rings around the z axis (the UVs' origin) bent by the given amount of noise
+/// - `Gradient`: from the first color at the origin to the second one at the end of the vector
+/// - `Voronoi`: cells around random points, every one with its own blend of the colors
+pub enum ProceduralTextures {
+    Checker(Color, Color, f64),
+    Noise(Color, Color, f64, usize),
+    Turbulence(Color, Color, f64, usize),
+    Marble(Color, Color, f64, f64),
+    Wood(Color, Color, f64, f64),
+    Gradient(Color, Color, Vec3),
+    Voronoi(Color, Color, f64),
+}
+
+impl ProceduralTextures {
+    /// creates the texture evaluating its pattern in the given space
+    pub fn create_texture(texture: ProceduralTextures, space: TextureSpace) -> Box<dyn TextureMap> {
+        match texture {
+            ProceduralTextures::Checker(first, second, size) => {
+                Box::new(Checker::new(first, second, size, space))
+            }
+            ProceduralTextures::Noise(first, second, scale, octaves) => {
+                Box::new(Noise::new(first, second, scale, octaves, false, space))
+            }
+            ProceduralTextures::Turbulence(first, second, scale, octaves) => {
+                Box::new(Noise::new(first, second, scale, octaves, true, space))
+            }
+            ProceduralTextures::Marble(first, second, scale, turbulence) => {
+                Box::new(Marble::new(first, second, scale, turbulence, space))
+            }
+            ProceduralTextures::Wood(first, second, scale, turbulence) => {
+                Box::new(Wood::new(first, second, scale, turbulence, space))
+            }
+            ProceduralTextures::Gradient(first, second, direction) => {
+                Box::new(Gradient::new(first, second, direction, space))
+            }
+            ProceduralTextures::Voronoi(first, second, scale) => {
+                Box::new(Voronoi::new(first, second, scale, space))
+            }
+        }
+    }
+}
@@ -0,0 +1,152 @@
+use crate::Vec3;
+
+/// mixes the coordinates of a lattice cell into a pseudo random number, the same cell always
+/// gives the same number so the patterns are stable between renders
+fn hash(x: i64, y: i64, z: i64) -> u64 {
+    let mut hash = (x as u64).wrapping_mul(0x9E37_79B9_7F4A_7C15)
+        ^ (y as u64).wrapping_mul(0xC2B2_AE3D_27D4_EB4F)
+        ^ (z as u64).wrapping_mul(0x1656_67B1_9E37_79F9);
+
+    hash ^= hash >> 33;
+    hash = hash.wrapping_mul(0xFF51_AFD7_ED55_8CCD);
+    hash ^= hash >> 33;
+    hash = hash.wrapping_mul(0xC4CE_B9FE_1A85_EC53);
+
+    hash ^ (hash >> 33)
+}
+
+/// turns 16 bits of the hash (starting from `shift`) into a number in the [0, 1) range
+fn hash_fraction(hash: u64, shift: u32) -> f64 {
+    ((hash >> shift) & 0xFFFF) as f64 / 65536.0
+}
+
+/// the dot product between the offset and one of the 12 gradients pointing to the edges of a
+/// cube (Perlin's improved noise)
+fn gradient(hash: u64, x: f64, y: f64, z: f64) -> f64 {
+    match hash % 12 {
+        0 => x + y,
+        1 => -x + y,
+        2 => x - y,
+        3 => -x - y,
+        4 => x + z,
+        5 => -x + z,
+        6 => x - z,
+        7 => -x - z,
+        8 => y + z,
+        9 => -y + z,
+        10 => y - z,
+        _ => -y - z,
+    }
+}
+
+/// the quintic curve smoothing the interpolation between the lattice cells
+fn fade(t: f64) -> f64 {
+    t * t * t * (t * (t * 6.0 - 15.0) + 10.0)
+}
+
+fn lerp(a: f64, b: f64, t: f64) -> f64 {
+    a + (b - a) * t
+}
+
+/// Perlin gradient noise at the point, the result is (roughly) in the [-1, 1] range and changes
+/// over a distance of about 1
+fn perlin(point: &Vec3) -> f64 {
+    let (x, y, z) = (*point.get_x(), *point.get_y(), *point.get_z());
+    let (cell_x, cell_y, cell_z) = (x.floor(), y.floor(), z.floor());
+    let (x, y, z) = (x - cell_x, y - cell_y, z - cell_z);
+    let (cell_x, cell_y, cell_z) = (cell_x as i64, cell_y as i64, cell_z as i64);
+
+    // the contribution of the gradient on every corner of the cell
+    let corner = |offset_x: i64, offset_y: i64, offset_z: i64| {
+        gradient(
+            hash(cell_x + offset_x, cell_y + offset_y, cell_z + offset_z),
+            x - offset_x as f64,
+            y - offset_y as f64,
+            z - offset_z as f64,
+        )
+    };
+
+    let (blend_x, blend_y, blend_z) = (fade(x), fade(y), fade(z));
+
+    lerp(
+        lerp(
+            lerp(corner(0, 0, 0), corner(1, 0, 0), blend_x),
+            lerp(corner(0, 1, 0), corner(1, 1, 0), blend_x),
+            blend_y,
+        ),
+        lerp(
+            lerp(corner(0, 0, 1), corner(1, 0, 1), blend_x),
+            lerp(corner(0, 1, 1), corner(1, 1, 1), blend_x),
+            blend_y,
+        ),
+        blend_z,
+    )
+}
+
+/// sums `octaves` layers of noise, every one with twice the frequency and half the amplitude of
+/// the previous one; `layer` shapes the noise of every layer
+fn sum_octaves(point: &Vec3, octaves: usize, layer: impl Fn(f64) -> f64) -> f64 {
+    let mut sum = 0.0;
+    let mut amplitude = 1.0;
+    let mut total_amplitude = 0.0;
+    let mut point = *point;
+
+    for _ in 0..octaves.max(1) {
+        sum += layer(perlin(&point)) * amplitude;
+        total_amplitude += amplitude;
+        amplitude *= 0.5;
+        point = point * 2.0;
+    }
+
+    sum / total_amplitude
+}
+
+/// fractal Brownian motion, layers of noise adding finer and finer details, in the [-1, 1] range
+pub fn fbm(point: &Vec3, octaves: usize) -> f64 {
+    sum_octaves(point, octaves, |noise| noise)
+}
+
+/// like `fbm` but summing the absolute value of every layer, the creases where the noise crosses
+/// 0 make it look turbulent; the result is in the [0, 1] range
+pub fn turbulence(point: &Vec3, octaves: usize) -> f64 {
+    sum_octaves(point, octaves, f64::abs)
+}
+
+/// finds the closest of the feature points scattered one per lattice cell (Worley noise), a
+/// random number in the [0, 1) range identifying its cell is returned
+pub fn voronoi(point: &Vec3) -> f64 {
+    let cell = Vec3::new(
+        point.get_x().floor(),
+        point.get_y().floor(),
+        point.get_z().floor(),
+    );
+    let mut closest = (f64::INFINITY, 0.0);
+
+    // the closest feature point is always in one of the neighbouring cells
+    for offset_x in -1..=1 {
+        for offset_y in -1..=1 {
+            for offset_z in -1..=1 {
+                let neighbour = cell + Vec3::new(offset_x as f64, offset_y as f64, offset_z as f64);
+                let hash = hash(
+                    *neighbour.get_x() as i64,
+                    *neighbour.get_y() as i64,
+                    *neighbour.get_z() as i64,
+                );
+
+                let feature = neighbour
+                    + Vec3::new(
+                        hash_fraction(hash, 0),
+                        hash_fraction(hash, 16),
+                        hash_fraction(hash, 32),
+                    );
+                let distance = (feature - (*point)).get_length();
+
+                if distance < closest.0 {
+                    closest = (distance, hash_fraction(hash, 48));
+                }
+            }
+        }
+    }
+
+    closest.1
+}
@@ -0,0 +1,241 @@
+use std::f64::consts::PI;
+
+use canvas::Color;
+
+use super::TextureSpace;
+use super::noise::{fbm, turbulence, voronoi};
+use crate::{HitRecord, TextureMap, Vec3};
+
+/// the layers of noise used to perturb the marble's veins and the wood's rings
+const TURBULENCE_OCTAVES: usize = 6;
+/// the offset added to the checker's coordinates, surfaces lying exactly on the edge of a square
+/// (like a floor at y = 0) would flicker between two squares otherwise
+const CHECKER_OFFSET: f64 = 1e-4;
+
+/// blends the two colors, `t` goes from 0 (the first color) to 1 (the second one)
+fn blend(first: &Color, second: &Color, t: f64) -> Color {
+    let t = t.clamp(0.0, 1.0);
+
+    (*first) * (1.0 - t) + (*second) * t
+}
+
+/// squares alternating the two colors, a checkerboard on the UVs and a grid of cubes in the
+/// world
+#[derive(Debug)]
+pub struct Checker {
+    first: Color,
+    second: Color,
+    size: f64,
+    space: TextureSpace,
+}
+
+impl Checker {
+    pub fn new(first: Color, second: Color, size: f64, space: TextureSpace) -> Self {
+        Self {
+            first,
+            second,
+            size,
+            space,
+        }
+    }
+}
+
+impl TextureMap for Checker {
+    fn get_color_at(&self, hit: &HitRecord) -> Color {
+        let point = self.space.get_point(hit) / self.size;
+        let square = |coordinate: f64| (coordinate + CHECKER_OFFSET).floor() as i64;
+
+        if (square(*point.get_x()) + square(*point.get_y()) + square(*point.get_z())) % 2 == 0 {
+            self.first
+        } else {
+            self.second
+        }
+    }
+}
+
+/// smooth noise blending the two colors, either fractal (fBm) or turbulent
+#[derive(Debug)]
+pub struct Noise {
+    first: Color,
+    second: Color,
+    scale: f64,
+    octaves: usize,
+    turbulent: bool,
+    space: TextureSpace,
+}
+
+impl Noise {
+    pub fn new(
+        first: Color,
+        second: Color,
+        scale: f64,
+        octaves: usize,
+        turbulent: bool,
+        space: TextureSpace,
+    ) -> Self {
+        Self {
+            first,
+            second,
+            scale,
+            octaves,
+            turbulent,
+            space,
+        }
+    }
+}
+
+impl TextureMap for Noise {
+    fn get_color_at(&self, hit: &HitRecord) -> Color {
+        let point = self.space.get_point(hit) / self.scale;
+
+        let t = if self.turbulent {
+            turbulence(&point, self.octaves)
+        } else {
+            0.5 + 0.5 * fbm(&point, self.octaves)
+        };
+
+        blend(&self.first, &self.second, t)
+    }
+}
+
+/// veins of the second color running along the x axis (u on the UVs), bent by turbulence
+#[derive(Debug)]
+pub struct Marble {
+    first: Color,
+    second: Color,
+    scale: f64,
+    turbulence: f64,
+    space: TextureSpace,
+}
+
+impl Marble {
+    pub fn new(
+        first: Color,
+        second: Color,
+        scale: f64,
+        turbulence: f64,
+        space: TextureSpace,
+    ) -> Self {
+        Self {
+            first,
+            second,
+            scale,
+            turbulence,
+            space,
+        }
+    }
+}
+
+impl TextureMap for Marble {
+    fn get_color_at(&self, hit: &HitRecord) -> Color {
+        let point = self.space.get_point(hit) / self.scale;
+        let phase = point.get_x() + self.turbulence * turbulence(&point, TURBULENCE_OCTAVES);
+
+        blend(&self.first, &self.second, 0.5 + 0.5 * (phase * PI).sin())
+    }
+}
+
+/// rings around the z axis (around the UVs' origin) going from the first color to the second one,
+/// made irregular by noise
+#[derive(Debug)]
+pub struct Wood {
+    first: Color,
+    second: Color,
+    scale: f64,
+    turbulence: f64,
+    space: TextureSpace,
+}
+
+impl Wood {
+    pub fn new(
+        first: Color,
+        second: Color,
+        scale: f64,
+        turbulence: f64,
+        space: TextureSpace,
+    ) -> Self {
+        Self {
+            first,
+            second,
+            scale,
+            turbulence,
+            space,
+        }
+    }
+}
+
+impl TextureMap for Wood {
+    fn get_color_at(&self, hit: &HitRecord) -> Color {
+        let point = self.space.get_point(hit) / self.scale;
+        let radius =
+            point.get_x().hypot(*point.get_y()) + self.turbulence * fbm(&point, TURBULENCE_OCTAVES);
+
+        blend(&self.first, &self.second, radius.rem_euclid(1.0))
+    }
+}
+
+/// a linear gradient from the first color at the origin to the second one at the end of the
+/// direction
+#[derive(Debug)]
+pub struct Gradient {
+    first: Color,
+    second: Color,
+    direction: Vec3,
+    space: TextureSpace,
+}
+
+impl Gradient {
+    pub fn new(first: Color, second: Color, direction: Vec3, space: TextureSpace) -> Self {
+        Self {
+            first,
+            second,
+            direction,
+            space,
+        }
+    }
+}
+
+impl TextureMap for Gradient {
+    fn get_color_at(&self, hit: &HitRecord) -> Color {
+        let point = self.space.get_point(hit);
+        let length_squared = self.direction.dot_product(&self.direction);
+
+        if length_squared <= 0.0 {
+            return self.first;
+        }
+
+        blend(
+            &self.first,
+            &self.second,
+            point.dot_product(&self.direction) / length_squared,
+        )
+    }
+}
+
+/// cells around randomly scattered points, every cell gets its own blend of the two colors
+#[derive(Debug)]
+pub struct Voronoi {
+    first: Color,
+    second: Color,
+    scale: f64,
+    space: TextureSpace,
+}
+
+impl Voronoi {
+    pub fn new(first: Color, second: Color, scale: f64, space: TextureSpace) -> Self {
+        Self {
+            first,
+            second,
+            scale,
+            space,
+        }
+    }
+}
+
+impl TextureMap for Voronoi {
+    fn get_color_at(&self, hit: &HitRecord) -> Color {
+        let cell = voronoi(&(self.space.get_point(hit) / self.scale));
+
+        blend(&self.first, &self.second, cell)
+    }
+}
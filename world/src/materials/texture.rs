@@ -1,42 +1,457 @@
-use canvas::RGB;
-use image::ImageReader;
+use canvas::{Color, RGB};
+use image::{ImageError, ImageReader};
+
+use super::TextureMap;
+use crate::{HitRecord, Vec3};
+
+/// An enum containing the ways a texture is sampled outside of the [0, 1] UV range
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub enum TextureWrap {
+    /// the image is tiled
+    #[default]
+    Repeat,
+    /// the image's borders are stretched
+    Clamp,
+    /// the image is tiled flipping every other tile
+    Mirror,
+}
+
+impl TextureWrap {
+    /// brings the index of a texel back inside of an image `size` texels wide
+    fn wrap(&self, index: i64, size: u32) -> usize {
+        let size = i64::from(size);
+
+        let index = match self {
+            TextureWrap::Repeat => index.rem_euclid(size),
+            TextureWrap::Clamp => index.clamp(0, size - 1),
+            TextureWrap::Mirror => {
+                let index = index.rem_euclid(2 * size);
+
+                if index >= size {
+                    2 * size - 1 - index
+                } else {
+                    index
+                }
+            }
+        };
+
+        index as usize
+    }
+}
+
+/// An enum containing the ways the texels around a UV coordinate are filtered
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub enum TextureFilter {
+    /// the closest texel is used
+    Nearest,
+    /// the four closest texels are blended
+    Bilinear,
+    /// the four closest texels of the two mipmap levels closest to the area seen by the ray are
+    /// blended (it's bilinear when the area isn't known)
+    #[default]
+    Trilinear,
+}
+
+/// a level of a texture's mipmap, every level is half the size of the previous one
+#[derive(Debug, Clone)]
+struct MipmapLevel {
+    pixels: Vec<Color>,
+    width: u32,
+    height: u32,
+}
+
+impl MipmapLevel {
+    /// creates the next (smaller) level by averaging every 2x2 block of texels
+    fn downsample(&self) -> Self {
+        let width = (self.width / 2).max(1);
+        let height = (self.height / 2).max(1);
+        let mut pixels = Vec::with_capacity((width * height) as usize);
+
+        for y in 0..height {
+            for x in 0..width {
+                let mut color = Color::new(0.0, 0.0, 0.0);
+
+                for (offset_x, offset_y) in [(0, 0), (1, 0), (0, 1), (1, 1)] {
+                    // a side one texel long is averaged with itself
+                    let source_x = (2 * x + offset_x).min(self.width - 1);
+                    let source_y = (2 * y + offset_y).min(self.height - 1);
+
+                    color += self.pixels[(source_x + source_y * self.width) as usize];
+                }
+
+                pixels.push(color / 4.0);
+            }
+        }
+
+        Self {
+            pixels,
+            width,
+            height,
+        }
+    }
+}
 
 /// An object rappresenting a texture
+///
+/// the image can be rotated (counterclockwise, around its center), repeated with a scale and
+/// moved with an offset; the UVs are wrapped in the [0, 1] range as the texture's `TextureWrap`
+/// says and the texels are filtered as its `TextureFilter` says
 #[derive(Debug, Clone)]
 pub struct Texture {
-    image: Vec<RGB>,
-    image_width: u32,
-    image_height: u32,
+    mipmap: Vec<MipmapLevel>,
+    scale: (f64, f64),
+    offset: (f64, f64),
+    rotation: f64,
+    wrap: TextureWrap,
+    filter: TextureFilter,
 }
 
 impl Texture {
-    pub fn load(image_path: &str) -> Self {
-        let img = match ImageReader::open(image_path) {
-            Ok(img) => match img.decode() {
-                Ok(img) => img,
-                Err(msg) => panic!("{:?}", msg),
-            },
-            Err(msg) => panic!("{:?}", msg),
-        };
+    /// loads an image holding colors, they are treated as sRGB and converted to linear
+    pub fn load(image_path: &str) -> Result<Self, ImageError> {
+        Self::load_image(image_path, true)
+    }
+
+    /// loads an image holding data (like a normal or a height map), unlike `load` its values
+    /// aren't treated as sRGB colors
+    pub fn load_raw(image_path: &str) -> Result<Self, ImageError> {
+        Self::load_image(image_path, false)
+    }
+
+    /// loads the image building its mipmap, sRGB images are converted to linear
+    fn load_image(image_path: &str, is_srgb: bool) -> Result<Self, ImageError> {
+        let img = ImageReader::open(image_path)?.decode()?;
         let image_width = img.width();
         let image_height = img.height();
-        let mut image_pixels: Vec<RGB> = vec![];
+        let mut image_pixels: Vec<Color> = vec![];
 
         for pixel in img.into_rgb8().pixels() {
-            image_pixels.push(RGB::new(pixel[0], pixel[1], pixel[2]));
+            image_pixels.push(if is_srgb {
+                Color::from(RGB::new(pixel[0], pixel[1], pixel[2]))
+            } else {
+                Color::new(
+                    f64::from(pixel[0]) / 255.0,
+                    f64::from(pixel[1]) / 255.0,
+                    f64::from(pixel[2]) / 255.0,
+                )
+            });
+        }
+
+        Ok(Self::from_pixels(image_width, image_height, image_pixels))
+    }
+
+    /// creates a texture from linear colors stored row by row (from the top one), it panics if
+    /// their number isn't width times height
+    pub fn from_pixels(width: u32, height: u32, pixels: Vec<Color>) -> Self {
+        assert_eq!(
+            pixels.len(),
+            (width * height) as usize,
+            "a texture needs width * height pixels"
+        );
+
+        let mut mipmap = vec![MipmapLevel {
+            pixels,
+            width,
+            height,
+        }];
+
+        while let Some(level) = mipmap.last()
+            && (level.width > 1 || level.height > 1)
+        {
+            mipmap.push(level.downsample());
         }
 
         Self {
-            image_width,
-            image_height,
-            image: image_pixels,
+            mipmap,
+            scale: (1.0, 1.0),
+            offset: (0.0, 0.0),
+            rotation: 0.0,
+            wrap: TextureWrap::default(),
+            filter: TextureFilter::default(),
+        }
+    }
+
+    /// sets how many times the image fits in the [0, 1] UV range along u and v
+    pub fn set_scale(&mut self, scale: (f64, f64)) -> &mut Self {
+        self.scale = scale;
+        self
+    }
+
+    /// sets the offset added to the (scaled) UVs
+    pub fn set_offset(&mut self, offset: (f64, f64)) -> &mut Self {
+        self.offset = offset;
+        self
+    }
+
+    /// sets the image's rotation (in radians)
+    pub fn set_rotation(&mut self, rotation: f64) -> &mut Self {
+        self.rotation = rotation;
+        self
+    }
+
+    /// sets how the image is sampled outside of the [0, 1] range
+    pub fn set_wrap(&mut self, wrap: TextureWrap) -> &mut Self {
+        self.wrap = wrap;
+        self
+    }
+
+    /// sets how the texels are filtered
+    pub fn set_filter(&mut self, filter: TextureFilter) -> &mut Self {
+        self.filter = filter;
+        self
+    }
+
+    /// retrieves the color of the image at the given UV coordinates, (0, 0) is the bottom left
+    /// corner of the image and (1, 1) the top right one
+    pub fn get_color(&self, u: f64, v: f64) -> Color {
+        self.get_filtered_color(u, v, 0.0)
+    }
+
+    /// retrieves the color of the image at the given UV coordinates averaged over the area seen
+    /// by the ray, `footprint` is the size of the area in UV units
+    pub fn get_filtered_color(&self, u: f64, v: f64, footprint: f64) -> Color {
+        let (u, v) = self.get_image_uv(u, v);
+
+        match self.filter {
+            TextureFilter::Nearest => self.sample_nearest(&self.mipmap[0], u, v),
+            TextureFilter::Bilinear => self.sample_bilinear(&self.mipmap[0], u, v),
+            TextureFilter::Trilinear => {
+                let image = &self.mipmap[0];
+                let texels = footprint
+                    * self.scale.0.abs().max(self.scale.1.abs())
+                    * f64::from(image.width.max(image.height));
+
+                // every level halves the image, so the level showing the footprint as a single
+                // texel is the footprint's logarithm
+                let level = texels.max(1.0).log2().min((self.mipmap.len() - 1) as f64);
+                let lower_level = level.floor() as usize;
+                let blend = level - level.floor();
+
+                let color = self.sample_bilinear(&self.mipmap[lower_level], u, v);
+
+                if blend > 0.0 {
+                    color * (1.0 - blend)
+                        + self.sample_bilinear(&self.mipmap[lower_level + 1], u, v) * blend
+                } else {
+                    color
+                }
+            }
         }
     }
 
-    pub fn get_color(&self, mut u: f64, mut v: f64) -> RGB {
-        u = f64::floor(f64::clamp(u, 0.0, 1.0) * f64::from(self.image_width));
-        v = f64::floor(f64::clamp(v, 0.0, 1.0) * f64::from(self.image_height));
+    /// retrieves the normal stored in a (tangent space) normal map at the given UV coordinates,
+    /// x grows along u, y along v and z along the surface's normal
+    pub fn get_normal(&self, u: f64, v: f64, footprint: f64) -> Vec3 {
+        let color = self.get_filtered_color(u, v, footprint);
+        let (x, y) = (2.0 * color.get_red() - 1.0, 2.0 * color.get_green() - 1.0);
+        let (sin, cos) = self.rotation.sin_cos();
+        let (sign_u, sign_v) = (self.scale.0.signum(), self.scale.1.signum());
+
+        // the image's axes are turned (and flipped) on the surface
+        Vec3::new(
+            cos * sign_u * x - sin * sign_v * y,
+            sin * sign_u * x + cos * sign_v * y,
+            2.0 * color.get_blue() - 1.0,
+        )
+    }
+
+    /// retrieves how fast the height stored in a (grayscale) height map grows along u and v at
+    /// the given UV coordinates, the heights go from 0 (black) to 1 (white) and the slopes are
+    /// measured per texel
+    pub fn get_height_gradient(&self, u: f64, v: f64) -> (f64, f64) {
+        let (image_u, image_v) = self.get_image_uv(u, v);
+        let image = &self.mipmap[0];
+        let (step_u, step_v) = (1.0 / f64::from(image.width), 1.0 / f64::from(image.height));
+        let height = |u: f64, v: f64| self.sample_bilinear(image, u, v).get_luminance();
+
+        let slope_u = (height(image_u + step_u, image_v) - height(image_u - step_u, image_v)) / 2.0;
+        let slope_v = (height(image_u, image_v + step_v) - height(image_u, image_v - step_v)) / 2.0;
+
+        // the chain rule brings the image's slopes back to the surface's UVs
+        let (sin, cos) = self.rotation.sin_cos();
+
+        (
+            slope_u * cos * self.scale.0 - slope_v * sin * self.scale.1,
+            slope_u * sin * self.scale.0 + slope_v * cos * self.scale.1,
+        )
+    }
+
+    /// brings the surface's UV coordinates into the image's ones applying the rotation, the
+    /// scale and the offset
+    fn get_image_uv(&self, u: f64, v: f64) -> (f64, f64) {
+        let (sin, cos) = self.rotation.sin_cos();
+        let (centered_u, centered_v) = (u - 0.5, v - 0.5);
+
+        // the UVs turn the opposite way of the image
+        (
+            (cos * centered_u + sin * centered_v + 0.5) * self.scale.0 + self.offset.0,
+            (cos * centered_v - sin * centered_u + 0.5) * self.scale.1 + self.offset.1,
+        )
+    }
+
+    /// retrieves the texel of the mipmap level at the given (not wrapped) indices
+    fn get_texel(&self, level: &MipmapLevel, x: i64, y: i64) -> Color {
+        let x = self.wrap.wrap(x, level.width);
+        let y = self.wrap.wrap(y, level.height);
+
+        level.pixels[x + y * level.width as usize]
+    }
+
+    /// retrieves the texel covering the UV coordinates
+    fn sample_nearest(&self, level: &MipmapLevel, u: f64, v: f64) -> Color {
+        // the image starts from the top
+        let x = (u * f64::from(level.width)).floor() as i64;
+        let y = ((1.0 - v) * f64::from(level.height)).floor() as i64;
+
+        self.get_texel(level, x, y)
+    }
+
+    /// blends the four texels whose centers are the closest to the UV coordinates
+    fn sample_bilinear(&self, level: &MipmapLevel, u: f64, v: f64) -> Color {
+        let x = u * f64::from(level.width) - 0.5;
+        let y = (1.0 - v) * f64::from(level.height) - 0.5;
+        let (left, top) = (x.floor(), y.floor());
+        let (blend_x, blend_y) = (x - left, y - top);
+        let (left, top) = (left as i64, top as i64);
+
+        let upper = self.get_texel(level, left, top) * (1.0 - blend_x)
+            + self.get_texel(level, left + 1, top) * blend_x;
+        let lower = self.get_texel(level, left, top + 1) * (1.0 - blend_x)
+            + self.get_texel(level, left + 1, top + 1) * blend_x;
+
+        upper * (1.0 - blend_y) + lower * blend_y
+    }
+}
+
+impl TextureMap for Texture {
+    fn get_color_at(&self, hit: &HitRecord) -> Color {
+        let (u, v) = hit.get_uv();
+
+        self.get_filtered_color(u, v, hit.get_uv_footprint())
+    }
+
+    fn uses_footprint(&self) -> bool {
+        self.filter == TextureFilter::Trilinear
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// a row of four texels whose red channel is 0, 0.25, 0.5 and 0.75
+    fn strip(wrap: TextureWrap) -> Texture {
+        let pixels = (0..4)
+            .map(|x| Color::new(f64::from(x) * 0.25, 0.0, 0.0))
+            .collect();
+        let mut texture = Texture::from_pixels(4, 1, pixels);
+        texture.set_wrap(wrap).set_filter(TextureFilter::Nearest);
+
+        texture
+    }
+
+    /// a 4x4 image of black and white columns, every level after the first one is gray
+    fn stripes() -> Texture {
+        let pixels = (0..16)
+            .map(|index| {
+                let value = f64::from(index % 2);
+                Color::new(value, value, value)
+            })
+            .collect();
+
+        Texture::from_pixels(4, 4, pixels)
+    }
+
+    fn assert_close(found: f64, expected: f64) {
+        assert!(
+            (found - expected).abs() < 1e-9,
+            "expected {}, found {}",
+            expected,
+            found
+        );
+    }
+
+    #[test]
+    fn wraps_negative_indices() {
+        assert_eq!(TextureWrap::Repeat.wrap(-1, 4), 3);
+        assert_eq!(TextureWrap::Repeat.wrap(-5, 4), 3);
+        assert_eq!(TextureWrap::Clamp.wrap(-1, 4), 0);
+        assert_eq!(TextureWrap::Clamp.wrap(9, 4), 3);
+        assert_eq!(TextureWrap::Mirror.wrap(-1, 4), 0);
+        assert_eq!(TextureWrap::Mirror.wrap(-5, 4), 3);
+        assert_eq!(TextureWrap::Mirror.wrap(4, 4), 3);
+        assert_eq!(TextureWrap::Mirror.wrap(8, 4), 0);
+    }
+
+    #[test]
+    fn repeat_wraps_around() {
+        let texture = strip(TextureWrap::Repeat);
+
+        assert_close(texture.get_color(1.0, 0.5).get_red(), 0.0);
+        assert_close(texture.get_color(1.3, 0.5).get_red(), 0.25);
+        assert_close(texture.get_color(-0.1, 0.5).get_red(), 0.75);
+    }
+
+    #[test]
+    fn clamp_stretches_the_borders() {
+        let texture = strip(TextureWrap::Clamp);
+
+        assert_close(texture.get_color(1.0, 0.5).get_red(), 0.75);
+        assert_close(texture.get_color(1.3, 0.5).get_red(), 0.75);
+        assert_close(texture.get_color(-0.1, 0.5).get_red(), 0.0);
+    }
+
+    #[test]
+    fn mirror_flips_every_other_tile() {
+        let texture = strip(TextureWrap::Mirror);
+
+        assert_close(texture.get_color(1.0, 0.5).get_red(), 0.75);
+        assert_close(texture.get_color(1.3, 0.5).get_red(), 0.5);
+        assert_close(texture.get_color(-0.1, 0.5).get_red(), 0.0);
+        assert_close(texture.get_color(-0.3, 0.5).get_red(), 0.25);
+    }
+
+    #[test]
+    fn builds_every_mipmap_level() {
+        let texture = Texture::from_pixels(5, 3, vec![Color::new(1.0, 1.0, 1.0); 15]);
+        let sizes: Vec<(u32, u32)> = texture
+            .mipmap
+            .iter()
+            .map(|level| (level.width, level.height))
+            .collect();
+
+        assert_eq!(sizes, vec![(5, 3), (2, 1), (1, 1)]);
+    }
+
+    #[test]
+    fn chooses_the_mipmap_level_of_the_footprint() {
+        let texture = stripes();
+        // the center of the upper left (black) texel
+        let red = |footprint: f64| {
+            texture
+                .get_filtered_color(0.125, 0.875, footprint)
+                .get_red()
+        };
+
+        // up to a texel the full image is used
+        assert_close(red(0.0), 0.0);
+        assert_close(red(0.25), 0.0);
+        // two texels are seen as one texel of the next (gray) level
+        assert_close(red(0.5), 0.5);
+        // in between the two levels are blended
+        assert_close(red(0.25 * 2.0_f64.sqrt()), 0.25);
+        // past the last level the smallest one is used
+        assert_close(red(100.0), 0.5);
+    }
+
+    #[test]
+    fn only_trilinear_uses_the_footprint() {
+        let mut texture = stripes();
+
+        texture.set_filter(TextureFilter::Bilinear);
+        assert_close(texture.get_filtered_color(0.125, 0.875, 1.0).get_red(), 0.0);
 
-        self.image[(u + (v * f64::from(self.image_width))) as usize]
+        texture.set_filter(TextureFilter::Nearest);
+        assert_close(texture.get_filtered_color(0.125, 0.875, 1.0).get_red(), 0.0);
     }
 }
@@ -1,81 +1,383 @@
-use crate::Light;
+use crate::BoundingBox;
+use crate::DistanceField;
 use crate::Material;
 use crate::Ray;
+use crate::Scene;
+use crate::Transform;
 use crate::Vec3;
-use canvas::RGB;
+use canvas::Color;
+use rand::RngCore;
+use std::cell::OnceCell;
+use std::sync::Arc;
+
+/// the maximum number of hits walked along a ray to find an object's intervals
+const MAX_INTERVAL_HITS: usize = 64;
+/// how far past a hit the next one is looked for when walking an object's intervals
+const INTERVAL_STEP: f64 = 1e-7;
+/// the fraction of the ray's spread used to trace the ray differentials, small differentials
+/// keep UVs repeating many times inside of a pixel from wrapping back to a small footprint
+const DIFFERENTIAL_FRACTION: f64 = 1.0 / 16.0;
+
+/// An object recording where and how a ray hits an object
+///
+/// Trough the use of this object an `Object` reports everything the integrators need to shade
+/// the hit point: the ray's `t`, the point, the unit normals (pointing outside of the object),
+/// if the ray hits the front face (the side the normal points to), the UV coordinates (and the
+/// directions they grow along) and the id of the hit object
+///
+/// # Examples
+///
+/// Check where a ray hits an object
+///
+/// ```no_run
+/// # use world::{MaterialBuilder, Objects, Ray, Vec3};
+/// let sphere = Objects::create_object(Objects::Sphere(
+///     Vec3::new(0.0, 0.0, 5.0),
+///     1.0,
+///     MaterialBuilder::default().build(),
+/// ));
+/// let ray = Ray::new(Vec3::new(0.0, 0.0, 0.0), Vec3::new(0.0, 0.0, 1.0));
+///
+/// if let Some(hit) = sphere.intersect(&ray, 0.0, f64::MAX) {
+///     println!("{:?} {:?}", hit.get_point(), hit.get_shading_normal());
+/// }
+/// ```
+#[derive(Debug, Clone, Copy)]
+pub struct HitRecord {
+    t: f64,
+    point: Vec3,
+    geometric_normal: Vec3,
+    shading_normal: Vec3,
+    front_face: bool,
+    uv: (f64, f64),
+    tangents: Option<(Vec3, Vec3)>,
+    uv_footprint: f64,
+    object_id: usize,
+}
+
+impl HitRecord {
+    /// creates a new hit record of the ray at `t`, the outward normal is used both as the
+    /// geometric and the shading normal
+    pub fn new(ray: &Ray, t: f64, mut outward_normal: Vec3, uv: (f64, f64)) -> Self {
+        outward_normal.make_unit();
+
+        Self {
+            t,
+            point: ray.calculate_ray_position(t),
+            geometric_normal: outward_normal,
+            shading_normal: outward_normal,
+            front_face: ray.get_direction().dot_product(&outward_normal) < 0.0,
+            uv,
+            tangents: None,
+            uv_footprint: 0.0,
+            object_id: 0,
+        }
+    }
+
+    /// sets the normal used for shading (for example an interpolated one), it should point to
+    /// the same side of the geometric normal
+    pub fn set_shading_normal(&mut self, mut shading_normal: Vec3) -> &mut Self {
+        shading_normal.make_unit();
+        self.shading_normal = shading_normal;
+
+        self
+    }
+
+    /// turns the hit inside out: the normals are inverted and the front face becomes the back one
+    /// (used when the inside of an object becomes the outside, like in a CSG difference)
+    pub fn flip_face(&mut self) -> &mut Self {
+        self.geometric_normal = self.geometric_normal.get_inverse();
+        self.shading_normal = self.shading_normal.get_inverse();
+        self.front_face = !self.front_face;
+
+        self
+    }
+
+    /// sets the directions the u and v coordinates grow along on the surface, they're used to
+    /// orient the normal and bump maps
+    pub fn set_tangents(&mut self, tangent: Vec3, bitangent: Vec3) -> &mut Self {
+        self.tangents = Some((tangent, bitangent));
+
+        self
+    }
+
+    /// sets the size (in UV units) of the area seen by the ray around the hit point, it's used to
+    /// filter the textures
+    pub fn set_uv_footprint(&mut self, uv_footprint: f64) -> &mut Self {
+        self.uv_footprint = uv_footprint;
+
+        self
+    }
+
+    /// sets the id of the hit object
+    pub fn set_object_id(&mut self, object_id: usize) -> &mut Self {
+        self.object_id = object_id;
+
+        self
+    }
+
+    /// retrieves the ray's `t` at the hit point
+    pub fn get_t(&self) -> f64 {
+        self.t
+    }
+
+    /// retrieves the hit point
+    pub fn get_point(&self) -> &Vec3 {
+        &self.point
+    }
+
+    /// retrieves the unit normal of the actual surface (pointing outside of the object)
+    pub fn get_geometric_normal(&self) -> &Vec3 {
+        &self.geometric_normal
+    }
+
+    /// retrieves the unit normal used for shading (pointing outside of the object)
+    pub fn get_shading_normal(&self) -> &Vec3 {
+        &self.shading_normal
+    }
+
+    /// tells if the ray hit the side of the surface the normal points to
+    pub fn is_front_face(&self) -> bool {
+        self.front_face
+    }
+
+    /// retrieves the UV coordinates of the hit point
+    pub fn get_uv(&self) -> (f64, f64) {
+        self.uv
+    }
+
+    /// retrieves the directions the u and v coordinates grow along on the surface (not unit), if
+    /// the object doesn't give them any two directions perpendicular to the shading normal are
+    /// returned
+    pub fn get_tangents(&self) -> (Vec3, Vec3) {
+        self.tangents.unwrap_or_else(|| {
+            let tangent = self.shading_normal.get_perpendicular();
+
+            (tangent, self.shading_normal.cross_product(&tangent))
+        })
+    }
+
+    /// retrieves the size (in UV units) of the area seen by the ray around the hit point, 0 when
+    /// it's not known
+    pub fn get_uv_footprint(&self) -> f64 {
+        self.uv_footprint
+    }
+
+    /// retrieves the id of the hit object (its index in the scene's bounding volume hierarchy)
+    pub fn get_object_id(&self) -> usize {
+        self.object_id
+    }
+}
+
+/// An object rappresenting a stretch of a ray that lies inside of an object, between the hit
+/// where the ray enters the object and the one where it leaves it
+#[derive(Debug, Clone, Copy)]
+pub struct Interval {
+    entry: HitRecord,
+    exit: HitRecord,
+}
+
+impl Interval {
+    /// creates a new interval, the entry should come before the exit along the ray
+    pub fn new(entry: HitRecord, exit: HitRecord) -> Self {
+        Self { entry, exit }
+    }
+
+    /// retrieves the hit where the ray enters the object
+    pub fn get_entry(&self) -> &HitRecord {
+        &self.entry
+    }
+
+    /// retrieves the hit where the ray leaves the object
+    pub fn get_exit(&self) -> &HitRecord {
+        &self.exit
+    }
+}
 
 /// An object rappresenting the intersection between an object and a ray
 pub struct ObjectRayIntersection<'a> {
     ray: Ray,
     viewing_vector: Vec3,
-    object: &'a Box<dyn Object>,
-    object_point: Vec3,
+    object: &'a dyn Object,
+    hit: HitRecord,
+    shaded_hit: OnceCell<HitRecord>,
+    uv_footprint: OnceCell<f64>,
     is_light_hit: bool,
 }
 
 impl<'a> ObjectRayIntersection<'a> {
     /// creates a new ObjectRayIntersection object, it's a private function since only
     /// `check_intersection` should be really used
-    fn new(ray: Ray, t: f64, object: &'a Box<dyn Object>, is_light_hit: bool) -> Self {
-        let object_point = ray.calculate_ray_position(t);
+    fn new(ray: Ray, hit: HitRecord, object: &'a dyn Object, is_light_hit: bool) -> Self {
         let viewing_vector = ray.get_direction().get_inverse();
 
         Self {
             ray,
             viewing_vector,
             object,
-            object_point,
+            hit,
+            shaded_hit: OnceCell::new(),
+            uv_footprint: OnceCell::new(),
             is_light_hit,
         }
     }
 
-    /// this function checks for an interaction between the given ray and the list of world objects
-    pub fn check_intersection(
+    /// this function checks for an interaction between the given ray and the scene's objects
+    /// (and light objects) trough the scene's bounding volume hierarchy
+    pub fn check_intersection(ray: Ray, scene: &'a Scene, min_t: f64, max_t: f64) -> Option<Self> {
+        Self::find_intersection(ray, scene, min_t, max_t, false)
+    }
+
+    /// like `check_intersection` but the light objects are ignored (the ray goes trough them)
+    pub fn check_object_intersection(
         ray: Ray,
-        objects: &'a [Box<dyn Object>],
-        lights: &'a [Box<dyn Light>],
+        scene: &'a Scene,
         min_t: f64,
         max_t: f64,
     ) -> Option<Self> {
-        let mut smallest_t = f64::MAX;
-        let mut hit_object: Option<&Box<dyn Object>> = None;
-        let mut is_light_hit = false;
-
-        for object in objects {
-            if let Some(t) = object.is_object_hit(&ray) {
-                if t < smallest_t && t > min_t && t < max_t {
-                    smallest_t = t;
-                    hit_object = Some(object);
-                }
-            }
-        }
+        Self::find_intersection(ray, scene, min_t, max_t, true)
+    }
+
+    /// finds the closest primitive hit by the ray, skipping the light objects if asked to
+    fn find_intersection(
+        ray: Ray,
+        scene: &'a Scene,
+        min_t: f64,
+        max_t: f64,
+        skip_lights: bool,
+    ) -> Option<Self> {
+        let mut closest_hit: Option<HitRecord> = None;
 
-        // i check if a light is being hit before the object
-        for light in lights {
-            if let Some(light_object) = light.get_object() {
-                if let Some(t) = light_object.is_object_hit(&ray) {
-                    if t < smallest_t && t > min_t && t < max_t {
-                        smallest_t = t;
-                        is_light_hit = true;
-                        hit_object = Some(light_object);
+        let mut closest =
+            scene
+                .get_bvh()
+                .find_closest(&ray, min_t, max_t, |primitive, min_t, max_t| {
+                    let (object, is_light) = scene.get_bvh_primitive(primitive);
+
+                    if skip_lights && is_light {
+                        return None;
                     }
-                }
+
+                    let mut hit = object.intersect(&ray, min_t, max_t)?;
+
+                    // the hierarchy keeps only the hits closer than the closest one found
+                    if hit.get_t() <= min_t || hit.get_t() >= max_t {
+                        return None;
+                    }
+
+                    hit.set_object_id(primitive);
+                    closest_hit = Some(hit);
+
+                    Some(hit.get_t())
+                });
+
+        // the unbounded primitives are outside of the hierarchy so they're checked one by one
+        for primitive in scene.get_unbounded_primitives() {
+            let closest_t = closest.map_or(max_t, |(_, t)| t);
+            let (object, is_light) = scene.get_bvh_primitive(primitive);
+
+            if skip_lights && is_light {
+                continue;
+            }
+
+            if let Some(mut hit) = object.intersect(&ray, min_t, closest_t) {
+                hit.set_object_id(primitive);
+                closest_hit = Some(hit);
+                closest = Some((primitive, hit.get_t()));
             }
         }
 
-        hit_object.map(|hit_object| Self::new(ray, smallest_t, hit_object, is_light_hit))
+        let (primitive, _) = closest?;
+        let (hit_object, is_light_hit) = scene.get_bvh_primitive(primitive);
+
+        Some(Self::new(ray, closest_hit?, hit_object, is_light_hit))
     }
 
     /// returns the point at which the object has been hit
     pub fn get_hit_point(&self) -> &Vec3 {
-        &self.object_point
+        self.hit.get_point()
+    }
+
+    /// returns the record of the hit, its shading normal is bent by the material's normal and
+    /// bump maps (they're only applied the first time the record is needed)
+    pub fn get_hit_record(&self) -> &HitRecord {
+        self.shaded_hit.get_or_init(|| {
+            let material = self.object.get_material();
+            let mut hit = self.hit;
+
+            if self.ray.get_spread() > 0.0 && material.normal_map_uses_footprint() {
+                hit.set_uv_footprint(self.get_uv_footprint());
+            }
+
+            if let Some(mapped_normal) = material.get_mapped_normal(&hit) {
+                hit.set_shading_normal(mapped_normal);
+            }
+
+            hit
+        })
     }
 
     /// returns the hit object
-    pub fn get_hit_object(&self) -> &Box<dyn Object> {
+    pub fn get_hit_object(&self) -> &'a dyn Object {
         self.object
     }
 
+    /// returns the color of the hit object at the hit point, the textures are filtered over the
+    /// area seen by the ray (if the ray has a spread)
+    pub fn get_hit_color(&self) -> Color {
+        let mut hit = self.hit;
+
+        if self.ray.get_spread() > 0.0
+            && let Some(texture) = self.object.get_material().get_texture()
+            && texture.uses_footprint()
+        {
+            hit.set_uv_footprint(self.get_uv_footprint());
+        }
+
+        self.object.get_color(&hit)
+    }
+
+    /// retrieves the size of the area seen by the ray in UV units (it's estimated only once)
+    fn get_uv_footprint(&self) -> f64 {
+        *self
+            .uv_footprint
+            .get_or_init(|| self.estimate_uv_footprint())
+    }
+
+    /// estimates the size of the area seen by the ray in UV units by tracing two rays slightly
+    /// away from it (ray differentials) and comparing the UVs of their hits
+    fn estimate_uv_footprint(&self) -> f64 {
+        let direction = self.ray.get_direction();
+        let spread = self.ray.get_spread() * DIFFERENTIAL_FRACTION;
+        let tangent = direction.get_perpendicular();
+        let bitangent = direction.cross_product(&tangent);
+        let (u, v) = self.hit.get_uv();
+        let t = self.hit.get_t();
+
+        [tangent, bitangent]
+            .into_iter()
+            .filter_map(|offset| {
+                let differential =
+                    Ray::new(*self.ray.get_position(), (*direction) + offset * spread);
+
+                // rays missing the object (or hitting a far part of it) don't tell anything
+                self.object.intersect(&differential, t * 0.5, t * 2.0)
+            })
+            .map(|hit| {
+                let (other_u, other_v) = hit.get_uv();
+
+                // periodic UVs (like the ones of a sphere) jump from 1 to 0
+                let distance = |a: f64, b: f64| {
+                    let distance = (a - b).abs();
+
+                    distance.min((1.0 - distance).abs())
+                };
+
+                distance(u, other_u).max(distance(v, other_v)) / DIFFERENTIAL_FRACTION
+            })
+            .fold(0.0, f64::max)
+    }
+
     /// returns the vector that points from the `hit point` to the ray's starting position
     pub fn get_viewing_vector(&self) -> &Vec3 {
         &self.viewing_vector
@@ -93,38 +395,157 @@ impl<'a> ObjectRayIntersection<'a> {
 }
 
 /// trait for implementing the necessary functions to make a type a `Object`
-pub trait Object {
-    /// this method should do all of the necessary calculations to check if a ray hits an object
-    /// and return the `t` ray parameter that is closest to the ray
-    fn is_object_hit(&self, ray: &Ray) -> Option<f64>;
+///
+/// objects are shared between the rendering threads so they have to be `Send` and `Sync`
+pub trait Object: Send + Sync {
+    /// this method should do all of the necessary calculations to check if a ray hits the object
+    /// and return the record of the closest hit with a `t` between `min_t` and `max_t`
+    fn intersect(&self, ray: &Ray, min_t: f64, max_t: f64) -> Option<HitRecord>;
 
-    /// this method should retun a unit vector of the normal of the object based upon a point in
-    /// space that is on the surface of the object (the value could be None if the point given is
-    /// not on the surface
-    fn get_normal(&self, point: Vec3) -> Option<Vec3>;
+    /// this method should return every interval (sorted along the ray) in which the whole line
+    /// of the ray lies inside of the object, it's used by the CSG operations
+    ///
+    /// by default the hits of `intersect` are walked one after the other pairing every front
+    /// face hit with the following back face hit; it works for every closed object while open
+    /// surfaces (like panels) have no inside and give no interval
+    fn get_intervals(&self, ray: &Ray) -> Vec<Interval> {
+        let mut intervals = vec![];
+        let mut entry: Option<HitRecord> = None;
+        let mut min_t = f64::NEG_INFINITY;
+
+        for _ in 0..MAX_INTERVAL_HITS {
+            let Some(hit) = self.intersect(ray, min_t, f64::MAX) else {
+                break;
+            };
+
+            if hit.is_front_face() {
+                entry = Some(hit);
+            } else if let Some(entry) = entry.take() {
+                intervals.push(Interval::new(entry, hit));
+            }
+
+            min_t = hit.get_t() + INTERVAL_STEP;
+        }
+
+        intervals
+    }
 
     /// this methos should return the object's material
     fn get_material(&self) -> &Material;
 
-    /// this method should return the color of the sphere at the specified point
-    fn get_color(&self, point: Vec3) -> RGB;
+    /// this method returns the color of the object at the hit point, the material's texture (if
+    /// any) is added to its color at the hit's UV coordinates
+    fn get_color(&self, hit: &HitRecord) -> Color {
+        let material = self.get_material();
+        let mut final_color = *material.get_color();
+
+        if let Some(texture) = material.get_texture() {
+            final_color += texture.get_color_at(hit);
+        }
+
+        final_color
+    }
+
+    /// this method should return the smallest axis aligned box containing the whole object (it's
+    /// used to build the scene's bounding volume hierarchy)
+    fn get_bounding_box(&self) -> BoundingBox;
+
+    /// this method returns the area of the object's surface, it's 0 for the objects whose surface
+    /// can't be sampled (see `sample_surface`)
+    fn get_area(&self) -> f64 {
+        0.0
+    }
+
+    /// this method chooses a point of the object's surface (every part of the surface is as
+    /// likely as any other, unless `get_surface_pdf` says otherwise) and returns it with the
+    /// outward normal, it's used to sample the light of emissive objects; None is returned by the
+    /// objects that can't be sampled
+    fn sample_surface(&self, _rng: &mut dyn RngCore) -> Option<(Vec3, Vec3)> {
+        None
+    }
+
+    /// this method returns the probability density (per unit of area) of `sample_surface`
+    /// choosing the point with the given unit normal, by default every part of the surface is as
+    /// likely as any other
+    fn get_surface_pdf(&self, _point: &Vec3, _normal: &Vec3) -> f64 {
+        1.0 / self.get_area()
+    }
 }
 
-mod panel;
 /// modules implementing various objects
+mod cone;
+/// module implementing the constructive solid geometry operations
+mod csg;
+mod cuboid;
+mod cylinder;
+mod disk;
+/// module implementing a transformed instance of another object
+mod instance;
+mod mesh;
+/// module implementing the Wavefront OBJ loader used by `Mesh`
+mod obj_loader;
+mod panel;
+mod plane;
+/// module implementing the sphere traced signed distance field object
+mod sdf;
 mod sphere;
+mod torus;
 mod triangle;
 
 // extracting everything we may need
-use panel::Panel;
+use cone::Cone;
+use csg::Csg;
+pub use csg::CsgOperation;
+use cuboid::Cuboid;
+use cylinder::Cylinder;
+use disk::Disk;
+use instance::Instance;
+pub use mesh::Mesh;
+pub use obj_loader::ObjError;
+pub(crate) use panel::Panel;
+use plane::Plane;
+use sdf::Sdf;
 use sphere::Sphere;
+use torus::Torus;
 use triangle::Triangle;
 
 /// enum containing all of the object types we can create
+///
+/// the shapes with an axis are described by the center of their base (or their center) and the
+/// axis' direction:
+/// - `Cuboid`: two opposite corners of an axis aligned box
+/// - `OrientedCuboid`: the box's center, its size and the directions of its x and y sides
+/// - `Cylinder`: the base's center, the axis, the radius and the height
+/// - `Cone`: the base's center, the axis (pointing to the apex), the base's radius and the height
+/// - `Disk`: the center, the normal and the radius
+/// - `Annulus`: the center, the normal, the inner and the outer radius
+/// - `Torus`: the center, the axis, the major (center to tube) and the minor (tube) radius
+/// - `Csg`: the operation combining the two objects
+/// - `Plane`: a point of the infinite plane and its normal
+/// - `Sdf`: the signed distance field of the surface (see [`crate::DistanceFields`])
+/// - `InterpolatedTriangle`: the three vertices, their (optional) normals and their (optional)
+///   texture coordinates
 pub enum Objects {
     Sphere(Vec3, f64, Material),
     Triangle(Vec3, Vec3, Vec3, Material),
     Panel(Vec3, f64, f64, Vec3, Material),
+    Instance(Arc<dyn Object>, Transform),
+    Cuboid(Vec3, Vec3, Material),
+    OrientedCuboid(Vec3, Vec3, Vec3, Vec3, Material),
+    Cylinder(Vec3, Vec3, f64, f64, Material),
+    Cone(Vec3, Vec3, f64, f64, Material),
+    Disk(Vec3, Vec3, f64, Material),
+    Annulus(Vec3, Vec3, f64, f64, Material),
+    Torus(Vec3, Vec3, f64, f64, Material),
+    Csg(CsgOperation, Arc<dyn Object>, Arc<dyn Object>),
+    Plane(Vec3, Vec3, Material),
+    Sdf(Arc<dyn DistanceField>, Material),
+    InterpolatedTriangle(
+        [Vec3; 3],
+        Option<[Vec3; 3]>,
+        Option<[(f64, f64); 3]>,
+        Material,
+    ),
 }
 
 impl Objects {
@@ -145,6 +566,62 @@ impl Objects {
                     material,
                 ))
             }
+            Objects::Instance(object, transform) => Box::new(Instance::new(object, transform)),
+            Objects::Cuboid(corner_1, corner_2, material) => {
+                Box::new(Cuboid::new(corner_1, corner_2, material))
+            }
+            Objects::OrientedCuboid(center, size, x_axis, y_axis, material) => {
+                let half_size = size * 0.5;
+
+                Box::new(Instance::new(
+                    Arc::new(Cuboid::new(half_size.get_inverse(), half_size, material)),
+                    Transform::from_axes(center, x_axis, y_axis),
+                ))
+            }
+            Objects::Cylinder(base_center, axis, radius, height, material) => {
+                Box::new(Instance::new(
+                    Arc::new(Cylinder::new(radius, height, material)),
+                    Transform::from_axis(base_center, axis),
+                ))
+            }
+            Objects::Cone(base_center, axis, radius, height, material) => Box::new(Instance::new(
+                Arc::new(Cone::new(radius, height, material)),
+                Transform::from_axis(base_center, axis),
+            )),
+            Objects::Disk(center, normal, radius, material) => Box::new(Instance::new(
+                Arc::new(Disk::new(0.0, radius, material)),
+                Transform::from_axis(center, normal),
+            )),
+            Objects::Annulus(center, normal, inner_radius, outer_radius, material) => {
+                Box::new(Instance::new(
+                    Arc::new(Disk::new(inner_radius, outer_radius, material)),
+                    Transform::from_axis(center, normal),
+                ))
+            }
+            Objects::Torus(center, axis, major_radius, minor_radius, material) => {
+                Box::new(Instance::new(
+                    Arc::new(Torus::new(major_radius, minor_radius, material)),
+                    Transform::from_axis(center, axis),
+                ))
+            }
+            Objects::Csg(operation, first, second) => Box::new(Csg::new(operation, first, second)),
+            Objects::Plane(point, normal, material) => {
+                Box::new(Plane::new(point, normal, material))
+            }
+            Objects::Sdf(field, material) => Box::new(Sdf::new(field, material)),
+            Objects::InterpolatedTriangle(
+                [vertice_1, vertice_2, vertice_3],
+                normals,
+                texture_coordinates,
+                material,
+            ) => {
+                let mut triangle = Triangle::new(vertice_1, vertice_2, vertice_3, material);
+                triangle
+                    .set_normals(normals)
+                    .set_texture_coordinates(texture_coordinates);
+
+                Box::new(triangle)
+            }
         }
     }
 }
@@ -0,0 +1,127 @@
+use std::f64::consts::PI;
+
+use rand::{Rng, RngCore};
+
+use super::cylinder::{get_angle_fraction, intersect_cap, sample_ring, solve_quadratic};
+use super::{HitRecord, Material, Object};
+use crate::{BoundingBox, Ray, Vec3};
+
+/// object to abstract a cone (capped at its base) in our ray traced world
+///
+/// Trough the use of this object we can create a cone with its base on the origin and its apex
+/// along the z axis (it's placed in the world with an `Instance`) and check if a ray hits it
+/// with `intersect`; the side is UV mapped by the angle around the axis and the height, the base
+/// by its x and y coordinates
+pub struct Cone {
+    radius: f64,
+    height: f64,
+    material: Material,
+}
+
+impl Cone {
+    pub fn new(radius: f64, height: f64, material: Material) -> Self {
+        Self {
+            radius,
+            height,
+            material,
+        }
+    }
+}
+
+impl Object for Cone {
+    fn intersect(&self, ray: &Ray, min_t: f64, max_t: f64) -> Option<HitRecord> {
+        let (position, direction) = (ray.get_position(), ray.get_direction());
+        let mut closest: Option<HitRecord> = None;
+        let mut closest_t = max_t;
+
+        // the side: x² + y² = (slope * (height - z))²
+        let slope_squared = (self.radius / self.height).powi(2);
+        let apex_distance = self.height - position.get_z();
+
+        let a = direction.get_x().powi(2) + direction.get_y().powi(2)
+            - slope_squared * direction.get_z().powi(2);
+        let half_b = position.get_x() * direction.get_x()
+            + position.get_y() * direction.get_y()
+            + slope_squared * apex_distance * direction.get_z();
+        let c = position.get_x().powi(2) + position.get_y().powi(2)
+            - slope_squared * apex_distance * apex_distance;
+
+        for t in solve_quadratic(a, half_b, c) {
+            let point = ray.calculate_ray_position(t);
+
+            // the equation describes a double cone, only the part between the base and the apex
+            // is ours
+            if t > min_t && t < closest_t && (0.0..=self.height).contains(point.get_z()) {
+                let mut normal = Vec3::new(
+                    *point.get_x(),
+                    *point.get_y(),
+                    slope_squared * (self.height - point.get_z()),
+                );
+
+                // the apex has no normal, it points along the axis
+                if normal.get_length() < f64::EPSILON {
+                    normal = Vec3::new(0.0, 0.0, 1.0);
+                }
+
+                closest_t = t;
+                closest = Some(HitRecord::new(
+                    ray,
+                    t,
+                    normal,
+                    (get_angle_fraction(&point), point.get_z() / self.height),
+                ));
+            }
+        }
+
+        if let Some(hit) = intersect_cap(ray, 0.0, -1.0, self.radius, min_t, closest_t) {
+            closest = Some(hit);
+        }
+
+        closest
+    }
+
+    fn get_material(&self) -> &Material {
+        &(self.material)
+    }
+
+    fn get_bounding_box(&self) -> BoundingBox {
+        BoundingBox::new(
+            Vec3::new(-self.radius, -self.radius, 0.0),
+            Vec3::new(self.radius, self.radius, self.height),
+        )
+    }
+
+    fn get_area(&self) -> f64 {
+        let slant_height = (self.radius * self.radius + self.height * self.height).sqrt();
+
+        PI * self.radius * (self.radius + slant_height)
+    }
+
+    fn sample_surface(&self, rng: &mut dyn RngCore) -> Option<(Vec3, Vec3)> {
+        let base_area = PI * self.radius * self.radius;
+
+        if rng.random::<f64>() * self.get_area() < base_area {
+            return Some((
+                sample_ring(rng, 0.0, self.radius, 0.0),
+                Vec3::new(0.0, 0.0, -1.0),
+            ));
+        }
+
+        // the side's area grows with the square of the distance from the apex
+        let fraction = rng.random::<f64>().sqrt();
+        let angle = 2.0 * PI * rng.random::<f64>();
+        let (cos, sin) = (angle.cos(), angle.sin());
+
+        let mut normal = Vec3::new(self.height * cos, self.height * sin, self.radius);
+        normal.make_unit();
+
+        Some((
+            Vec3::new(
+                self.radius * fraction * cos,
+                self.radius * fraction * sin,
+                self.height * (1.0 - fraction),
+            ),
+            normal,
+        ))
+    }
+}
@@ -0,0 +1,345 @@
+use std::sync::Arc;
+
+use super::{HitRecord, Interval, Material, Object};
+use crate::{BoundingBox, Ray};
+use canvas::Color;
+
+/// An enum containing the ways two objects can be combined by a [`Csg`] node
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum CsgOperation {
+    /// everything inside of either object
+    Union,
+    /// only what is inside of both objects
+    Intersection,
+    /// what is inside of the first object but not inside of the second one
+    Difference,
+}
+
+impl CsgOperation {
+    /// tells if a point is inside of the result given if it's inside of the two objects
+    fn is_inside(&self, inside_first: bool, inside_second: bool) -> bool {
+        match self {
+            CsgOperation::Union => inside_first || inside_second,
+            CsgOperation::Intersection => inside_first && inside_second,
+            CsgOperation::Difference => inside_first && !inside_second,
+        }
+    }
+}
+
+/// object to abstract a constructive solid geometry node in our ray traced world
+///
+/// Trough the use of this object we can combine two closed objects (and other CSG nodes) into a
+/// new one, like a lens made of the intersection of two spheres; the objects' intervals along
+/// the ray are merged and the result is shaded with the first object's material
+///
+/// # Examples
+///
+/// Carve a hole trough a sphere
+///
+/// ```no_run
+/// # use std::sync::Arc;
+/// # use world::{CsgOperation, MaterialBuilder, Objects, Vec3};
+/// let sphere = Objects::create_object(Objects::Sphere(
+///     Vec3::new(0.0, 0.0, 5.0),
+///     1.0,
+///     MaterialBuilder::default().build(),
+/// ));
+/// let hole = Objects::create_object(Objects::Cylinder(
+///     Vec3::new(0.0, -2.0, 5.0),
+///     Vec3::new(0.0, 1.0, 0.0),
+///     0.4,
+///     4.0,
+///     MaterialBuilder::default().build(),
+/// ));
+///
+/// let pierced_sphere = Objects::create_object(Objects::Csg(
+///     CsgOperation::Difference,
+///     Arc::from(sphere),
+///     Arc::from(hole),
+/// ));
+/// ```
+pub struct Csg {
+    operation: CsgOperation,
+    first: Arc<dyn Object>,
+    second: Arc<dyn Object>,
+    bounding_box: BoundingBox,
+}
+
+impl Csg {
+    pub fn new(operation: CsgOperation, first: Arc<dyn Object>, second: Arc<dyn Object>) -> Self {
+        let first_box = first.get_bounding_box();
+        let second_box = second.get_bounding_box();
+
+        let bounding_box = match operation {
+            CsgOperation::Union => first_box.union(&second_box),
+            CsgOperation::Intersection => first_box.intersection(&second_box),
+            CsgOperation::Difference => first_box,
+        };
+
+        Self {
+            operation,
+            first,
+            second,
+            bounding_box,
+        }
+    }
+}
+
+impl Object for Csg {
+    fn intersect(&self, ray: &Ray, min_t: f64, max_t: f64) -> Option<HitRecord> {
+        self.get_intervals(ray)
+            .iter()
+            .flat_map(|interval| [*interval.get_entry(), *interval.get_exit()])
+            .find(|hit| hit.get_t() > min_t && hit.get_t() < max_t)
+    }
+
+    fn get_intervals(&self, ray: &Ray) -> Vec<Interval> {
+        // every boundary of the two objects, with which object it belongs to and if the ray
+        // enters the object there
+        let mut boundaries: Vec<(HitRecord, bool, bool)> = vec![];
+
+        for (is_first, object) in [(true, &self.first), (false, &self.second)] {
+            for interval in object.get_intervals(ray) {
+                boundaries.push((*interval.get_entry(), is_first, true));
+                boundaries.push((*interval.get_exit(), is_first, false));
+            }
+        }
+
+        // where boundaries meet the ray enters the objects before leaving them, so touching
+        // objects are merged by the union instead of being split in two
+        boundaries.sort_by(|(hit, _, entering), (other, _, other_entering)| {
+            hit.get_t()
+                .total_cmp(&other.get_t())
+                .then(other_entering.cmp(entering))
+        });
+
+        let mut intervals = vec![];
+        let mut entry: Option<HitRecord> = None;
+        let (mut inside_first, mut inside_second) = (false, false);
+
+        for (mut hit, is_first, entering) in boundaries {
+            let was_inside = self.operation.is_inside(inside_first, inside_second);
+
+            if is_first {
+                inside_first = entering;
+            } else {
+                inside_second = entering;
+            }
+
+            let is_inside = self.operation.is_inside(inside_first, inside_second);
+
+            if was_inside == is_inside {
+                continue;
+            }
+
+            // entering the result while leaving an object (or the other way around) means the
+            // surface is seen from its inside
+            if is_inside != entering {
+                hit.flip_face();
+            }
+
+            if is_inside {
+                entry = Some(hit);
+            } else if let Some(entry) = entry.take()
+                && entry.get_t() < hit.get_t()
+            {
+                // coincident boundaries can leave empty intervals behind
+                intervals.push(Interval::new(entry, hit));
+            }
+        }
+
+        intervals
+    }
+
+    fn get_material(&self) -> &Material {
+        self.first.get_material()
+    }
+
+    fn get_color(&self, hit: &HitRecord) -> Color {
+        self.first.get_color(hit)
+    }
+
+    fn get_bounding_box(&self) -> BoundingBox {
+        self.bounding_box
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{MaterialBuilder, Objects, Vec3};
+
+    fn sphere(center_x: f64, radius: f64) -> Arc<dyn Object> {
+        Arc::from(Objects::create_object(Objects::Sphere(
+            Vec3::new(center_x, 0.0, 0.0),
+            radius,
+            MaterialBuilder::default().build(),
+        )))
+    }
+
+    /// a box one unit wide along y and z (around the x axis) spanning the range along x
+    fn cuboid(min_x: f64, max_x: f64) -> Arc<dyn Object> {
+        Arc::from(Objects::create_object(Objects::Cuboid(
+            Vec3::new(min_x, -0.5, -0.5),
+            Vec3::new(max_x, 0.5, 0.5),
+            MaterialBuilder::default().build(),
+        )))
+    }
+
+    /// retrieves the x ranges of the intervals of the ray travelling along the x axis trough the
+    /// node (the ray starts at x = 0 so its `t` is the x coordinate)
+    fn spans(
+        operation: CsgOperation,
+        first: Arc<dyn Object>,
+        second: Arc<dyn Object>,
+    ) -> Vec<(f64, f64)> {
+        let ray = Ray::new(Vec3::new(0.0, 0.0, 0.0), Vec3::new(1.0, 0.0, 0.0));
+
+        Csg::new(operation, first, second)
+            .get_intervals(&ray)
+            .iter()
+            .map(|interval| (interval.get_entry().get_t(), interval.get_exit().get_t()))
+            .collect()
+    }
+
+    fn assert_spans(spans: Vec<(f64, f64)>, expected: &[(f64, f64)]) {
+        let matches = spans.len() == expected.len()
+            && spans.iter().zip(expected).all(|(span, expected)| {
+                (span.0 - expected.0).abs() < 1e-6 && (span.1 - expected.1).abs() < 1e-6
+            });
+
+        assert!(matches, "expected {:?}, found {:?}", expected, spans);
+    }
+
+    #[test]
+    fn union_merges_overlapping_objects() {
+        assert_spans(
+            spans(CsgOperation::Union, sphere(10.0, 2.0), sphere(13.0, 2.0)),
+            &[(8.0, 15.0)],
+        );
+    }
+
+    #[test]
+    fn intersection_keeps_the_overlap() {
+        assert_spans(
+            spans(
+                CsgOperation::Intersection,
+                sphere(10.0, 2.0),
+                sphere(13.0, 2.0),
+            ),
+            &[(11.0, 12.0)],
+        );
+    }
+
+    #[test]
+    fn difference_removes_the_overlap() {
+        assert_spans(
+            spans(
+                CsgOperation::Difference,
+                sphere(10.0, 2.0),
+                sphere(13.0, 2.0),
+            ),
+            &[(8.0, 11.0)],
+        );
+    }
+
+    #[test]
+    fn nested_objects() {
+        let (outer, inner) = (sphere(10.0, 3.0), sphere(10.0, 1.0));
+
+        assert_spans(
+            spans(CsgOperation::Union, outer.clone(), inner.clone()),
+            &[(7.0, 13.0)],
+        );
+        assert_spans(
+            spans(CsgOperation::Intersection, outer.clone(), inner.clone()),
+            &[(9.0, 11.0)],
+        );
+        assert_spans(
+            spans(CsgOperation::Difference, outer.clone(), inner.clone()),
+            &[(7.0, 9.0), (11.0, 13.0)],
+        );
+        assert_spans(spans(CsgOperation::Difference, inner, outer), &[]);
+    }
+
+    #[test]
+    fn ray_starting_inside_an_object() {
+        let hollow = Csg::new(CsgOperation::Difference, sphere(0.0, 3.0), sphere(0.0, 1.0));
+        let ray = Ray::new(Vec3::new(0.0, 0.0, 0.0), Vec3::new(1.0, 0.0, 0.0));
+
+        // the ray starts in the hole and first meets the inner side of the shell, which faces it
+        let hit = hollow
+            .intersect(&ray, 0.0, f64::MAX)
+            .expect("the ray should hit the shell");
+        assert!(
+            (hit.get_t() - 1.0).abs() < 1e-6,
+            "expected t = 1, found {}",
+            hit.get_t()
+        );
+        assert!(hit.is_front_face());
+
+        // the intervals cover the whole line of the ray, also behind its start
+        assert_spans(
+            spans(CsgOperation::Union, sphere(0.0, 2.0), sphere(3.0, 2.0)),
+            &[(-2.0, 5.0)],
+        );
+
+        let hit = Csg::new(CsgOperation::Union, sphere(0.0, 2.0), sphere(3.0, 2.0))
+            .intersect(&ray, 0.0, f64::MAX)
+            .expect("the ray should leave the union");
+        assert!(
+            (hit.get_t() - 5.0).abs() < 1e-6,
+            "expected t = 5, found {}",
+            hit.get_t()
+        );
+        assert!(!hit.is_front_face());
+    }
+
+    #[test]
+    fn touching_boundaries() {
+        assert_spans(
+            spans(CsgOperation::Union, cuboid(1.0, 2.0), cuboid(2.0, 3.0)),
+            &[(1.0, 3.0)],
+        );
+        assert_spans(
+            spans(
+                CsgOperation::Intersection,
+                cuboid(1.0, 2.0),
+                cuboid(2.0, 3.0),
+            ),
+            &[],
+        );
+        assert_spans(
+            spans(CsgOperation::Difference, cuboid(1.0, 2.0), cuboid(2.0, 3.0)),
+            &[(1.0, 2.0)],
+        );
+    }
+
+    #[test]
+    fn coincident_boundaries() {
+        assert_spans(
+            spans(CsgOperation::Union, cuboid(1.0, 3.0), cuboid(1.0, 3.0)),
+            &[(1.0, 3.0)],
+        );
+        assert_spans(
+            spans(
+                CsgOperation::Intersection,
+                cuboid(1.0, 3.0),
+                cuboid(1.0, 2.0),
+            ),
+            &[(1.0, 2.0)],
+        );
+        assert_spans(
+            spans(CsgOperation::Difference, cuboid(1.0, 3.0), cuboid(1.0, 2.0)),
+            &[(2.0, 3.0)],
+        );
+        assert_spans(
+            spans(CsgOperation::Difference, cuboid(1.0, 3.0), cuboid(2.0, 3.0)),
+            &[(1.0, 2.0)],
+        );
+        assert_spans(
+            spans(CsgOperation::Difference, cuboid(1.0, 3.0), cuboid(1.0, 3.0)),
+            &[],
+        );
+    }
+}
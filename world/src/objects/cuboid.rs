@@ -0,0 +1,142 @@
+use rand::{Rng, RngCore};
+
+use super::{HitRecord, Material, Object};
+use crate::{BoundingBox, Ray, Vec3};
+
+/// object to abstract an axis aligned box in our ray traced world
+///
+/// Trough the use of this object we can create a box in our world and check if a ray hits it
+/// with `intersect`, every face is UV mapped on its own (from its corner with the smallest
+/// coordinates)
+pub struct Cuboid {
+    min: Vec3,
+    max: Vec3,
+    material: Material,
+}
+
+impl Cuboid {
+    /// creates a new box between two opposite corners
+    pub fn new(corner_1: Vec3, corner_2: Vec3, material: Material) -> Self {
+        let bounding_box = BoundingBox::new(corner_1, corner_2);
+
+        Self {
+            min: *bounding_box.get_min(),
+            max: *bounding_box.get_max(),
+            material,
+        }
+    }
+}
+
+impl Object for Cuboid {
+    fn intersect(&self, ray: &Ray, min_t: f64, max_t: f64) -> Option<HitRecord> {
+        // the slab method: the ray is inside of the box where it's inside of all three slabs,
+        // we keep track of the axis of the last slab entered and of the first one left
+        let mut near = (f64::NEG_INFINITY, 0);
+        let mut far = (f64::INFINITY, 0);
+
+        for axis in 0..3 {
+            let position = ray.get_position().get_axis(axis);
+            let direction = ray.get_direction().get_axis(axis);
+
+            if direction == 0.0 {
+                if position < self.min.get_axis(axis) || position > self.max.get_axis(axis) {
+                    return None;
+                }
+
+                continue;
+            }
+
+            let t_1 = (self.min.get_axis(axis) - position) / direction;
+            let t_2 = (self.max.get_axis(axis) - position) / direction;
+            let (t_enter, t_exit) = (t_1.min(t_2), t_1.max(t_2));
+
+            if t_enter > near.0 {
+                near = (t_enter, axis);
+            }
+
+            if t_exit < far.0 {
+                far = (t_exit, axis);
+            }
+        }
+
+        if near.0 > far.0 {
+            return None;
+        }
+
+        // when the ray starts inside of the box it hits the face it leaves from
+        let ((t, axis), sign) = if near.0 > min_t && near.0 < max_t {
+            (near, -1.0)
+        } else if far.0 > min_t && far.0 < max_t {
+            (far, 1.0)
+        } else {
+            return None;
+        };
+
+        let mut normal = [0.0; 3];
+        normal[axis] = sign * ray.get_direction().get_axis(axis).signum();
+        let outward_normal = Vec3::new(normal[0], normal[1], normal[2]);
+
+        let point = ray.calculate_ray_position(t);
+        let size = self.max - self.min;
+        let [u_axis, v_axis] = [(axis + 1) % 3, (axis + 2) % 3];
+        let uv = (
+            (point.get_axis(u_axis) - self.min.get_axis(u_axis)) / size.get_axis(u_axis),
+            (point.get_axis(v_axis) - self.min.get_axis(v_axis)) / size.get_axis(v_axis),
+        );
+
+        Some(HitRecord::new(ray, t, outward_normal, uv))
+    }
+
+    fn get_material(&self) -> &Material {
+        &(self.material)
+    }
+
+    fn get_bounding_box(&self) -> BoundingBox {
+        // a box could be flat along an axis
+        BoundingBox::new(self.min, self.max).pad(0.0001)
+    }
+
+    fn get_area(&self) -> f64 {
+        let size = self.max - self.min;
+
+        2.0 * (size.get_x() * size.get_y()
+            + size.get_y() * size.get_z()
+            + size.get_z() * size.get_x())
+    }
+
+    fn sample_surface(&self, rng: &mut dyn RngCore) -> Option<(Vec3, Vec3)> {
+        let size = self.max - self.min;
+        let mut chosen_area = rng.random::<f64>() * self.get_area() / 2.0;
+
+        // a pair of opposite faces (the ones facing `axis`) is chosen as often as big it is
+        let axis = (0..3)
+            .find(|axis| {
+                let face_area = size.get_axis((axis + 1) % 3) * size.get_axis((axis + 2) % 3);
+                chosen_area -= face_area;
+
+                chosen_area < 0.0
+            })
+            .unwrap_or(2);
+
+        let mut point = [0.0; 3];
+        let mut normal = [0.0; 3];
+
+        for (other_axis, coordinate) in point.iter_mut().enumerate() {
+            *coordinate =
+                self.min.get_axis(other_axis) + size.get_axis(other_axis) * rng.random::<f64>();
+        }
+
+        if rng.random::<bool>() {
+            point[axis] = self.min.get_axis(axis);
+            normal[axis] = -1.0;
+        } else {
+            point[axis] = self.max.get_axis(axis);
+            normal[axis] = 1.0;
+        }
+
+        Some((
+            Vec3::new(point[0], point[1], point[2]),
+            Vec3::new(normal[0], normal[1], normal[2]),
+        ))
+    }
+}
@@ -0,0 +1,190 @@
+use std::f64::consts::PI;
+
+use rand::{Rng, RngCore};
+
+use super::{HitRecord, Material, Object};
+use crate::{BoundingBox, Ray, Vec3};
+
+/// object to abstract a capped cylinder in our ray traced world
+///
+/// Trough the use of this object we can create a cylinder standing on the origin along the z
+/// axis (it's placed in the world with an `Instance`) and check if a ray hits it with
+/// `intersect`; the side is UV mapped by the angle around the axis and the height, the caps by
+/// their x and y coordinates
+pub struct Cylinder {
+    radius: f64,
+    height: f64,
+    material: Material,
+}
+
+impl Cylinder {
+    pub fn new(radius: f64, height: f64, material: Material) -> Self {
+        Self {
+            radius,
+            height,
+            material,
+        }
+    }
+}
+
+impl Object for Cylinder {
+    fn intersect(&self, ray: &Ray, min_t: f64, max_t: f64) -> Option<HitRecord> {
+        let (position, direction) = (ray.get_position(), ray.get_direction());
+        let mut closest: Option<HitRecord> = None;
+        let mut closest_t = max_t;
+
+        // the side: x² + y² = radius²
+        let a = direction.get_x().powi(2) + direction.get_y().powi(2);
+        let half_b = position.get_x() * direction.get_x() + position.get_y() * direction.get_y();
+        let c = position.get_x().powi(2) + position.get_y().powi(2) - self.radius * self.radius;
+
+        for t in solve_quadratic(a, half_b, c) {
+            let point = ray.calculate_ray_position(t);
+
+            if t > min_t && t < closest_t && (0.0..=self.height).contains(point.get_z()) {
+                closest_t = t;
+                closest = Some(HitRecord::new(
+                    ray,
+                    t,
+                    Vec3::new(*point.get_x(), *point.get_y(), 0.0),
+                    (get_angle_fraction(&point), point.get_z() / self.height),
+                ));
+            }
+        }
+
+        for (cap_z, normal_z) in [(0.0, -1.0), (self.height, 1.0)] {
+            if let Some(hit) = intersect_cap(ray, cap_z, normal_z, self.radius, min_t, closest_t) {
+                closest_t = hit.get_t();
+                closest = Some(hit);
+            }
+        }
+
+        closest
+    }
+
+    fn get_material(&self) -> &Material {
+        &(self.material)
+    }
+
+    fn get_bounding_box(&self) -> BoundingBox {
+        BoundingBox::new(
+            Vec3::new(-self.radius, -self.radius, 0.0),
+            Vec3::new(self.radius, self.radius, self.height),
+        )
+    }
+
+    fn get_area(&self) -> f64 {
+        2.0 * PI * self.radius * (self.radius + self.height)
+    }
+
+    fn sample_surface(&self, rng: &mut dyn RngCore) -> Option<(Vec3, Vec3)> {
+        let side_area = 2.0 * PI * self.radius * self.height;
+        let chosen_area = rng.random::<f64>() * self.get_area();
+
+        // the side and the caps are chosen as often as big they are
+        if chosen_area < side_area {
+            let angle = 2.0 * PI * rng.random::<f64>();
+            let normal = Vec3::new(angle.cos(), angle.sin(), 0.0);
+            let point =
+                normal * self.radius + Vec3::new(0.0, 0.0, self.height * rng.random::<f64>());
+
+            Some((point, normal))
+        } else if chosen_area < side_area + PI * self.radius * self.radius {
+            Some((
+                sample_ring(rng, 0.0, self.radius, 0.0),
+                Vec3::new(0.0, 0.0, -1.0),
+            ))
+        } else {
+            Some((
+                sample_ring(rng, 0.0, self.radius, self.height),
+                Vec3::new(0.0, 0.0, 1.0),
+            ))
+        }
+    }
+}
+
+/// solves `a t² + 2 half_b t + c = 0` returning the real roots (if any)
+pub(super) fn solve_quadratic(a: f64, half_b: f64, c: f64) -> Vec<f64> {
+    if a.abs() < f64::EPSILON {
+        // the equation is linear
+        if half_b.abs() < f64::EPSILON {
+            return vec![];
+        }
+
+        return vec![-c / (2.0 * half_b)];
+    }
+
+    let discriminant = half_b * half_b - a * c;
+
+    if discriminant < 0.0 {
+        return vec![];
+    }
+
+    let discriminant_sqrt = discriminant.sqrt();
+
+    vec![
+        (-half_b - discriminant_sqrt) / a,
+        (-half_b + discriminant_sqrt) / a,
+    ]
+}
+
+/// retrieves the angle of the point around the z axis as a fraction of a full turn
+pub(super) fn get_angle_fraction(point: &Vec3) -> f64 {
+    (point.get_y().atan2(*point.get_x()) / (2.0 * PI)).rem_euclid(1.0)
+}
+
+/// chooses a point of the ring between the two radii lying on the xy plane raised to `z` (every
+/// part of the ring is as likely as any other)
+pub(super) fn sample_ring(
+    rng: &mut dyn RngCore,
+    inner_radius: f64,
+    outer_radius: f64,
+    z: f64,
+) -> Vec3 {
+    // the area inside a radius grows with its square
+    let inner_squared = inner_radius * inner_radius;
+    let radius = (inner_squared
+        + rng.random::<f64>() * (outer_radius * outer_radius - inner_squared))
+        .sqrt();
+    let angle = 2.0 * PI * rng.random::<f64>();
+
+    Vec3::new(radius * angle.cos(), radius * angle.sin(), z)
+}
+
+/// checks if the ray hits the disk of the given radius lying on the `z = cap_z` plane
+pub(super) fn intersect_cap(
+    ray: &Ray,
+    cap_z: f64,
+    normal_z: f64,
+    radius: f64,
+    min_t: f64,
+    max_t: f64,
+) -> Option<HitRecord> {
+    let direction_z = *ray.get_direction().get_z();
+
+    if direction_z.abs() < f64::EPSILON {
+        return None;
+    }
+
+    let t = (cap_z - ray.get_position().get_z()) / direction_z;
+
+    if t <= min_t || t >= max_t {
+        return None;
+    }
+
+    let point = ray.calculate_ray_position(t);
+
+    if point.get_x().powi(2) + point.get_y().powi(2) > radius * radius {
+        return None;
+    }
+
+    Some(HitRecord::new(
+        ray,
+        t,
+        Vec3::new(0.0, 0.0, normal_z),
+        (
+            (point.get_x() / radius + 1.0) / 2.0,
+            (point.get_y() / radius + 1.0) / 2.0,
+        ),
+    ))
+}
@@ -0,0 +1,88 @@
+use std::f64::consts::PI;
+
+use rand::RngCore;
+
+use super::cylinder::{get_angle_fraction, sample_ring};
+use super::{HitRecord, Material, Object};
+use crate::{BoundingBox, Ray, Vec3};
+
+/// object to abstract a disk or an annulus (a disk with a hole) in our ray traced world
+///
+/// Trough the use of this object we can create a flat ring centered on the origin and lying on
+/// the xy plane, facing the z axis (it's placed in the world with an `Instance`) and check if a
+/// ray hits it with `intersect`; it's UV mapped by the angle around the center and the distance
+/// from the inner radius
+pub struct Disk {
+    inner_radius: f64,
+    outer_radius: f64,
+    material: Material,
+}
+
+impl Disk {
+    /// creates a new annulus, with an `inner_radius` of 0 it's a disk
+    pub fn new(inner_radius: f64, outer_radius: f64, material: Material) -> Self {
+        Self {
+            inner_radius: inner_radius.min(outer_radius),
+            outer_radius,
+            material,
+        }
+    }
+}
+
+impl Object for Disk {
+    fn intersect(&self, ray: &Ray, min_t: f64, max_t: f64) -> Option<HitRecord> {
+        let direction_z = *ray.get_direction().get_z();
+
+        if direction_z.abs() < f64::EPSILON {
+            return None;
+        }
+
+        let t = -ray.get_position().get_z() / direction_z;
+
+        if t <= min_t || t >= max_t {
+            return None;
+        }
+
+        let point = ray.calculate_ray_position(t);
+        let distance = (point.get_x().powi(2) + point.get_y().powi(2)).sqrt();
+
+        if distance < self.inner_radius || distance > self.outer_radius {
+            return None;
+        }
+
+        Some(HitRecord::new(
+            ray,
+            t,
+            Vec3::new(0.0, 0.0, 1.0),
+            (
+                get_angle_fraction(&point),
+                (distance - self.inner_radius)
+                    / (self.outer_radius - self.inner_radius).max(f64::EPSILON),
+            ),
+        ))
+    }
+
+    fn get_material(&self) -> &Material {
+        &(self.material)
+    }
+
+    fn get_bounding_box(&self) -> BoundingBox {
+        // a disk is always flat so we pad its box a little bit
+        BoundingBox::new(
+            Vec3::new(-self.outer_radius, -self.outer_radius, 0.0),
+            Vec3::new(self.outer_radius, self.outer_radius, 0.0),
+        )
+        .pad(0.0001)
+    }
+
+    fn get_area(&self) -> f64 {
+        PI * (self.outer_radius.powi(2) - self.inner_radius.powi(2))
+    }
+
+    fn sample_surface(&self, rng: &mut dyn RngCore) -> Option<(Vec3, Vec3)> {
+        Some((
+            sample_ring(rng, self.inner_radius, self.outer_radius, 0.0),
+            Vec3::new(0.0, 0.0, 1.0),
+        ))
+    }
+}
@@ -0,0 +1,174 @@
+use std::sync::Arc;
+
+use rand::RngCore;
+
+use super::{HitRecord, Interval, Material, Object};
+use crate::{BoundingBox, Ray, Transform, Vec3};
+use canvas::Color;
+
+/// object to abstract a transformed instance of another object in our ray traced world
+///
+/// Trough the use of this object we can move, rotate and scale any object; the wrapped object is
+/// shared so the same object (like a big mesh) can be placed many times without copying it, rays
+/// are brought into the object's own space before checking them against it; the surface of an
+/// instance is sampled (as a light) like the object's one, so where the transform stretches the
+/// surface more its points are less likely (see `Transform::get_area_stretch`)
+///
+/// # Examples
+///
+/// Place the same sphere twice, the second time stretched into an ellipsoid
+///
+/// ```no_run
+/// # use std::sync::Arc;
+/// # use world::{MaterialBuilder, Objects, Transform, Vec3};
+/// let sphere: Arc<dyn world::Object> = Arc::from(Objects::create_object(Objects::Sphere(
+///     Vec3::new(0.0, 0.0, 0.0),
+///     1.0,
+///     MaterialBuilder::default().build(),
+/// )));
+///
+/// let first = Objects::create_object(Objects::Instance(
+///     Arc::clone(&sphere),
+///     Transform::translate(Vec3::new(-2.0, 0.0, 5.0)),
+/// ));
+/// let second = Objects::create_object(Objects::Instance(
+///     sphere,
+///     Transform::scale(Vec3::new(1.0, 2.0, 1.0)).then(&Transform::translate(Vec3::new(
+///         2.0, 0.0, 5.0,
+///     ))),
+/// ));
+/// ```
+pub struct Instance {
+    object: Arc<dyn Object>,
+    transform: Transform,
+    bounding_box: BoundingBox,
+}
+
+impl Instance {
+    /// creates a new instance of the object, `transform` brings the object's space into the
+    /// world's one
+    pub fn new(object: Arc<dyn Object>, transform: Transform) -> Self {
+        let bounding_box = transform.transform_bounding_box(&object.get_bounding_box());
+
+        Self {
+            object,
+            transform,
+            bounding_box,
+        }
+    }
+}
+
+impl Instance {
+    /// brings the ray into the object's space, returning the object's ray and how much longer
+    /// its `t` is than the world's one (the object's ray has a unit direction too, so its `t` is
+    /// scaled by how much the transform stretches the direction)
+    fn to_object_ray(&self, ray: &Ray) -> Option<(Ray, f64)> {
+        let inverse = self.transform.get_inverse();
+        let object_direction = inverse.transform_vector(ray.get_direction());
+        let scale = object_direction.get_length();
+
+        if scale == 0.0 || !scale.is_finite() {
+            return None;
+        }
+
+        Some((
+            Ray::new(
+                inverse.transform_point(ray.get_position()),
+                object_direction,
+            ),
+            scale,
+        ))
+    }
+
+    /// brings a hit of the object's ray back into the world's space
+    fn to_world_hit(&self, ray: &Ray, object_hit: &HitRecord, scale: f64) -> HitRecord {
+        let mut hit = HitRecord::new(
+            ray,
+            object_hit.get_t() / scale,
+            self.transform
+                .transform_normal(object_hit.get_geometric_normal()),
+            object_hit.get_uv(),
+        );
+        hit.set_shading_normal(
+            self.transform
+                .transform_normal(object_hit.get_shading_normal()),
+        );
+
+        // the tangents lie on the surface so they move like any other vector
+        let (tangent, bitangent) = object_hit.get_tangents();
+        hit.set_tangents(
+            self.transform.transform_vector(&tangent),
+            self.transform.transform_vector(&bitangent),
+        );
+
+        hit
+    }
+}
+
+impl Object for Instance {
+    fn intersect(&self, ray: &Ray, min_t: f64, max_t: f64) -> Option<HitRecord> {
+        let (object_ray, scale) = self.to_object_ray(ray)?;
+        let object_hit = self
+            .object
+            .intersect(&object_ray, min_t * scale, max_t * scale)?;
+
+        let hit = self.to_world_hit(ray, &object_hit, scale);
+
+        if hit.get_t() <= min_t || hit.get_t() >= max_t {
+            return None;
+        }
+
+        Some(hit)
+    }
+
+    fn get_intervals(&self, ray: &Ray) -> Vec<Interval> {
+        let Some((object_ray, scale)) = self.to_object_ray(ray) else {
+            return vec![];
+        };
+
+        self.object
+            .get_intervals(&object_ray)
+            .iter()
+            .map(|interval| {
+                Interval::new(
+                    self.to_world_hit(ray, interval.get_entry(), scale),
+                    self.to_world_hit(ray, interval.get_exit(), scale),
+                )
+            })
+            .collect()
+    }
+
+    fn get_material(&self) -> &Material {
+        self.object.get_material()
+    }
+
+    fn get_color(&self, hit: &HitRecord) -> Color {
+        self.object.get_color(hit)
+    }
+
+    fn get_bounding_box(&self) -> BoundingBox {
+        self.bounding_box
+    }
+
+    fn get_area(&self) -> f64 {
+        self.object.get_area() * self.transform.get_area_scale()
+    }
+
+    fn sample_surface(&self, rng: &mut dyn RngCore) -> Option<(Vec3, Vec3)> {
+        let (point, normal) = self.object.sample_surface(rng)?;
+        let mut normal = self.transform.transform_normal(&normal);
+        normal.make_unit();
+
+        Some((self.transform.transform_point(&point), normal))
+    }
+
+    fn get_surface_pdf(&self, point: &Vec3, normal: &Vec3) -> f64 {
+        let inverse = self.transform.get_inverse();
+        let mut object_normal = inverse.transform_normal(normal);
+        object_normal.make_unit();
+
+        self.object
+            .get_surface_pdf(&inverse.transform_point(point), &object_normal)
+            / self.transform.get_area_stretch(&object_normal)
+    }
+}
@@ -0,0 +1,228 @@
+use std::sync::Arc;
+
+use rand::{Rng, RngCore};
+
+use super::obj_loader::{self, ObjError};
+use super::triangle::{
+    create_triangle_hit, get_triangle_area, intersect_triangle, sample_triangle,
+};
+use super::{HitRecord, Material, Object};
+use crate::{BoundingBox, Bvh, Ray, Vec3};
+
+/// the vertex data of a mesh, it's shared between every mesh (group) loaded from the same file so
+/// that vertices are stored only once
+#[derive(Debug, Default)]
+pub(super) struct MeshData {
+    pub positions: Vec<Vec3>,
+    pub normals: Vec<Vec3>,
+    pub texture_coordinates: Vec<(f64, f64)>,
+}
+
+/// a corner of a mesh's triangle, made of indices into the mesh's `MeshData`
+#[derive(Debug, Clone, Copy)]
+pub(super) struct MeshVertex {
+    pub position: usize,
+    pub texture_coordinate: Option<usize>,
+    pub normal: Option<usize>,
+}
+
+/// object to abstract a triangle mesh in our ray traced world
+///
+/// Trough the use of this object we can load a Wavefront OBJ file and render it; the mesh keeps
+/// its own bounding volume hierarchy over its triangles so it's checked as a single object by the
+/// scene
+///
+/// # Examples
+///
+/// Load every group of an OBJ file as a separate mesh
+///
+/// ```no_run
+/// # use world::{MaterialBuilder, Mesh};
+/// let meshes = Mesh::load_obj_groups("./models/teapot.obj", MaterialBuilder::default().build());
+///
+/// match meshes {
+///     Ok(meshes) => {
+///         for mesh in meshes {
+///             println!("{}: {} triangles", mesh.get_name(), mesh.get_triangle_count());
+///         }
+///     }
+///     Err(error) => println!("{}", error),
+/// }
+/// ```
+pub struct Mesh {
+    name: String,
+    data: Arc<MeshData>,
+    triangles: Vec<[MeshVertex; 3]>,
+    bvh: Bvh,
+    bounding_box: BoundingBox,
+    // the sum of the areas of every triangle up to (and including) the index, used to choose
+    // the triangles by their area
+    cumulative_areas: Vec<f64>,
+    material: Material,
+}
+
+impl Mesh {
+    /// creates a new mesh from the given triangles (their vertices point into `data`)
+    pub(super) fn new(
+        name: String,
+        data: Arc<MeshData>,
+        triangles: Vec<[MeshVertex; 3]>,
+        material: Material,
+    ) -> Self {
+        let triangle_boxes: Vec<BoundingBox> = triangles
+            .iter()
+            .map(|triangle| {
+                BoundingBox::from_points(&triangle.map(|vertex| data.positions[vertex.position]))
+                    .pad(0.0001)
+            })
+            .collect();
+
+        let bounding_box = triangle_boxes
+            .iter()
+            .fold(BoundingBox::empty(), |mesh_box, triangle_box| {
+                mesh_box.union(triangle_box)
+            });
+
+        let cumulative_areas = triangles
+            .iter()
+            .scan(0.0, |total_area, triangle| {
+                *total_area +=
+                    get_triangle_area(&triangle.map(|vertex| data.positions[vertex.position]));
+
+                Some(*total_area)
+            })
+            .collect();
+
+        Self {
+            name,
+            bvh: Bvh::new(&triangle_boxes),
+            cumulative_areas,
+            data,
+            triangles,
+            bounding_box,
+            material,
+        }
+    }
+
+    /// loads every face of a Wavefront OBJ file into a single mesh
+    pub fn load_obj(obj_path: &str, material: Material) -> Result<Self, ObjError> {
+        let (data, groups) = obj_loader::load(obj_path)?;
+        let triangles = groups
+            .into_iter()
+            .flat_map(|(_, triangles)| triangles)
+            .collect();
+
+        Ok(Self::new(
+            String::from(obj_path),
+            Arc::new(data),
+            triangles,
+            material,
+        ))
+    }
+
+    /// loads a Wavefront OBJ file creating a mesh for every group (`g` or `o` statement), all of
+    /// the meshes share the same vertices
+    pub fn load_obj_groups(obj_path: &str, material: Material) -> Result<Vec<Self>, ObjError> {
+        let (data, groups) = obj_loader::load(obj_path)?;
+        let data = Arc::new(data);
+
+        Ok(groups
+            .into_iter()
+            .map(|(name, triangles)| {
+                Self::new(name, Arc::clone(&data), triangles, material.clone())
+            })
+            .collect())
+    }
+
+    /// retrieves the mesh's name (the group name or the file path)
+    pub fn get_name(&self) -> &str {
+        &self.name
+    }
+
+    /// retrieves how many triangles the mesh is made of
+    pub fn get_triangle_count(&self) -> usize {
+        self.triangles.len()
+    }
+
+    /// retrieves the positions of the triangle's vertices
+    fn get_triangle_positions(&self, triangle: usize) -> [Vec3; 3] {
+        self.triangles[triangle].map(|vertex| self.data.positions[vertex.position])
+    }
+}
+
+impl Object for Mesh {
+    fn intersect(&self, ray: &Ray, min_t: f64, max_t: f64) -> Option<HitRecord> {
+        let mut closest: Option<(f64, f64, f64)> = None;
+
+        let (triangle, _) =
+            self.bvh
+                .find_closest(ray, min_t, max_t, |triangle, min_t, max_t| {
+                    let [vertice_1, vertice_2, vertice_3] = self.get_triangle_positions(triangle);
+                    let (t, u, v) = intersect_triangle(&vertice_1, &vertice_2, &vertice_3, ray)?;
+
+                    // the hierarchy keeps only the hits closer than the closest one found
+                    if t <= min_t || t >= max_t {
+                        return None;
+                    }
+
+                    closest = Some((t, u, v));
+
+                    Some(t)
+                })?;
+
+        let (t, u, v) = closest?;
+        let vertices = &self.triangles[triangle];
+
+        // the normals and texture coordinates are interpolated only when every vertex has them
+        let normals = match vertices.map(|vertex| vertex.normal) {
+            [Some(normal_1), Some(normal_2), Some(normal_3)] => {
+                Some([normal_1, normal_2, normal_3].map(|normal| self.data.normals[normal]))
+            }
+            _ => None,
+        };
+        let texture_coordinates = match vertices.map(|vertex| vertex.texture_coordinate) {
+            [Some(uv_1), Some(uv_2), Some(uv_3)] => {
+                Some([uv_1, uv_2, uv_3].map(|uv| self.data.texture_coordinates[uv]))
+            }
+            _ => None,
+        };
+
+        Some(create_triangle_hit(
+            ray,
+            t,
+            (u, v),
+            self.get_triangle_positions(triangle),
+            normals,
+            texture_coordinates,
+        ))
+    }
+
+    fn get_material(&self) -> &Material {
+        &(self.material)
+    }
+
+    fn get_bounding_box(&self) -> BoundingBox {
+        self.bounding_box
+    }
+
+    fn get_area(&self) -> f64 {
+        self.cumulative_areas.last().copied().unwrap_or(0.0)
+    }
+
+    fn sample_surface(&self, rng: &mut dyn RngCore) -> Option<(Vec3, Vec3)> {
+        let area = self.get_area();
+
+        if area <= 0.0 {
+            return None;
+        }
+
+        // bigger triangles are chosen more often
+        let chosen_area = rng.random::<f64>() * area;
+        let triangle = self
+            .cumulative_areas
+            .partition_point(|total_area| *total_area <= chosen_area)
+            .min(self.triangles.len() - 1);
+
+        Some(sample_triangle(&self.get_triangle_positions(triangle), rng))
+    }
+}
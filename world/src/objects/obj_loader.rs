@@ -0,0 +1,395 @@
+use std::fmt;
+use std::fs::File;
+use std::io::{self, BufRead, BufReader};
+
+use super::mesh::{MeshData, MeshVertex};
+use crate::Vec3;
+
+/// the name given to the faces that come before any `g` or `o` statement
+const DEFAULT_GROUP_NAME: &str = "default";
+
+/// An error that can happen while loading a Wavefront OBJ file
+#[derive(Debug)]
+pub enum ObjError {
+    /// the file couldn't be opened or read
+    Io(io::Error),
+    /// a line of the file is malformed (`line` starts from 1)
+    Parse { line: usize, message: String },
+}
+
+impl fmt::Display for ObjError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ObjError::Io(error) => write!(f, "couldn't read the OBJ file: {}", error),
+            ObjError::Parse { line, message } => write!(f, "line {}: {}", line, message),
+        }
+    }
+}
+
+impl std::error::Error for ObjError {}
+
+impl From<io::Error> for ObjError {
+    fn from(error: io::Error) -> Self {
+        ObjError::Io(error)
+    }
+}
+
+/// the triangles of every group of the file, in the order the groups appear
+type ObjGroups = Vec<(String, Vec<[MeshVertex; 3]>)>;
+
+/// loads the OBJ file at the given path returning its vertices and the triangles of every group
+pub(super) fn load(obj_path: &str) -> Result<(MeshData, ObjGroups), ObjError> {
+    parse(BufReader::new(File::open(obj_path)?))
+}
+
+/// parses an OBJ file, polygons with more than three vertices are split into triangles
+fn parse<R: BufRead>(reader: R) -> Result<(MeshData, ObjGroups), ObjError> {
+    let mut data = MeshData::default();
+    let mut groups: ObjGroups = vec![];
+    let mut current_group = String::from(DEFAULT_GROUP_NAME);
+    let mut current_triangles: Vec<[MeshVertex; 3]> = vec![];
+
+    for (index, line) in reader.lines().enumerate() {
+        let line_number = index + 1;
+        let line = line?;
+        // everything after a '#' is a comment
+        let line = line.split('#').next().unwrap_or_default();
+        let mut tokens = line.split_whitespace();
+
+        let Some(statement) = tokens.next() else {
+            continue;
+        };
+        let arguments: Vec<&str> = tokens.collect();
+
+        match statement {
+            "v" => {
+                let [x, y, z] = parse_floats::<3>(&arguments, line_number, "vertex", 3..=4)?;
+                data.positions.push(Vec3::new(x, y, z));
+            }
+            "vn" => {
+                let [x, y, z] = parse_floats::<3>(&arguments, line_number, "normal", 3..=3)?;
+                let mut normal = Vec3::new(x, y, z);
+
+                if normal.get_length() == 0.0 {
+                    return Err(parse_error(
+                        line_number,
+                        "a normal can't have a zero length",
+                    ));
+                }
+
+                data.normals.push(*normal.make_unit());
+            }
+            "vt" => {
+                let [u, v] =
+                    parse_floats::<2>(&arguments, line_number, "texture coordinate", 1..=3)?;
+                data.texture_coordinates.push((u, v));
+            }
+            "f" => {
+                if arguments.len() < 3 {
+                    return Err(parse_error(
+                        line_number,
+                        &format!(
+                            "a face needs at least 3 vertices, {} given",
+                            arguments.len()
+                        ),
+                    ));
+                }
+
+                let vertices = arguments
+                    .iter()
+                    .map(|vertex| parse_face_vertex(vertex, &data, line_number))
+                    .collect::<Result<Vec<MeshVertex>, ObjError>>()?;
+
+                // polygons are split in a fan of triangles around their first vertex
+                for i in 1..(vertices.len() - 1) {
+                    current_triangles.push([vertices[0], vertices[i], vertices[i + 1]]);
+                }
+            }
+            "g" | "o" => {
+                let name = if arguments.is_empty() {
+                    String::from(DEFAULT_GROUP_NAME)
+                } else {
+                    arguments.join(" ")
+                };
+
+                if !current_triangles.is_empty() {
+                    groups.push((current_group, std::mem::take(&mut current_triangles)));
+                }
+
+                current_group = name;
+            }
+            // valid statements we don't support (materials, smoothing groups, lines, points and
+            // free-form geometry) are ignored
+            "mtllib" | "usemtl" | "s" | "l" | "p" | "mg" | "vp" | "cstype" | "deg" | "bmat"
+            | "step" | "curv" | "curv2" | "surf" | "parm" | "trim" | "hole" | "scrv" | "sp"
+            | "end" | "con" | "lod" | "shadow_obj" | "trace_obj" | "ctech" | "stech" => {}
+            _ => {
+                return Err(parse_error(
+                    line_number,
+                    &format!("unknown statement '{}'", statement),
+                ));
+            }
+        }
+    }
+
+    if !current_triangles.is_empty() {
+        groups.push((current_group, current_triangles));
+    }
+
+    Ok((data, groups))
+}
+
+/// creates a parse error for the given line
+fn parse_error(line: usize, message: &str) -> ObjError {
+    ObjError::Parse {
+        line,
+        message: String::from(message),
+    }
+}
+
+/// parses the arguments of a `v`, `vn` or `vt` statement, `expected` is the allowed number of
+/// arguments and the first `N` of them are returned (missing ones are 0)
+fn parse_floats<const N: usize>(
+    arguments: &[&str],
+    line: usize,
+    what: &str,
+    expected: std::ops::RangeInclusive<usize>,
+) -> Result<[f64; N], ObjError> {
+    if !expected.contains(&arguments.len()) {
+        return Err(parse_error(
+            line,
+            &format!(
+                "a {} needs between {} and {} values, {} given",
+                what,
+                expected.start(),
+                expected.end(),
+                arguments.len()
+            ),
+        ));
+    }
+
+    let mut values = [0.0; N];
+
+    for (value, argument) in values.iter_mut().zip(arguments) {
+        *value = argument.parse::<f64>().map_err(|_| {
+            parse_error(
+                line,
+                &format!("'{}' is not a valid number for a {}", argument, what),
+            )
+        })?;
+    }
+
+    Ok(values)
+}
+
+/// parses a face's vertex (`v`, `v/vt`, `v//vn` or `v/vt/vn`)
+fn parse_face_vertex(vertex: &str, data: &MeshData, line: usize) -> Result<MeshVertex, ObjError> {
+    let mut parts = vertex.split('/');
+
+    let position = match parts.next() {
+        Some(position) if !position.is_empty() => {
+            resolve_index(position, data.positions.len(), "vertex", line)?
+        }
+        _ => {
+            return Err(parse_error(
+                line,
+                &format!("the face vertex '{}' has no position", vertex),
+            ));
+        }
+    };
+
+    let texture_coordinate = match parts.next() {
+        Some(uv) if !uv.is_empty() => Some(resolve_index(
+            uv,
+            data.texture_coordinates.len(),
+            "texture coordinate",
+            line,
+        )?),
+        _ => None,
+    };
+
+    let normal = match parts.next() {
+        Some(normal) if !normal.is_empty() => {
+            Some(resolve_index(normal, data.normals.len(), "normal", line)?)
+        }
+        _ => None,
+    };
+
+    if parts.next().is_some() {
+        return Err(parse_error(
+            line,
+            &format!("the face vertex '{}' has too many '/'", vertex),
+        ));
+    }
+
+    Ok(MeshVertex {
+        position,
+        texture_coordinate,
+        normal,
+    })
+}
+
+/// converts an OBJ index (starting from 1, or negative to count backwards from the last element
+/// defined) into an index of our vectors
+fn resolve_index(index: &str, count: usize, what: &str, line: usize) -> Result<usize, ObjError> {
+    let parsed = index
+        .parse::<i64>()
+        .map_err(|_| parse_error(line, &format!("'{}' is not a valid {} index", index, what)))?;
+
+    let resolved = if parsed > 0 {
+        parsed - 1
+    } else {
+        count as i64 + parsed
+    };
+
+    if parsed == 0 || resolved < 0 || resolved >= count as i64 {
+        return Err(parse_error(
+            line,
+            &format!(
+                "the {} index {} is out of range ({} defined so far)",
+                what, parsed, count
+            ),
+        ));
+    }
+
+    Ok(resolved as usize)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// mesh data with three positions, two texture coordinates and one normal
+    fn data() -> MeshData {
+        MeshData {
+            positions: vec![Vec3::new(0.0, 0.0, 0.0); 3],
+            normals: vec![Vec3::new(0.0, 0.0, 1.0)],
+            texture_coordinates: vec![(0.0, 0.0); 2],
+        }
+    }
+
+    /// retrieves the line of the parse error of the source
+    fn parse_error_line(source: &str) -> usize {
+        match parse(source.as_bytes()) {
+            Err(ObjError::Parse { line, .. }) => line,
+            Err(error) => panic!("expected a parse error, found {}", error),
+            Ok(_) => panic!("the source should not parse"),
+        }
+    }
+
+    /// retrieves the position indices of every triangle of every group
+    fn triangle_positions(source: &str) -> Vec<(String, Vec<[usize; 3]>)> {
+        let (_, groups) = parse(source.as_bytes()).expect("the source should parse");
+
+        groups
+            .into_iter()
+            .map(|(name, triangles)| {
+                let positions = triangles
+                    .iter()
+                    .map(|triangle| triangle.map(|vertex| vertex.position))
+                    .collect();
+
+                (name, positions)
+            })
+            .collect()
+    }
+
+    #[test]
+    fn resolves_positive_and_negative_indices() {
+        assert_eq!(resolve_index("1", 3, "vertex", 1).unwrap(), 0);
+        assert_eq!(resolve_index("3", 3, "vertex", 1).unwrap(), 2);
+        assert_eq!(resolve_index("-1", 3, "vertex", 1).unwrap(), 2);
+        assert_eq!(resolve_index("-3", 3, "vertex", 1).unwrap(), 0);
+    }
+
+    #[test]
+    fn rejects_zero_and_out_of_range_indices() {
+        for index in ["0", "4", "-4", "x", ""] {
+            assert!(
+                matches!(
+                    resolve_index(index, 3, "vertex", 7),
+                    Err(ObjError::Parse { line: 7, .. })
+                ),
+                "the index '{}' should be rejected",
+                index
+            );
+        }
+    }
+
+    #[test]
+    fn parses_every_face_vertex_format() {
+        let data = data();
+
+        let vertex = parse_face_vertex("2", &data, 1).unwrap();
+        assert_eq!(
+            (vertex.position, vertex.texture_coordinate, vertex.normal),
+            (1, None, None)
+        );
+
+        let vertex = parse_face_vertex("2/2", &data, 1).unwrap();
+        assert_eq!(
+            (vertex.position, vertex.texture_coordinate, vertex.normal),
+            (1, Some(1), None)
+        );
+
+        let vertex = parse_face_vertex("3//1", &data, 1).unwrap();
+        assert_eq!(
+            (vertex.position, vertex.texture_coordinate, vertex.normal),
+            (2, None, Some(0))
+        );
+
+        let vertex = parse_face_vertex("-1/-2/-1", &data, 1).unwrap();
+        assert_eq!(
+            (vertex.position, vertex.texture_coordinate, vertex.normal),
+            (2, Some(0), Some(0))
+        );
+    }
+
+    #[test]
+    fn rejects_malformed_face_vertices() {
+        let data = data();
+
+        for vertex in ["1/1/1/1", "1//1/", "/1", "//1", "1/3", "1//2"] {
+            assert!(
+                parse_face_vertex(vertex, &data, 1).is_err(),
+                "the face vertex '{}' should be rejected",
+                vertex
+            );
+        }
+    }
+
+    #[test]
+    fn splits_polygons_in_a_fan() {
+        let source = "v 0 0 0\nv 1 0 0\nv 1 1 0\nv 0 1 0\nv -1 1 0\nf 1 2 3 4 5\n";
+
+        assert_eq!(
+            triangle_positions(source),
+            vec![(
+                String::from(DEFAULT_GROUP_NAME),
+                vec![[0, 1, 2], [0, 2, 3], [0, 3, 4]]
+            )]
+        );
+    }
+
+    #[test]
+    fn keeps_the_triangles_of_every_group() {
+        let source = "v 0 0 0\nv 1 0 0\nv 1 1 0\nf 1 2 3\ng top # a comment\nf -3 -2 -1\n";
+
+        assert_eq!(
+            triangle_positions(source),
+            vec![
+                (String::from(DEFAULT_GROUP_NAME), vec![[0, 1, 2]]),
+                (String::from("top"), vec![[0, 1, 2]]),
+            ]
+        );
+    }
+
+    #[test]
+    fn reports_the_line_of_errors() {
+        assert_eq!(parse_error_line("v 0 0 0\nv 1 0 0\nf 1 2\n"), 3);
+        assert_eq!(parse_error_line("v 0 0 0\n\nf 1 1 2\n"), 3);
+        assert_eq!(parse_error_line("# comment\nvn 0 0 0\n"), 2);
+        assert_eq!(parse_error_line("v 0 zero 0\n"), 1);
+        assert_eq!(parse_error_line("v 0 0 0\nfoo 1\n"), 2);
+    }
+}
@@ -1,11 +1,12 @@
-use crate::objects::{Material, Object};
-use crate::{Ray, Vec3};
-use canvas::RGB;
+use rand::{Rng, RngCore};
+
+use crate::objects::{HitRecord, Material, Object};
+use crate::{BoundingBox, Ray, Vec3};
 
 /// object to abstract a panel in our ray traced world
 ///
 /// Trough the use of this object we can create a panel in our world and check if a ray hits it
-/// with `is_object_hit`
+/// with `intersect`
 pub struct Panel {
     panel_origin: Vec3,
     u: Vec3,
@@ -23,20 +24,12 @@ impl Panel {
         material: Material,
     ) -> Self {
         panel_normal.make_unit();
-        // these vectors are necessary to establish u and v
-        let mut u = *panel_origin.cross_product(&panel_normal).make_unit();
-        let mut v = panel_normal.cross_product(&u);
-
-        // if the scalar_vectors are impossible then they are "normal"
-        if u.get_x().is_nan() && u.get_y().is_nan() && u.get_z().is_nan()
-            || v.get_x().is_nan() && v.get_y().is_nan() && v.get_z().is_nan()
-        {
-            u = Vec3::new(1.0, 0.0, 0.0);
-            v = Vec3::new(0.0, 1.0, 0.0);
-        }
+        // u and v are half of the panel's sides, u x v points along the normal
+        let u = panel_normal.get_perpendicular();
+        let v = panel_normal.cross_product(&u);
 
-        u = u * (panel_width / 2.0);
-        v = v * (panel_height / 2.0);
+        let u = u * (panel_width / 2.0);
+        let v = v * (panel_height / 2.0);
 
         Self {
             panel_origin,
@@ -57,7 +50,7 @@ impl Panel {
 }
 
 impl Object for Panel {
-    fn is_object_hit(&self, ray: &Ray) -> Option<f64> {
+    fn intersect(&self, ray: &Ray, min_t: f64, max_t: f64) -> Option<HitRecord> {
         let u_v_cross = self.u.cross_product(&self.v);
         let discriminant = ray.get_direction().get_inverse().dot_product(&u_v_cross);
 
@@ -67,6 +60,10 @@ impl Object for Panel {
 
         let t = u_v_cross.dot_product(&((*ray.get_position()) - self.panel_origin)) / discriminant;
 
+        if t <= min_t || t >= max_t {
+            return None;
+        }
+
         // u and v are calculated to check if the point lies inside or outside the plane
         let u_scalar = self
             .v
@@ -83,18 +80,95 @@ impl Object for Panel {
             return None;
         }
 
-        Some(t)
-    }
+        // the u and v scalars go from -1 to 1 across the panel
+        let mut hit = HitRecord::new(
+            ray,
+            t,
+            self.normal,
+            ((u_scalar + 1.0) / 2.0, (v_scalar + 1.0) / 2.0),
+        );
+        hit.set_tangents(self.u, self.v);
 
-    fn get_normal(&self, _point: Vec3) -> Option<Vec3> {
-        Some(self.normal)
+        Some(hit)
     }
 
     fn get_material(&self) -> &Material {
         &(self.material)
     }
 
-    fn get_color(&self, _point: Vec3) -> RGB {
-        *self.get_material().get_color()
+    fn get_bounding_box(&self) -> BoundingBox {
+        let u = self.get_u();
+        let v = self.get_v();
+
+        // a panel is always flat so we pad its box a little bit
+        BoundingBox::from_points(&[
+            self.panel_origin + u + v,
+            self.panel_origin + u - v,
+            self.panel_origin - u + v,
+            self.panel_origin - u - v,
+        ])
+        .pad(0.0001)
+    }
+
+    fn get_area(&self) -> f64 {
+        // u and v are half of the panel's sides
+        4.0 * self.u.get_length() * self.v.get_length()
+    }
+
+    fn sample_surface(&self, rng: &mut dyn RngCore) -> Option<(Vec3, Vec3)> {
+        let point = self.panel_origin
+            + self.u * rng.random_range(-1.0..1.0)
+            + self.v * rng.random_range(-1.0..1.0);
+
+        Some((point, self.normal))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::MaterialBuilder;
+
+    fn panel(origin: Vec3, normal: Vec3) -> Panel {
+        Panel::new(origin, 2.0, 4.0, normal, MaterialBuilder::default().build())
+    }
+
+    #[test]
+    fn sides_are_perpendicular_to_the_normal() {
+        // the origin is on the line of the normal (or is the origin) in most of these panels
+        for (origin, normal) in [
+            (Vec3::new(0.0, 0.0, 0.0), Vec3::new(0.0, 0.0, 1.0)),
+            (Vec3::new(0.0, -4.0, 0.0), Vec3::new(0.0, 2.0, 0.0)),
+            (Vec3::new(3.0, 0.0, 0.0), Vec3::new(-1.0, 0.0, 0.0)),
+            (Vec3::new(1.0, 2.0, 3.0), Vec3::new(1.0, 1.0, 0.0)),
+        ] {
+            let panel = panel(origin, normal);
+            let (u, v) = (panel.get_u(), panel.get_v());
+
+            assert!((u.get_length() - 1.0).abs() < 1e-9);
+            assert!((v.get_length() - 2.0).abs() < 1e-9);
+            assert!(u.dot_product(&v).abs() < 1e-9);
+            assert!(u.dot_product(&panel.normal).abs() < 1e-9);
+            assert!(u.cross_product(&v).dot_product(&panel.normal) > 0.0);
+            assert!((panel.get_area() - 8.0).abs() < 1e-9);
+        }
+    }
+
+    #[test]
+    fn panel_at_the_origin_is_hit() {
+        let panel = panel(Vec3::new(0.0, 0.0, 0.0), Vec3::new(0.0, 0.0, -1.0));
+        let ray = Ray::new(Vec3::new(0.5, 0.5, -3.0), Vec3::new(0.0, 0.0, 1.0));
+        let hit = panel
+            .intersect(&ray, 0.0, f64::MAX)
+            .expect("the ray should hit the panel");
+
+        assert!((hit.get_t() - 3.0).abs() < 1e-9);
+        assert!(hit.is_front_face());
+
+        // the corners are just outside of the panel's reach
+        let corner = panel.get_u() * 1.01 + panel.get_v() * 1.01;
+        let ray = Ray::new(corner + Vec3::new(0.0, 0.0, -3.0), Vec3::new(0.0, 0.0, 1.0));
+
+        assert!(panel.intersect(&ray, 0.0, f64::MAX).is_none());
     }
 }
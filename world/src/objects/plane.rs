@@ -0,0 +1,72 @@
+use super::{HitRecord, Material, Object};
+use crate::{BoundingBox, Ray, Vec3};
+
+/// object to abstract an infinite plane in our ray traced world
+///
+/// Trough the use of this object we can create a floor or a backdrop reaching the horizon and
+/// check if a ray hits it with `intersect`; the plane is tiled by 1x1 squares, every square maps
+/// the whole [0, 1] UV range. Since the plane has no bounds it's kept outside of the scene's
+/// bounding volume hierarchy
+pub struct Plane {
+    point: Vec3,
+    normal: Vec3,
+    u_axis: Vec3,
+    v_axis: Vec3,
+    material: Material,
+}
+
+impl Plane {
+    /// creates a new plane passing trough `point`
+    pub fn new(point: Vec3, mut normal: Vec3, material: Material) -> Self {
+        normal.make_unit();
+
+        let u_axis = normal.get_perpendicular();
+        let v_axis = normal.cross_product(&u_axis);
+
+        Self {
+            point,
+            normal,
+            u_axis,
+            v_axis,
+            material,
+        }
+    }
+}
+
+impl Object for Plane {
+    fn intersect(&self, ray: &Ray, min_t: f64, max_t: f64) -> Option<HitRecord> {
+        let denominator = self.normal.dot_product(ray.get_direction());
+
+        if denominator.abs() < f64::EPSILON {
+            return None;
+        }
+
+        let t = self
+            .normal
+            .dot_product(&(self.point - (*ray.get_position())))
+            / denominator;
+
+        if t <= min_t || t >= max_t {
+            return None;
+        }
+
+        let offset = ray.calculate_ray_position(t) - self.point;
+        let uv = (
+            offset.dot_product(&self.u_axis).rem_euclid(1.0),
+            offset.dot_product(&self.v_axis).rem_euclid(1.0),
+        );
+
+        let mut hit = HitRecord::new(ray, t, self.normal, uv);
+        hit.set_tangents(self.u_axis, self.v_axis);
+
+        Some(hit)
+    }
+
+    fn get_material(&self) -> &Material {
+        &(self.material)
+    }
+
+    fn get_bounding_box(&self) -> BoundingBox {
+        BoundingBox::infinite()
+    }
+}
@@ -0,0 +1,152 @@
+use std::f64::consts::PI;
+use std::sync::Arc;
+
+use super::{HitRecord, Material, Object};
+use crate::{BoundingBox, DistanceField, Ray, Vec3};
+
+/// the maximum number of steps taken along a ray before giving up
+const MAX_STEPS: usize = 512;
+/// the distance from the surface at which a point is considered on it
+const HIT_DISTANCE: f64 = 1e-4;
+/// the farthest a ray is traced trough an unbounded distance field
+const MAX_DISTANCE: f64 = 1e4;
+/// the offset used to estimate the gradient of the distance field
+const GRADIENT_OFFSET: f64 = 1e-5;
+
+/// object to abstract a surface described by a signed distance field in our ray traced world
+///
+/// Trough the use of this object we can render implicit shapes (see [`crate::DistanceFields`])
+/// that have no closed form intersection, the ray is sphere traced trough the field: every step
+/// moves it forward by the distance from the surface until it gets close enough to it; the
+/// normals are given by the gradient of the field and the UVs by the normal like on a sphere
+///
+/// # Examples
+///
+/// Blend two spheres into a blob
+///
+/// ```no_run
+/// # use std::sync::Arc;
+/// # use world::{
+/// #     CsgOperation, DistanceField, DistanceFields, MaterialBuilder, Objects, Transform, Vec3,
+/// # };
+/// let sphere: Arc<dyn DistanceField> =
+///     Arc::from(DistanceFields::create_distance_field(DistanceFields::Sphere(1.0)));
+/// let moved_sphere = DistanceFields::create_distance_field(DistanceFields::Transformed(
+///     sphere.clone(),
+///     Transform::translate(Vec3::new(1.2, 0.0, 0.0)),
+/// ));
+///
+/// let blob = DistanceFields::create_distance_field(DistanceFields::SmoothCombine(
+///     CsgOperation::Union,
+///     sphere,
+///     Arc::from(moved_sphere),
+///     0.5,
+/// ));
+///
+/// let object = Objects::create_object(Objects::Sdf(
+///     Arc::from(blob),
+///     MaterialBuilder::default().build(),
+/// ));
+/// ```
+pub struct Sdf {
+    field: Arc<dyn DistanceField>,
+    bounding_box: BoundingBox,
+    lipschitz_bound: f64,
+    material: Material,
+}
+
+impl Sdf {
+    pub fn new(field: Arc<dyn DistanceField>, material: Material) -> Self {
+        let bounding_box = field.get_bounding_box();
+        let lipschitz_bound = field.get_lipschitz_bound().max(1.0);
+
+        Self {
+            field,
+            bounding_box,
+            lipschitz_bound,
+            material,
+        }
+    }
+
+    /// estimates the (not normalized) gradient of the field at the point with the tetrahedron
+    /// technique
+    fn get_gradient(&self, point: &Vec3) -> Vec3 {
+        [
+            Vec3::new(1.0, -1.0, -1.0),
+            Vec3::new(-1.0, -1.0, 1.0),
+            Vec3::new(-1.0, 1.0, -1.0),
+            Vec3::new(1.0, 1.0, 1.0),
+        ]
+        .into_iter()
+        .fold(Vec3::new(0.0, 0.0, 0.0), |gradient, offset| {
+            gradient
+                + offset
+                    * self
+                        .field
+                        .get_distance(&((*point) + offset * GRADIENT_OFFSET))
+        })
+    }
+}
+
+impl Object for Sdf {
+    fn intersect(&self, ray: &Ray, min_t: f64, max_t: f64) -> Option<HitRecord> {
+        let direction = ray.get_direction();
+        let inverse_direction = Vec3::new(
+            1.0 / direction.get_x(),
+            1.0 / direction.get_y(),
+            1.0 / direction.get_z(),
+        );
+
+        let (start, end) = self.bounding_box.get_hit_range(
+            ray,
+            &inverse_direction,
+            min_t.max(-MAX_DISTANCE),
+            max_t.min(MAX_DISTANCE),
+        )?;
+
+        let mut t = start;
+        let mut distance = self.field.get_distance(&ray.calculate_ray_position(t));
+
+        // rays leaving the surface (like reflected rays) start on it, so they're moved out of
+        // the hit distance before looking for the next hit
+        while distance.abs() < HIT_DISTANCE && t <= end {
+            t += HIT_DISTANCE;
+            distance = self.field.get_distance(&ray.calculate_ray_position(t));
+        }
+
+        // inside of the surface the ray walks towards the point where the distance becomes
+        // positive
+        let side = distance.signum();
+
+        for _ in 0..MAX_STEPS {
+            if t > end {
+                return None;
+            }
+
+            let step = side * distance / self.lipschitz_bound;
+
+            if step < HIT_DISTANCE {
+                let point = ray.calculate_ray_position(t);
+                let normal = *self.get_gradient(&point).make_unit();
+
+                let u = 0.5 + normal.get_z().atan2(*normal.get_x()) / (2.0 * PI);
+                let v = 0.5 + normal.get_y().clamp(-1.0, 1.0).asin() / PI;
+
+                return Some(HitRecord::new(ray, t, normal, (u, v)));
+            }
+
+            t += step;
+            distance = self.field.get_distance(&ray.calculate_ray_position(t));
+        }
+
+        None
+    }
+
+    fn get_material(&self) -> &Material {
+        &(self.material)
+    }
+
+    fn get_bounding_box(&self) -> BoundingBox {
+        self.bounding_box
+    }
+}
@@ -1,13 +1,14 @@
 use std::f64;
 
-use super::{Material, Object};
-use crate::{Ray, Vec3};
-use canvas::RGB;
+use rand::{Rng, RngCore};
+
+use super::{HitRecord, Material, Object};
+use crate::{BoundingBox, Ray, Vec3};
 
 /// object to abstract a sphere in our ray traced world
 ///
 /// Trough the use of this object we can create a sphere in our world and check if a ray hits it
-/// with `is_object_hit`
+/// with `intersect`
 pub struct Sphere {
     // there is no necessity to have private fields on this object
     position: Vec3,
@@ -28,53 +29,68 @@ impl Sphere {
 }
 
 impl Object for Sphere {
-    fn is_object_hit(&self, ray: &Ray) -> Option<f64> {
+    fn intersect(&self, ray: &Ray, min_t: f64, max_t: f64) -> Option<HitRecord> {
         let oc = (*ray.get_position()) - self.position;
         let a = ray.get_direction().dot_product(ray.get_direction()); // should always be one
         // but who knows
-        let b = ray.get_direction().dot_product(&oc) * 2.0;
+        let half_b = ray.get_direction().dot_product(&oc);
         let c = oc.dot_product(&oc) - (self.radius * self.radius);
-        let discriminant = b * b - 4.0 * a * c;
+        let discriminant = half_b * half_b - a * c;
 
         if discriminant < 0.0 {
             return None;
         }
 
-        let t1 = (-b + discriminant.sqrt()) / (2.0 * a);
-        let t2 = (-b - discriminant.sqrt()) / (2.0 * a);
+        // the nearest root in range, when the ray starts inside of the sphere it's the far one
+        let discriminant_sqrt = discriminant.sqrt();
+        let near_t = (-half_b - discriminant_sqrt) / a;
+        let far_t = (-half_b + discriminant_sqrt) / a;
+        let t = [near_t, far_t]
+            .into_iter()
+            .find(|t| *t > min_t && *t < max_t)?;
 
-        Some(f64::min(t1, t2)) // i think that the syntax is more readable like this
-    }
+        let point = ray.calculate_ray_position(t);
+        let outward_normal = (point - self.position) / self.radius;
+
+        // the texture is wrapped around the sphere with an equirectangular projection, v goes
+        // from the bottom (0) to the top (1)
+        let u = 0.5
+            + (f64::atan2(*outward_normal.get_z(), *outward_normal.get_x())
+                / (2.0 * f64::consts::PI));
+        let v = 0.5 + (f64::asin(outward_normal.get_y().clamp(-1.0, 1.0)) / f64::consts::PI);
+
+        let mut hit = HitRecord::new(ray, t, outward_normal, (u, v));
+
+        // u grows around the y axis and v towards the north pole (the poles keep the default
+        // tangents)
+        let tangent = Vec3::new(-outward_normal.get_z(), 0.0, *outward_normal.get_x());
 
-    // TODO: check if the point is inside of the sphere in a better way since we have to account
-    // for float number's shenanigans
-    fn get_normal(&self, point: Vec3) -> Option<Vec3> {
-        //if (point - self.position).dot_product(&(point - self.position)) != (self.radius * self.radius) {
-        //    return None;
-        //}
+        if tangent.get_length() > 0.0 {
+            hit.set_tangents(tangent, tangent.cross_product(&outward_normal));
+        }
 
-        Some(*((point - self.position).make_unit()))
+        Some(hit)
     }
 
     fn get_material(&self) -> &Material {
         &(self.material)
     }
 
-    fn get_color(&self, point: Vec3) -> RGB {
-        let mut final_color = *self.material.get_color();
-
-        if let Some(texture) = self.material.get_texture() {
-            let mut point_to_center = point - self.position;
-            point_to_center.make_unit();
+    fn get_bounding_box(&self) -> BoundingBox {
+        BoundingBox::new(self.position - self.radius, self.position + self.radius)
+    }
 
-            let u = 0.5
-                + (f64::atan2(*point_to_center.get_z(), *point_to_center.get_x())
-                    / (2.0 * f64::consts::PI));
-            let v = 0.5 - (f64::asin(*point_to_center.get_y()) / f64::consts::PI);
+    fn get_area(&self) -> f64 {
+        4.0 * f64::consts::PI * self.radius * self.radius
+    }
 
-            final_color += texture.get_color(u, v);
-        }
+    fn sample_surface(&self, rng: &mut dyn RngCore) -> Option<(Vec3, Vec3)> {
+        // a uniform height and angle are uniform on the sphere (Archimedes' hat-box theorem)
+        let y: f64 = rng.random_range(-1.0..1.0);
+        let angle = 2.0 * f64::consts::PI * rng.random::<f64>();
+        let radius = (1.0 - y * y).max(0.0).sqrt();
+        let normal = Vec3::new(radius * angle.cos(), y, radius * angle.sin());
 
-        final_color
+        Some((self.position + normal * self.radius, normal))
     }
 }
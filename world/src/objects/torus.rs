@@ -0,0 +1,394 @@
+use std::f64::consts::PI;
+
+use rand::{Rng, RngCore};
+
+use super::cylinder::get_angle_fraction;
+use super::{HitRecord, Material, Object};
+use crate::{BoundingBox, Ray, Vec3};
+
+/// values closer to zero than this are treated as zero by the polynomial solvers
+const SOLVER_EPSILON: f64 = 1e-9;
+/// the number of Newton iterations used to refine the roots of the quartic
+const NEWTON_ITERATIONS: usize = 3;
+
+/// object to abstract a torus in our ray traced world
+///
+/// Trough the use of this object we can create a torus centered on the origin around the z axis
+/// (it's placed in the world with an `Instance`) and check if a ray hits it with `intersect`; it's
+/// UV mapped by the angle around the axis and the angle around the tube
+pub struct Torus {
+    major_radius: f64,
+    minor_radius: f64,
+    material: Material,
+}
+
+impl Torus {
+    /// creates a new torus, the `major_radius` is the distance of the tube's center from the
+    /// torus' center and the `minor_radius` is the radius of the tube
+    pub fn new(major_radius: f64, minor_radius: f64, material: Material) -> Self {
+        Self {
+            major_radius,
+            minor_radius,
+            material,
+        }
+    }
+}
+
+impl Object for Torus {
+    fn intersect(&self, ray: &Ray, min_t: f64, max_t: f64) -> Option<HitRecord> {
+        let direction = ray.get_direction();
+
+        // the quartic is solved from the point of the ray closest to the center so that the
+        // coefficients stay small even for far away rays
+        let shift = -ray.get_position().dot_product(direction);
+        let position = ray.calculate_ray_position(shift);
+
+        let major_squared = self.major_radius * self.major_radius;
+        let minor_squared = self.minor_radius * self.minor_radius;
+        let f = position.dot_product(direction);
+        let e = position.dot_product(&position) - major_squared - minor_squared;
+        let (position_z, direction_z) = (*position.get_z(), *direction.get_z());
+
+        // (|p|² - R² - r²)² = 4R² (r² - z²) with p = position + t direction (a unit vector)
+        let coefficients = [
+            e * e - 4.0 * major_squared * (minor_squared - position_z * position_z),
+            4.0 * f * e + 8.0 * major_squared * position_z * direction_z,
+            2.0 * e + 4.0 * f * f + 4.0 * major_squared * direction_z * direction_z,
+            4.0 * f,
+            1.0,
+        ];
+
+        let t = solve_quartic(&coefficients)
+            .into_iter()
+            .map(|root| polish_root(&coefficients, root) + shift)
+            .filter(|t| *t > min_t && *t < max_t)
+            .min_by(f64::total_cmp)?;
+
+        let point = ray.calculate_ray_position(t);
+
+        // the normal points away from the closest point of the tube's center circle
+        let radial_distance = (point.get_x().powi(2) + point.get_y().powi(2)).sqrt();
+        let tube_center = if radial_distance > 0.0 {
+            Vec3::new(*point.get_x(), *point.get_y(), 0.0) * (self.major_radius / radial_distance)
+        } else {
+            Vec3::new(self.major_radius, 0.0, 0.0)
+        };
+        let normal = point - tube_center;
+
+        let tube_angle = point.get_z().atan2(radial_distance - self.major_radius);
+
+        Some(HitRecord::new(
+            ray,
+            t,
+            normal,
+            (
+                get_angle_fraction(&point),
+                (tube_angle / (2.0 * PI)).rem_euclid(1.0),
+            ),
+        ))
+    }
+
+    fn get_material(&self) -> &Material {
+        &(self.material)
+    }
+
+    fn get_bounding_box(&self) -> BoundingBox {
+        let radius = self.major_radius + self.minor_radius;
+
+        BoundingBox::new(
+            Vec3::new(-radius, -radius, -self.minor_radius),
+            Vec3::new(radius, radius, self.minor_radius),
+        )
+    }
+
+    fn get_area(&self) -> f64 {
+        4.0 * PI * PI * self.major_radius * self.minor_radius
+    }
+
+    fn sample_surface(&self, rng: &mut dyn RngCore) -> Option<(Vec3, Vec3)> {
+        // the outer side of the tube is bigger than the inner one, so the angles around the tube
+        // are kept as often as far from the axis they bring the point
+        let tube_angle = loop {
+            let tube_angle = 2.0 * PI * rng.random::<f64>();
+            let distance = self.major_radius + self.minor_radius * tube_angle.cos();
+
+            if rng.random::<f64>() * (self.major_radius + self.minor_radius) <= distance {
+                break tube_angle;
+            }
+        };
+        let angle = 2.0 * PI * rng.random::<f64>();
+
+        let normal = Vec3::new(
+            tube_angle.cos() * angle.cos(),
+            tube_angle.cos() * angle.sin(),
+            tube_angle.sin(),
+        );
+        let tube_center = Vec3::new(angle.cos(), angle.sin(), 0.0) * self.major_radius;
+
+        Some((tube_center + normal * self.minor_radius, normal))
+    }
+}
+
+/// refines a root of the polynomial (with its coefficients from the constant one) with a few
+/// Newton iterations
+fn polish_root(coefficients: &[f64; 5], mut root: f64) -> f64 {
+    for _ in 0..NEWTON_ITERATIONS {
+        let (value, derivative) =
+            coefficients
+                .iter()
+                .rev()
+                .fold((0.0, 0.0), |(value, derivative), coefficient| {
+                    (value * root + coefficient, derivative * root + value)
+                });
+
+        if derivative.abs() < SOLVER_EPSILON {
+            break;
+        }
+
+        root -= value / derivative;
+    }
+
+    root
+}
+
+/// solves `c[0] + c[1] x + c[2] x²` returning the real roots
+fn solve_quadric(coefficients: [f64; 3]) -> Vec<f64> {
+    let p = coefficients[1] / (2.0 * coefficients[2]);
+    let q = coefficients[0] / coefficients[2];
+    let discriminant = p * p - q;
+
+    if discriminant.abs() < SOLVER_EPSILON {
+        vec![-p]
+    } else if discriminant < 0.0 {
+        vec![]
+    } else {
+        let discriminant_sqrt = discriminant.sqrt();
+
+        vec![discriminant_sqrt - p, -discriminant_sqrt - p]
+    }
+}
+
+/// solves `c[0] + c[1] x + c[2] x² + c[3] x³` returning the real roots (with Cardano's method)
+fn solve_cubic(coefficients: [f64; 4]) -> Vec<f64> {
+    // normal form: x³ + A x² + B x + C = 0
+    let a = coefficients[2] / coefficients[3];
+    let b = coefficients[1] / coefficients[3];
+    let c = coefficients[0] / coefficients[3];
+
+    // substitute x = y - A/3 to eliminate the quadric term: y³ + 3p y + 2q = 0
+    let a_squared = a * a;
+    let p = (-a_squared / 3.0 + b) / 3.0;
+    let q = (2.0 / 27.0 * a * a_squared - a * b / 3.0 + c) / 2.0;
+
+    let p_cubed = p * p * p;
+    let discriminant = q * q + p_cubed;
+
+    let roots = if discriminant.abs() < SOLVER_EPSILON {
+        if q.abs() < SOLVER_EPSILON {
+            vec![0.0]
+        } else {
+            let u = (-q).cbrt();
+
+            vec![2.0 * u, -u]
+        }
+    } else if discriminant < 0.0 {
+        // three real roots
+        let phi = (-q / (-p_cubed).sqrt()).clamp(-1.0, 1.0).acos() / 3.0;
+        let t = 2.0 * (-p).sqrt();
+
+        vec![
+            t * phi.cos(),
+            -t * (phi + PI / 3.0).cos(),
+            -t * (phi - PI / 3.0).cos(),
+        ]
+    } else {
+        let discriminant_sqrt = discriminant.sqrt();
+
+        vec![(discriminant_sqrt - q).cbrt() - (discriminant_sqrt + q).cbrt()]
+    };
+
+    roots.into_iter().map(|root| root - a / 3.0).collect()
+}
+
+/// solves `c[0] + c[1] x + c[2] x² + c[3] x³ + c[4] x⁴` returning the real roots (with
+/// Ferrari's method)
+fn solve_quartic(coefficients: &[f64; 5]) -> Vec<f64> {
+    // normal form: x⁴ + A x³ + B x² + C x + D = 0
+    let a = coefficients[3] / coefficients[4];
+    let b = coefficients[2] / coefficients[4];
+    let c = coefficients[1] / coefficients[4];
+    let d = coefficients[0] / coefficients[4];
+
+    // substitute x = y - A/4 to eliminate the cubic term: y⁴ + p y² + q y + r = 0
+    let a_squared = a * a;
+    let p = -3.0 / 8.0 * a_squared + b;
+    let q = a_squared * a / 8.0 - a * b / 2.0 + c;
+    let r = -3.0 / 256.0 * a_squared * a_squared + a_squared * b / 16.0 - a * c / 4.0 + d;
+
+    let roots = if r.abs() < SOLVER_EPSILON {
+        // no absolute term: y (y³ + p y + q) = 0
+        let mut roots = solve_cubic([q, p, 0.0, 1.0]);
+        roots.push(0.0);
+
+        roots
+    } else {
+        // one root of the resolvent cubic splits the quartic into two quadrics
+        let z = solve_cubic([r * p / 2.0 - q * q / 8.0, -r, -p / 2.0, 1.0])[0];
+
+        let u = z * z - r;
+        let v = 2.0 * z - p;
+
+        let u = if u.abs() < SOLVER_EPSILON {
+            0.0
+        } else if u > 0.0 {
+            u.sqrt()
+        } else {
+            return vec![];
+        };
+        let v = if v.abs() < SOLVER_EPSILON {
+            0.0
+        } else if v > 0.0 {
+            v.sqrt()
+        } else {
+            return vec![];
+        };
+
+        let mut roots = solve_quadric([z - u, if q < 0.0 { -v } else { v }, 1.0]);
+        roots.extend(solve_quadric([z + u, if q < 0.0 { v } else { -v }, 1.0]));
+
+        roots
+    };
+
+    roots.into_iter().map(|root| root - a / 4.0).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::MaterialBuilder;
+
+    const MAJOR_RADIUS: f64 = 2.0;
+    const MINOR_RADIUS: f64 = 0.5;
+
+    fn torus() -> Torus {
+        Torus::new(
+            MAJOR_RADIUS,
+            MINOR_RADIUS,
+            MaterialBuilder::default().build(),
+        )
+    }
+
+    /// retrieves the `t` of the closest hit of the ray (if any)
+    fn hit_t(position: Vec3, direction: Vec3) -> Option<f64> {
+        torus()
+            .intersect(&Ray::new(position, direction), 0.0, f64::MAX)
+            .map(|hit| hit.get_t())
+    }
+
+    fn assert_close(t: Option<f64>, expected: f64) {
+        let t = t.expect("the ray should hit the torus");
+
+        assert!(
+            (t - expected).abs() < 1e-6,
+            "expected t = {}, found {}",
+            expected,
+            t
+        );
+    }
+
+    #[test]
+    fn hits_the_outer_side_of_the_tube() {
+        assert_close(
+            hit_t(Vec3::new(-5.0, 0.0, 0.0), Vec3::new(1.0, 0.0, 0.0)),
+            5.0 - MAJOR_RADIUS - MINOR_RADIUS,
+        );
+    }
+
+    #[test]
+    fn misses_trough_the_hole() {
+        // the ray crosses the torus' plane half a unit away from the center
+        assert_eq!(
+            hit_t(Vec3::new(-0.5, 0.0, -5.0), Vec3::new(0.2, 0.0, 1.0)),
+            None
+        );
+    }
+
+    #[test]
+    fn misses_along_the_axis() {
+        assert_eq!(
+            hit_t(Vec3::new(0.0, 0.0, -5.0), Vec3::new(0.0, 0.0, 1.0)),
+            None
+        );
+        assert_eq!(
+            hit_t(Vec3::new(0.0, 0.0, 5.0), Vec3::new(0.0, 0.0, -1.0)),
+            None
+        );
+    }
+
+    #[test]
+    fn grazes_the_top_of_the_tube() {
+        // the double root of a tangent ray is only as precise as the square root of the
+        // rounding errors
+        let t = hit_t(Vec3::new(-5.0, 0.0, MINOR_RADIUS), Vec3::new(1.0, 0.0, 0.0))
+            .expect("the tangent ray should touch the torus");
+
+        assert!((t - (5.0 - MAJOR_RADIUS)).abs() < 1e-4, "found t = {}", t);
+    }
+
+    #[test]
+    fn hits_close_to_the_tangent_of_the_tube() {
+        // a ray along x at the height z cuts a chord of the tube's circle around x = -R
+        let z = MINOR_RADIUS - 1e-3;
+        let half_chord = (MINOR_RADIUS * MINOR_RADIUS - z * z).sqrt();
+
+        assert_close(
+            hit_t(Vec3::new(-5.0, 0.0, z), Vec3::new(1.0, 0.0, 0.0)),
+            5.0 - MAJOR_RADIUS - half_chord,
+        );
+        assert_eq!(
+            hit_t(
+                Vec3::new(-5.0, 0.0, MINOR_RADIUS + 1e-3),
+                Vec3::new(1.0, 0.0, 0.0)
+            ),
+            None
+        );
+    }
+
+    #[test]
+    fn hits_from_inside_the_tube() {
+        let tube_center = Vec3::new(MAJOR_RADIUS, 0.0, 0.0);
+
+        for direction in [
+            Vec3::new(1.0, 0.0, 0.0),
+            Vec3::new(-1.0, 0.0, 0.0),
+            Vec3::new(0.0, 0.0, 1.0),
+            Vec3::new(0.0, 0.0, -1.0),
+        ] {
+            assert_close(hit_t(tube_center, direction), MINOR_RADIUS);
+        }
+
+        // a point off the tube's center along x leaves the tube after the rest of the radius
+        assert_close(
+            hit_t(
+                Vec3::new(MAJOR_RADIUS + 0.2, 0.0, 0.0),
+                Vec3::new(1.0, 0.0, 0.0),
+            ),
+            MINOR_RADIUS - 0.2,
+        );
+    }
+
+    #[test]
+    fn points_the_normal_away_from_the_tube() {
+        let hit = torus()
+            .intersect(
+                &Ray::new(Vec3::new(0.0, -5.0, 0.0), Vec3::new(0.0, 1.0, 0.0)),
+                0.0,
+                f64::MAX,
+            )
+            .expect("the ray should hit the torus");
+        let normal = hit.get_geometric_normal();
+
+        assert_close(Some(hit.get_t()), 5.0 - MAJOR_RADIUS - MINOR_RADIUS);
+        assert!(normal.get_y() < &-0.999, "found the normal {:?}", normal);
+    }
+}
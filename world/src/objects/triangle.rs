@@ -1,15 +1,20 @@
-use crate::objects::{Material, Object};
-use crate::{Ray, Vec3};
-use canvas::RGB;
+use rand::{Rng, RngCore};
+
+use crate::objects::{HitRecord, Material, Object};
+use crate::{BoundingBox, Ray, Vec3};
 
 /// object to abstract a triangle in our ray traced world
 ///
 /// Trough the use of this object we can create a triangle in our world and check if a ray hits it
-/// with `is_object_hit`
+/// with `intersect`; the triangle can have a normal for every vertex (interpolated for smooth
+/// shading) and texture coordinates for every vertex (or else the barycentric coordinates are its
+/// UVs)
 pub struct Triangle {
     vertice_1: Vec3,
     vertice_2: Vec3,
     vertice_3: Vec3,
+    normals: Option<[Vec3; 3]>,
+    texture_coordinates: Option<[(f64, f64); 3]>,
     material: Material,
 }
 
@@ -19,55 +24,186 @@ impl Triangle {
             vertice_1,
             vertice_2,
             vertice_3,
+            normals: None,
+            texture_coordinates: None,
             material,
         }
     }
+
+    /// sets the normals of the three vertices
+    pub fn set_normals(&mut self, normals: Option<[Vec3; 3]>) -> &mut Self {
+        self.normals = normals;
+        self
+    }
+
+    /// sets the texture coordinates of the three vertices
+    pub fn set_texture_coordinates(
+        &mut self,
+        texture_coordinates: Option<[(f64, f64); 3]>,
+    ) -> &mut Self {
+        self.texture_coordinates = texture_coordinates;
+        self
+    }
 }
 
-impl Object for Triangle {
-    fn is_object_hit(&self, ray: &Ray) -> Option<f64> {
-        let e1 = self.vertice_2 - self.vertice_1;
-        let e2 = self.vertice_3 - self.vertice_1;
+/// checks if the ray hits the triangle with the given vertices (Möller–Trumbore algorithm),
+/// returning the ray's `t` and the barycentric `u` and `v` coordinates of the hit point
+pub(super) fn intersect_triangle(
+    vertice_1: &Vec3,
+    vertice_2: &Vec3,
+    vertice_3: &Vec3,
+    ray: &Ray,
+) -> Option<(f64, f64, f64)> {
+    let e1 = (*vertice_2) - (*vertice_1);
+    let e2 = (*vertice_3) - (*vertice_1);
 
-        let ray_cross_e2 = ray.get_direction().cross_product(&e2);
-        let det = e1.dot_product(&ray_cross_e2);
+    let ray_cross_e2 = ray.get_direction().cross_product(&e2);
+    let det = e1.dot_product(&ray_cross_e2);
 
-        if f64::abs(det) < f64::EPSILON {
-            return None;
-        }
+    if f64::abs(det) < f64::EPSILON {
+        return None;
+    }
 
-        let inv_det = 1.0 / det;
-        let s = (*ray.get_position()) - self.vertice_1;
-        let u = inv_det * s.dot_product(&ray_cross_e2);
+    let inv_det = 1.0 / det;
+    let s = (*ray.get_position()) - (*vertice_1);
+    let u = inv_det * s.dot_product(&ray_cross_e2);
 
-        if !(0.0..1.0).contains(&u) {
-            return None;
-        }
+    if !(0.0..1.0).contains(&u) {
+        return None;
+    }
 
-        let s_cross_e1 = s.cross_product(&e1);
-        let v = inv_det * ray.get_direction().dot_product(&s_cross_e1);
+    let s_cross_e1 = s.cross_product(&e1);
+    let v = inv_det * ray.get_direction().dot_product(&s_cross_e1);
 
-        if v < 0.0 || u + v > 1.0 {
-            return None;
+    if v < 0.0 || u + v > 1.0 {
+        return None;
+    }
+
+    let t = inv_det * e2.dot_product(&s_cross_e1);
+
+    Some((t, u, v))
+}
+
+/// creates the record of a hit on the triangle at the barycentric `u` and `v` coordinates,
+/// interpolating the vertices' texture coordinates and normals (if given)
+pub(super) fn create_triangle_hit(
+    ray: &Ray,
+    t: f64,
+    (u, v): (f64, f64),
+    vertices: [Vec3; 3],
+    normals: Option<[Vec3; 3]>,
+    texture_coordinates: Option<[(f64, f64); 3]>,
+) -> HitRecord {
+    let face_normal = (vertices[1] - vertices[0]).cross_product(&(vertices[2] - vertices[0]));
+    let weights = [1.0 - u - v, u, v];
+
+    // without texture coordinates the barycentric coordinates are the triangle's UVs
+    let uv = match texture_coordinates {
+        Some(uvs) => (
+            (0..3).map(|vertex| uvs[vertex].0 * weights[vertex]).sum(),
+            (0..3).map(|vertex| uvs[vertex].1 * weights[vertex]).sum(),
+        ),
+        None => (u, v),
+    };
+
+    let mut hit = HitRecord::new(ray, t, face_normal, uv);
+
+    // the directions the UVs grow along solve the edges' change of UVs (the barycentric UVs grow
+    // along the edges themselves)
+    let first_edge = vertices[1] - vertices[0];
+    let second_edge = vertices[2] - vertices[0];
+    let [(first_u, first_v), (second_u, second_v)] = match texture_coordinates {
+        Some(uvs) => [
+            (uvs[1].0 - uvs[0].0, uvs[1].1 - uvs[0].1),
+            (uvs[2].0 - uvs[0].0, uvs[2].1 - uvs[0].1),
+        ],
+        None => [(1.0, 0.0), (0.0, 1.0)],
+    };
+    let determinant = first_u * second_v - second_u * first_v;
+
+    if determinant.abs() > f64::EPSILON {
+        hit.set_tangents(
+            (first_edge * second_v - second_edge * first_v) / determinant,
+            (second_edge * first_u - first_edge * second_u) / determinant,
+        );
+    }
+
+    if let Some(normals) = normals {
+        let mut shading_normal =
+            normals[0] * weights[0] + normals[1] * weights[1] + normals[2] * weights[2];
+
+        // the interpolated normal has to stay on the face normal's side
+        if shading_normal.dot_product(&face_normal) < 0.0 {
+            shading_normal = shading_normal.get_inverse();
         }
 
-        let t = inv_det * e2.dot_product(&s_cross_e1);
+        hit.set_shading_normal(shading_normal);
+    }
 
-        Some(t)
+    hit
+}
+
+/// returns the area of the triangle with the given vertices
+pub(super) fn get_triangle_area(vertices: &[Vec3; 3]) -> f64 {
+    (vertices[1] - vertices[0])
+        .cross_product(&(vertices[2] - vertices[0]))
+        .get_length()
+        / 2.0
+}
+
+/// chooses a uniformly distributed point of the triangle with the given vertices, returning it
+/// with the face normal (the one of its hits)
+pub(super) fn sample_triangle(vertices: &[Vec3; 3], rng: &mut dyn RngCore) -> (Vec3, Vec3) {
+    // folding the square of the random numbers on the triangle keeps the points uniform
+    let (mut u, mut v): (f64, f64) = (rng.random(), rng.random());
+
+    if u + v > 1.0 {
+        (u, v) = (1.0 - u, 1.0 - v);
     }
 
-    fn get_normal(&self, _point: Vec3) -> Option<Vec3> {
-        let e1 = self.vertice_2 - self.vertice_1;
-        let e2 = self.vertice_3 - self.vertice_1;
+    let first_edge = vertices[1] - vertices[0];
+    let second_edge = vertices[2] - vertices[0];
+    let mut normal = first_edge.cross_product(&second_edge);
+    normal.make_unit();
 
-        Some(e1.cross_product(&e2))
+    (vertices[0] + first_edge * u + second_edge * v, normal)
+}
+
+impl Object for Triangle {
+    fn intersect(&self, ray: &Ray, min_t: f64, max_t: f64) -> Option<HitRecord> {
+        let (t, u, v) = intersect_triangle(&self.vertice_1, &self.vertice_2, &self.vertice_3, ray)?;
+
+        if t <= min_t || t >= max_t {
+            return None;
+        }
+
+        Some(create_triangle_hit(
+            ray,
+            t,
+            (u, v),
+            [self.vertice_1, self.vertice_2, self.vertice_3],
+            self.normals,
+            self.texture_coordinates,
+        ))
     }
 
     fn get_material(&self) -> &Material {
         &(self.material)
     }
 
-    fn get_color(&self, _point: Vec3) -> RGB {
-        *self.get_material().get_color()
+    fn get_bounding_box(&self) -> BoundingBox {
+        // a triangle lying on an axis plane would have a flat box
+        BoundingBox::from_points(&[self.vertice_1, self.vertice_2, self.vertice_3]).pad(0.0001)
+    }
+
+    fn get_area(&self) -> f64 {
+        get_triangle_area(&[self.vertice_1, self.vertice_2, self.vertice_3])
+    }
+
+    fn sample_surface(&self, rng: &mut dyn RngCore) -> Option<(Vec3, Vec3)> {
+        Some(sample_triangle(
+            &[self.vertice_1, self.vertice_2, self.vertice_3],
+            rng,
+        ))
     }
 }
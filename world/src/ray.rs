@@ -1,6 +1,7 @@
 use std::ops::Range;
 
 use super::vec3::Vec3;
+use rand::Rng;
 
 /// An Object abstracting a Ray in 3d space
 ///
@@ -11,12 +12,14 @@ use super::vec3::Vec3;
 /// Create a new ray at position (0, 0, 0) that is sent at direction (1, 0, 0)
 ///
 /// ```no_run
+/// # use world::{Ray, Vec3};
 /// let ray = Ray::new(Vec3::new(0.0, 0.0, 0.0), Vec3::new(1.0, 0.0, 0.0));
 /// ```
 ///
 /// Calculate the ray's position at t
 ///
 /// ```no_run
+/// # use world::{Ray, Vec3};
 /// let ray = Ray::new(Vec3::new(0.0, 0.0, 0.0), Vec3::new(1.0, 0.0, 0.0));
 ///
 /// let ray_position = ray.calculate_ray_position(0.8);
@@ -28,6 +31,7 @@ use super::vec3::Vec3;
 pub struct Ray {
     starting_position: Vec3,
     direction: Vec3,
+    spread: f64,
 }
 
 impl Ray {
@@ -38,6 +42,7 @@ impl Ray {
         Self {
             starting_position,
             direction,
+            spread: 0.0,
         }
     }
 
@@ -56,9 +61,23 @@ impl Ray {
         &self.direction
     }
 
-    /// scatters the ray's direction into the given x, y and z range
-    pub fn scatter(
+    /// sets the ray's spread, the angle (in radians) between the ray and the rays of the
+    /// neighbouring pixels; it's used to filter the textures
+    pub fn set_spread(&mut self, spread: f64) -> &mut Self {
+        self.spread = spread;
+        self
+    }
+
+    /// retrieves the ray's spread (0 for rays that don't come from the camera)
+    pub fn get_spread(&self) -> f64 {
+        self.spread
+    }
+
+    /// scatters the ray's direction into the given x, y and z range using the given random
+    /// number generator
+    pub fn scatter<R: Rng>(
         &mut self,
+        rng: &mut R,
         x_range: Option<Range<f64>>,
         y_range: Option<Range<f64>>,
         z_range: Option<Range<f64>>,
@@ -66,15 +85,15 @@ impl Ray {
         let mut scatter_vector = Vec3::new(0.0, 0.0, 0.0);
 
         if let Some(range) = x_range {
-            scatter_vector.add_x(rand::random_range(range));
+            scatter_vector.add_x(rng.random_range(range));
         }
 
         if let Some(range) = y_range {
-            scatter_vector.add_y(rand::random_range(range));
+            scatter_vector.add_y(rng.random_range(range));
         }
 
         if let Some(range) = z_range {
-            scatter_vector.add_z(rand::random_range(range));
+            scatter_vector.add_z(rng.random_range(range));
         }
 
         self.direction += scatter_vector;
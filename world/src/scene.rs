@@ -0,0 +1,153 @@
+use std::ops::Range;
+
+use crate::{BoundingBox, Bvh, Light, Object};
+
+/// A reference to a primitive stored in the scene's bounding volume hierarchy
+#[derive(Debug, Clone, Copy)]
+enum ScenePrimitive {
+    /// the index of an object
+    Object(usize),
+    /// the index of a light that has an object (for example a panel light)
+    Light(usize),
+}
+
+/// An object containing everything that can be hit by a ray in our world
+///
+/// The scene owns the world's objects and lights and keeps a bounding volume hierarchy over all
+/// of them, the hierarchy has to be rebuilt with `build_bvh` after all of the objects and lights
+/// have been added (or else `ObjectRayIntersection::check_intersection` won't see them); objects
+/// without bounds (like infinite planes) are kept outside of the hierarchy and checked one by one;
+/// the objects with an emissive material are remembered too so that they can be sampled as lights
+///
+/// # Examples
+///
+/// Create a scene with a sphere and build its hierarchy
+///
+/// ```no_run
+/// # use world::{MaterialBuilder, Objects, Scene, Vec3};
+/// let mut scene = Scene::new();
+///
+/// scene.add_object(Objects::create_object(Objects::Sphere(
+///     Vec3::new(0.0, 0.0, 5.0),
+///     1.0,
+///     MaterialBuilder::default().build(),
+/// )));
+///
+/// scene.build_bvh();
+/// ```
+#[derive(Default)]
+pub struct Scene {
+    objects: Vec<Box<dyn Object>>,
+    lights: Vec<Box<dyn Light>>,
+    bvh: Bvh,
+    bvh_primitives: Vec<ScenePrimitive>,
+    bounded_primitives: usize,
+    emitters: Vec<usize>,
+}
+
+impl Scene {
+    /// creates a new empty scene
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// adds a new object to the scene
+    pub fn add_object(&mut self, new_object: Box<dyn Object>) {
+        self.objects.push(new_object);
+    }
+
+    /// adds a new light to the scene
+    pub fn add_light(&mut self, new_light: Box<dyn Light>) {
+        self.lights.push(new_light);
+    }
+
+    /// retrieves the scene's objects
+    pub fn get_objects(&self) -> &[Box<dyn Object>] {
+        &self.objects
+    }
+
+    /// retrieves the scene's lights
+    pub fn get_lights(&self) -> &[Box<dyn Light>] {
+        &self.lights
+    }
+
+    /// (re)builds the bounding volume hierarchy over every object and light object of the scene,
+    /// the unbounded ones are stored after the primitives of the hierarchy
+    pub fn build_bvh(&mut self) {
+        let mut primitives: Vec<(ScenePrimitive, BoundingBox)> = vec![];
+
+        for (index, object) in self.objects.iter().enumerate() {
+            primitives.push((ScenePrimitive::Object(index), object.get_bounding_box()));
+        }
+
+        for (index, light) in self.lights.iter().enumerate() {
+            if let Some(light_object) = light.get_object() {
+                primitives.push((
+                    ScenePrimitive::Light(index),
+                    light_object.get_bounding_box(),
+                ));
+            }
+        }
+
+        let (bounded, unbounded): (Vec<_>, Vec<_>) = primitives
+            .into_iter()
+            .partition(|(_, bounding_box)| bounding_box.is_finite());
+
+        let bounding_boxes: Vec<BoundingBox> = bounded
+            .iter()
+            .map(|(_, bounding_box)| *bounding_box)
+            .collect();
+
+        self.bounded_primitives = bounded.len();
+        self.bvh_primitives = bounded
+            .into_iter()
+            .chain(unbounded)
+            .map(|(primitive, _)| primitive)
+            .collect();
+        self.bvh = Bvh::new(&bounding_boxes);
+
+        // the glowing objects whose surface can be sampled light up the scene like lights
+        self.emitters = self
+            .objects
+            .iter()
+            .enumerate()
+            .filter(|(_, object)| {
+                object.get_material().get_emission().is_some() && object.get_area() > 0.0
+            })
+            .map(|(index, _)| index)
+            .collect();
+    }
+
+    /// retrieves the emissive objects whose surface can be sampled (see
+    /// `Object::sample_surface`), they're found when the hierarchy is built
+    pub fn get_emitters(&self) -> impl Iterator<Item = &dyn Object> {
+        self.emitters
+            .iter()
+            .map(|index| self.objects[*index].as_ref())
+    }
+
+    /// retrieves the scene's bounding volume hierarchy
+    pub fn get_bvh(&self) -> &Bvh {
+        &self.bvh
+    }
+
+    /// retrieves the indices of the primitives that are not stored in the hierarchy since they
+    /// have no bounds
+    pub fn get_unbounded_primitives(&self) -> Range<usize> {
+        self.bounded_primitives..self.bvh_primitives.len()
+    }
+
+    /// retrieves the object referenced by the hierarchy's primitive `index` and if it belongs to
+    /// a light
+    pub fn get_bvh_primitive(&self, index: usize) -> (&dyn Object, bool) {
+        match self.bvh_primitives[index] {
+            ScenePrimitive::Object(object_index) => (self.objects[object_index].as_ref(), false),
+            ScenePrimitive::Light(light_index) => (
+                self.lights[light_index]
+                    .get_object()
+                    .expect("only lights with an object are stored in the hierarchy"),
+                true,
+            ),
+        }
+    }
+}
@@ -0,0 +1,520 @@
+use crate::{BoundingBox, Vec3};
+
+/// a 4x4 matrix stored row by row
+type Matrix4 = [[f64; 4]; 4];
+
+const IDENTITY: Matrix4 = [
+    [1.0, 0.0, 0.0, 0.0],
+    [0.0, 1.0, 0.0, 0.0],
+    [0.0, 0.0, 1.0, 0.0],
+    [0.0, 0.0, 0.0, 1.0],
+];
+
+/// Object abstracting an affine transform in 3d space
+///
+/// Trough the use of this object we can move, rotate and scale points, vectors and normals; the
+/// transform keeps both its 4x4 matrix and the inverse one so that rays can be brought back into
+/// an object's own space without inverting the matrix every time
+///
+/// # Examples
+///
+/// Scale a point, rotate it around the y axis and then move it
+///
+/// ```no_run
+/// # use world::{Transform, Vec3};
+/// let transform = Transform::scale(Vec3::new(2.0, 1.0, 1.0))
+///     .then(&Transform::rotate_y(90.0_f64.to_radians()))
+///     .then(&Transform::translate(Vec3::new(0.0, 0.0, 5.0)));
+///
+/// let point = transform.transform_point(&Vec3::new(1.0, 0.0, 0.0));
+///
+/// println!("{:?}", point);
+/// ```
+#[derive(Debug, Clone, Copy)]
+pub struct Transform {
+    matrix: Matrix4,
+    inverse: Matrix4,
+}
+
+impl Transform {
+    /// creates the transform that leaves everything where it is
+    pub fn identity() -> Self {
+        Self {
+            matrix: IDENTITY,
+            inverse: IDENTITY,
+        }
+    }
+
+    /// creates a transform from its matrix (stored row by row, the last row should be
+    /// `0 0 0 1`), None is returned if the matrix can't be inverted
+    pub fn from_matrix(matrix: [[f64; 4]; 4]) -> Option<Self> {
+        Some(Self {
+            matrix,
+            inverse: invert(&matrix)?,
+        })
+    }
+
+    /// creates the transform bringing the x and y axes onto the given ones (the z axis is their
+    /// cross product) and the origin onto `origin`; `y_axis` is made perpendicular to `x_axis`
+    /// so the transform is always a rotation and a translation
+    pub fn from_axes(origin: Vec3, mut x_axis: Vec3, y_axis: Vec3) -> Self {
+        x_axis.make_unit();
+
+        let mut z_axis = x_axis.cross_product(&y_axis);
+
+        // with parallel axes any y axis perpendicular to the x one will do
+        if z_axis.get_length() < f64::EPSILON {
+            z_axis = x_axis.cross_product(&x_axis.get_perpendicular());
+        }
+
+        z_axis.make_unit();
+        let y_axis = z_axis.cross_product(&x_axis);
+
+        let rotation = [x_axis, y_axis, z_axis];
+        let mut matrix = IDENTITY;
+
+        for (column, axis) in rotation.iter().enumerate() {
+            for (row, coordinate) in [*axis.get_x(), *axis.get_y(), *axis.get_z()]
+                .into_iter()
+                .enumerate()
+            {
+                matrix[row][column] = coordinate;
+            }
+        }
+
+        Self {
+            matrix,
+            inverse: transpose(&matrix),
+        }
+        .then(&Self::translate(origin))
+    }
+
+    /// creates the transform bringing the z axis onto `axis` and the origin onto `origin` (the
+    /// rotation around the axis is arbitrary)
+    pub fn from_axis(origin: Vec3, mut axis: Vec3) -> Self {
+        axis.make_unit();
+        let x_axis = axis.get_perpendicular();
+
+        Self::from_axes(origin, x_axis, axis.cross_product(&x_axis))
+    }
+
+    /// creates a transform moving everything by the given offset
+    pub fn translate(offset: Vec3) -> Self {
+        let (x, y, z) = (*offset.get_x(), *offset.get_y(), *offset.get_z());
+
+        Self {
+            matrix: [
+                [1.0, 0.0, 0.0, x],
+                [0.0, 1.0, 0.0, y],
+                [0.0, 0.0, 1.0, z],
+                [0.0, 0.0, 0.0, 1.0],
+            ],
+            inverse: [
+                [1.0, 0.0, 0.0, -x],
+                [0.0, 1.0, 0.0, -y],
+                [0.0, 0.0, 1.0, -z],
+                [0.0, 0.0, 0.0, 1.0],
+            ],
+        }
+    }
+
+    /// creates a transform scaling everything (from the origin) by the given factors, a zero
+    /// factor would flatten the objects so it's replaced by a really small one
+    pub fn scale(factors: Vec3) -> Self {
+        let [x, y, z] = [*factors.get_x(), *factors.get_y(), *factors.get_z()].map(|factor| {
+            if factor.abs() < f64::EPSILON {
+                f64::EPSILON.copysign(factor)
+            } else {
+                factor
+            }
+        });
+
+        Self {
+            matrix: [
+                [x, 0.0, 0.0, 0.0],
+                [0.0, y, 0.0, 0.0],
+                [0.0, 0.0, z, 0.0],
+                [0.0, 0.0, 0.0, 1.0],
+            ],
+            inverse: [
+                [1.0 / x, 0.0, 0.0, 0.0],
+                [0.0, 1.0 / y, 0.0, 0.0],
+                [0.0, 0.0, 1.0 / z, 0.0],
+                [0.0, 0.0, 0.0, 1.0],
+            ],
+        }
+    }
+
+    /// creates a transform rotating everything by `angle` radians around the x axis
+    pub fn rotate_x(angle: f64) -> Self {
+        Self::rotate(Vec3::new(1.0, 0.0, 0.0), angle)
+    }
+
+    /// creates a transform rotating everything by `angle` radians around the y axis
+    pub fn rotate_y(angle: f64) -> Self {
+        Self::rotate(Vec3::new(0.0, 1.0, 0.0), angle)
+    }
+
+    /// creates a transform rotating everything by `angle` radians around the z axis
+    pub fn rotate_z(angle: f64) -> Self {
+        Self::rotate(Vec3::new(0.0, 0.0, 1.0), angle)
+    }
+
+    /// creates a transform rotating everything by `angle` radians around the given axis (passing
+    /// trough the origin), the rotation is counterclockwise looking from the axis' tip
+    pub fn rotate(mut axis: Vec3, angle: f64) -> Self {
+        axis.make_unit();
+
+        let (x, y, z) = (*axis.get_x(), *axis.get_y(), *axis.get_z());
+        let (sin, cos) = angle.sin_cos();
+        let one_minus_cos = 1.0 - cos;
+
+        let matrix = [
+            [
+                cos + x * x * one_minus_cos,
+                x * y * one_minus_cos - z * sin,
+                x * z * one_minus_cos + y * sin,
+                0.0,
+            ],
+            [
+                y * x * one_minus_cos + z * sin,
+                cos + y * y * one_minus_cos,
+                y * z * one_minus_cos - x * sin,
+                0.0,
+            ],
+            [
+                z * x * one_minus_cos - y * sin,
+                z * y * one_minus_cos + x * sin,
+                cos + z * z * one_minus_cos,
+                0.0,
+            ],
+            [0.0, 0.0, 0.0, 1.0],
+        ];
+
+        // the inverse of a rotation is its transpose
+        Self {
+            matrix,
+            inverse: transpose(&matrix),
+        }
+    }
+
+    /// creates the transform applying this transform first and then the `next` one
+    pub fn then(&self, next: &Transform) -> Self {
+        Self {
+            matrix: multiply(&next.matrix, &self.matrix),
+            inverse: multiply(&self.inverse, &next.inverse),
+        }
+    }
+
+    /// retrieves the transform undoing this one
+    pub fn get_inverse(&self) -> Self {
+        Self {
+            matrix: self.inverse,
+            inverse: self.matrix,
+        }
+    }
+
+    /// retrieves the transform's matrix (stored row by row)
+    pub fn get_matrix(&self) -> &[[f64; 4]; 4] {
+        &self.matrix
+    }
+
+    /// transforms a point (it's moved by the translations)
+    pub fn transform_point(&self, point: &Vec3) -> Vec3 {
+        apply(&self.matrix, point, 1.0)
+    }
+
+    /// transforms a vector (like a direction, it isn't moved by the translations)
+    pub fn transform_vector(&self, vector: &Vec3) -> Vec3 {
+        apply(&self.matrix, vector, 0.0)
+    }
+
+    /// transforms a normal so that it stays perpendicular to the transformed surface (with the
+    /// inverse transpose matrix), the result isn't a unit vector
+    pub fn transform_normal(&self, normal: &Vec3) -> Vec3 {
+        apply(&transpose(&self.inverse), normal, 0.0)
+    }
+
+    /// retrieves how much the transform scales the areas of the surfaces, it's exact when every
+    /// direction is scaled the same way (like with `from_axes` or a uniform `scale`) while with
+    /// different factors along the axes it's only their average (see `get_area_stretch`)
+    pub fn get_area_scale(&self) -> f64 {
+        // the determinant scales the volumes
+        self.get_determinant().abs().powf(2.0 / 3.0)
+    }
+
+    /// retrieves how much the transform stretches the area of a surface around a point with the
+    /// given unit normal, it's exact with any scale
+    pub fn get_area_stretch(&self, normal: &Vec3) -> f64 {
+        // an area element is scaled like the volumes and divided by how much its height (along
+        // the normal) is scaled, which is what the transformed normal's length is
+        self.get_determinant().abs() * self.transform_normal(normal).get_length()
+    }
+
+    /// retrieves the determinant of the matrix's linear part
+    fn get_determinant(&self) -> f64 {
+        let m = &self.matrix;
+
+        m[0][0] * (m[1][1] * m[2][2] - m[1][2] * m[2][1])
+            - m[0][1] * (m[1][0] * m[2][2] - m[1][2] * m[2][0])
+            + m[0][2] * (m[1][0] * m[2][1] - m[1][1] * m[2][0])
+    }
+
+    /// creates the smallest axis aligned box containing the transformed bounding box
+    pub fn transform_bounding_box(&self, bounding_box: &BoundingBox) -> BoundingBox {
+        // the corners of an infinite box would become NaNs
+        if !bounding_box.is_finite() {
+            return BoundingBox::infinite();
+        }
+
+        let (min, max) = (bounding_box.get_min(), bounding_box.get_max());
+        let corners: Vec<Vec3> = (0..8)
+            .map(|corner| {
+                // every bit of the corner's index chooses the min or max coordinate of an axis
+                let [x, y, z] = [0, 1, 2].map(|axis| {
+                    if corner & (1 << axis) == 0 {
+                        min.get_axis(axis)
+                    } else {
+                        max.get_axis(axis)
+                    }
+                });
+
+                self.transform_point(&Vec3::new(x, y, z))
+            })
+            .collect();
+
+        BoundingBox::from_points(&corners)
+    }
+}
+
+impl Default for Transform {
+    fn default() -> Self {
+        Self::identity()
+    }
+}
+
+/// multiplies the matrix by the vector with the given `w` (1 for points and 0 for vectors)
+fn apply(matrix: &Matrix4, vector: &Vec3, w: f64) -> Vec3 {
+    let coordinates = [*vector.get_x(), *vector.get_y(), *vector.get_z(), w];
+    let [x, y, z] = [0, 1, 2].map(|row| {
+        (0..4)
+            .map(|column| matrix[row][column] * coordinates[column])
+            .sum::<f64>()
+    });
+
+    Vec3::new(x, y, z)
+}
+
+fn multiply(left: &Matrix4, right: &Matrix4) -> Matrix4 {
+    let mut result = [[0.0; 4]; 4];
+
+    for (row, result_row) in result.iter_mut().enumerate() {
+        for (column, value) in result_row.iter_mut().enumerate() {
+            *value = (0..4).map(|k| left[row][k] * right[k][column]).sum();
+        }
+    }
+
+    result
+}
+
+fn transpose(matrix: &Matrix4) -> Matrix4 {
+    let mut result = [[0.0; 4]; 4];
+
+    for (row, result_row) in result.iter_mut().enumerate() {
+        for (column, value) in result_row.iter_mut().enumerate() {
+            *value = matrix[column][row];
+        }
+    }
+
+    result
+}
+
+/// inverts the matrix with the Gauss-Jordan elimination, None is returned if the matrix is
+/// singular
+fn invert(matrix: &Matrix4) -> Option<Matrix4> {
+    let mut left = *matrix;
+    let mut right = IDENTITY;
+
+    for column in 0..4 {
+        // the row with the biggest value is used as the pivot to limit the rounding errors
+        let pivot =
+            (column..4).max_by(|a, b| left[*a][column].abs().total_cmp(&left[*b][column].abs()))?;
+
+        if left[pivot][column].abs() < 1e-12 {
+            return None;
+        }
+
+        left.swap(column, pivot);
+        right.swap(column, pivot);
+
+        let pivot_value = left[column][column];
+
+        for k in 0..4 {
+            left[column][k] /= pivot_value;
+            right[column][k] /= pivot_value;
+        }
+
+        for row in 0..4 {
+            if row == column {
+                continue;
+            }
+
+            let factor = left[row][column];
+
+            for k in 0..4 {
+                left[row][k] -= factor * left[column][k];
+                right[row][k] -= factor * right[column][k];
+            }
+        }
+    }
+
+    Some(right)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// a transform with every kind of step (and a non uniform scale)
+    fn transform() -> Transform {
+        Transform::scale(Vec3::new(2.0, 0.5, 3.0))
+            .then(&Transform::rotate(Vec3::new(1.0, 2.0, -1.0), 0.7))
+            .then(&Transform::translate(Vec3::new(4.0, -1.0, 2.5)))
+    }
+
+    fn assert_close(found: f64, expected: f64) {
+        assert!(
+            (found - expected).abs() < 1e-9,
+            "expected {}, found {}",
+            expected,
+            found
+        );
+    }
+
+    fn assert_vectors_close(found: &Vec3, expected: &Vec3) {
+        for axis in 0..3 {
+            assert_close(found.get_axis(axis), expected.get_axis(axis));
+        }
+    }
+
+    #[test]
+    fn inverse_undoes_the_transform() {
+        let transform = transform();
+        let point = Vec3::new(1.0, -2.0, 3.0);
+
+        assert_vectors_close(
+            &transform
+                .get_inverse()
+                .transform_point(&transform.transform_point(&point)),
+            &point,
+        );
+
+        let product = multiply(transform.get_matrix(), &transform.inverse);
+
+        for (row, identity_row) in product.iter().zip(IDENTITY) {
+            for (value, identity_value) in row.iter().zip(identity_row) {
+                assert_close(*value, identity_value);
+            }
+        }
+    }
+
+    #[test]
+    fn from_matrix_inverts_the_matrix() {
+        let transform = transform();
+        let from_matrix =
+            Transform::from_matrix(*transform.get_matrix()).expect("the matrix can be inverted");
+
+        for (row, expected_row) in from_matrix.inverse.iter().zip(transform.inverse) {
+            for (value, expected) in row.iter().zip(expected_row) {
+                assert_close(*value, expected);
+            }
+        }
+    }
+
+    #[test]
+    fn singular_matrices_are_rejected() {
+        let mut matrix = IDENTITY;
+        matrix[2] = [1.0, 2.0, 0.0, 0.0];
+
+        assert!(Transform::from_matrix(matrix).is_none());
+        assert!(Transform::from_matrix([[0.0; 4]; 4]).is_none());
+    }
+
+    #[test]
+    fn transformed_normals_stay_perpendicular() {
+        let transform = transform();
+        let normal = Vec3::new(1.0, 1.0, 0.0);
+
+        for tangent in [Vec3::new(1.0, -1.0, 0.0), Vec3::new(0.0, 0.0, 1.0)] {
+            assert_close(
+                transform
+                    .transform_normal(&normal)
+                    .dot_product(&transform.transform_vector(&tangent)),
+                0.0,
+            );
+        }
+    }
+
+    #[test]
+    fn area_stretch_follows_the_surface() {
+        let scale = Transform::scale(Vec3::new(2.0, 3.0, 4.0))
+            .then(&Transform::rotate_y(1.0))
+            .then(&Transform::translate(Vec3::new(1.0, 2.0, 3.0)));
+
+        // a surface facing an axis is stretched along the other two
+        assert_close(scale.get_area_stretch(&Vec3::new(1.0, 0.0, 0.0)), 12.0);
+        assert_close(scale.get_area_stretch(&Vec3::new(0.0, 1.0, 0.0)), 8.0);
+        assert_close(scale.get_area_stretch(&Vec3::new(0.0, 0.0, 1.0)), 6.0);
+
+        let uniform = Transform::scale(Vec3::new(3.0, 3.0, 3.0)).then(&Transform::rotate_x(0.3));
+
+        assert_close(uniform.get_area_scale(), 9.0);
+        assert_close(
+            uniform.get_area_stretch(Vec3::new(1.0, 2.0, 3.0).make_unit()),
+            9.0,
+        );
+    }
+
+    #[test]
+    fn from_axis_brings_the_z_axis_onto_the_axis() {
+        let origin = Vec3::new(1.0, 2.0, 3.0);
+        let axis = Vec3::new(0.0, -2.0, 0.0);
+        let transform = Transform::from_axis(origin, axis);
+
+        assert_vectors_close(
+            &transform.transform_point(&Vec3::new(0.0, 0.0, 2.0)),
+            &Vec3::new(1.0, 0.0, 3.0),
+        );
+        assert_close(transform.get_area_scale(), 1.0);
+    }
+
+    #[test]
+    fn bounding_boxes_stay_infinite() {
+        let transformed = transform().transform_bounding_box(&BoundingBox::infinite());
+
+        assert!(!transformed.is_finite());
+        for axis in 0..3 {
+            assert_eq!(transformed.get_min().get_axis(axis), f64::NEG_INFINITY);
+            assert_eq!(transformed.get_max().get_axis(axis), f64::INFINITY);
+        }
+    }
+
+    #[test]
+    fn bounding_boxes_contain_the_transformed_box() {
+        let rotation = Transform::rotate_z(45.0_f64.to_radians());
+        let transformed = rotation.transform_bounding_box(&BoundingBox::new(
+            Vec3::new(-1.0, -1.0, -1.0),
+            Vec3::new(1.0, 1.0, 1.0),
+        ));
+        let half_diagonal = 2.0_f64.sqrt();
+
+        assert_vectors_close(
+            transformed.get_min(),
+            &Vec3::new(-half_diagonal, -half_diagonal, -1.0),
+        );
+        assert_vectors_close(
+            transformed.get_max(),
+            &Vec3::new(half_diagonal, half_diagonal, 1.0),
+        );
+    }
+}
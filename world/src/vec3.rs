@@ -11,12 +11,14 @@ use std::ops;
 /// create a new Vec3 with all zero
 ///
 ///```no_run
+/// # use world::Vec3;
 /// let vector = Vec3::new(0.0, 0.0, 0.0);
 ///```
 ///
 /// Execute addition between two vectors
 ///
 ///```no_run
+/// # use world::Vec3;
 /// let vector1 = Vec3::new(3.0, 1.0, 2.0);
 /// let vector2 = Vec3::new(1.0, 2.0, 3.0);
 ///
@@ -71,6 +73,15 @@ impl Vec3 {
         &self.z
     }
 
+    /// retrieves the coordinate along the given axis (0 = x, 1 = y, 2 = z)
+    pub fn get_axis(&self, axis: usize) -> f64 {
+        match axis {
+            0 => self.x,
+            1 => self.y,
+            _ => self.z,
+        }
+    }
+
     /// sets the x coordinate
     pub fn set_x(&mut self, new_x: f64) -> &mut Self {
         self.x = new_x;
@@ -140,6 +151,18 @@ impl Vec3 {
     pub fn get_inverse(&self) -> Self {
         (*self) * -1.0
     }
+
+    /// retrieves a unit vector perpendicular to the current one (any of them)
+    pub fn get_perpendicular(&self) -> Self {
+        // crossing with the axis least aligned to the vector is always safe
+        let helper = if self.x.abs() > 0.9 * self.get_length() {
+            Vec3::new(0.0, 1.0, 0.0)
+        } else {
+            Vec3::new(1.0, 0.0, 0.0)
+        };
+
+        *self.cross_product(&helper).make_unit()
+    }
 }
 
 impl ops::Mul<f64> for Vec3 {